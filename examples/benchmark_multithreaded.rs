@@ -5,7 +5,7 @@
 
 use anyhow::Result;
 use game_of_life_reverse::{
-    config::{Settings, SolverBackend, BoundaryCondition},
+    config::{Settings, SolverBackend, BoundaryCondition, PortfolioMember},
     reverse::ReverseProblem,
     game_of_life::io::parse_grid_from_string,
 };
@@ -52,11 +52,11 @@ fn main() -> Result<()> {
 
     let mut results = Vec::new();
 
-    for (backend, thread_count) in configs {
+    for (backend, thread_count) in configs.clone() {
         println!("Testing {:?} with {} thread{}:", 
                  backend, thread_count, if thread_count == 1 { "" } else { "s" });
         
-        match run_benchmark(backend, thread_count) {
+        match run_benchmark(backend.clone(), thread_count) {
             Ok(result) => {
                 println!("  ✅ Completed in {:.2}s (found {} solution{})",
                          result.run_time.as_secs_f64(),
@@ -91,13 +91,40 @@ fn main() -> Result<()> {
         }
     }
 
+    // Race the same configurations against each other
+    println!("\n=== Portfolio Race ===");
+    let members = configs.into_iter()
+        .map(|(backend, thread_count)| PortfolioMember::new(backend).with_threads(thread_count))
+        .collect();
+    match run_portfolio(members) {
+        Ok(statistics) => {
+            for member in statistics.portfolio {
+                println!("{}", member);
+            }
+        }
+        Err(e) => println!("❌ Failed: {}", e),
+    }
+
     Ok(())
 }
 
+fn run_portfolio(members: Vec<PortfolioMember>) -> Result<game_of_life_reverse::sat::encoder::EncodingStatistics> {
+    let mut settings = Settings::default();
+    settings.solver.backend = SolverBackend::Portfolio;
+    settings.solver.portfolio = members;
+    settings.solver.timeout_seconds = 30;
+    settings.simulation.generations = 3;
+
+    let target_grid = parse_grid_from_string("010\n010\n010", BoundaryCondition::Dead)?;
+    let mut problem = ReverseProblem::with_target_grid(settings, target_grid)?;
+    problem.solve()?;
+    Ok(problem.encoding_statistics())
+}
+
 fn run_benchmark(backend: SolverBackend, thread_count: usize) -> Result<BenchmarkResult> {
     // Create a simple test settings
     let mut settings = Settings::default();
-    settings.solver.backend = backend;
+    settings.solver.backend = backend.clone();
     settings.solver.num_threads = Some(thread_count);
     settings.solver.enable_preprocessing = true;
     settings.solver.verbosity = 0;
@@ -1,9 +1,9 @@
 //! Demonstration of ParKissat-RS integration
 //! 
 //! This example shows how to use both CaDiCaL and ParKissat-RS solvers
-//! through the unified interface.
+//! through the common `SatBackend` interface.
 
-use game_of_life_reverse::sat::{UnifiedSatSolver, SolverOptions, SolverSolution};
+use game_of_life_reverse::sat::{create_solver, SolverOptions, SolverSolution};
 use game_of_life_reverse::sat::constraints::Clause;
 use game_of_life_reverse::config::SolverBackend;
 use std::time::Duration;
@@ -29,11 +29,12 @@ fn test_solver_backend(backend: SolverBackend) -> Result<(), Box<dyn std::error:
         verbosity: 0,
         timeout: Some(Duration::from_secs(10)),
         random_seed: Some(42),
+        cube_variables: 6,
     };
     
     // Test 1: Simple satisfiable problem
     println!("  Test 1: Simple satisfiable problem (x1)");
-    let mut solver1 = UnifiedSatSolver::new(backend)?;
+    let mut solver1 = create_solver(&backend)?;
     solver1.configure(&options)?;
     solver1.add_clause(&Clause::new(vec![1]))?;  // x1
     
@@ -54,7 +55,7 @@ fn test_solver_backend(backend: SolverBackend) -> Result<(), Box<dyn std::error:
     
     // Test 2: Unsatisfiable problem (create new solver instance)
     println!("  Test 2: Unsatisfiable problem (x1 ∧ ¬x1)");
-    let mut solver2 = UnifiedSatSolver::new(backend)?;
+    let mut solver2 = create_solver(&backend)?;
     solver2.configure(&options)?;
     solver2.add_clause(&Clause::new(vec![1]))?;   // x1
     solver2.add_clause(&Clause::new(vec![-1]))?;  // ¬x1
@@ -72,7 +73,7 @@ fn test_solver_backend(backend: SolverBackend) -> Result<(), Box<dyn std::error:
     
     // Test 3: Multiple solutions (create new solver instance)
     println!("  Test 3: Multiple solutions (x1 ∨ x2)");
-    let mut solver3 = UnifiedSatSolver::new(backend)?;
+    let mut solver3 = create_solver(&backend)?;
     solver3.configure(&options)?;
     solver3.add_clause(&Clause::new(vec![1, 2]))?;  // x1 ∨ x2
     
@@ -84,7 +85,7 @@ fn test_solver_backend(backend: SolverBackend) -> Result<(), Box<dyn std::error:
     while solutions.len() < MAX_SOLUTIONS && iteration < 10 {
         iteration += 1;
         
-        let mut solver = UnifiedSatSolver::new(backend)?;
+        let mut solver = create_solver(&backend)?;
         solver.configure(&options)?;
         solver.add_clause(&Clause::new(vec![1, 2]))?;  // x1 ∨ x2
         
@@ -123,7 +124,7 @@ fn test_solver_backend(backend: SolverBackend) -> Result<(), Box<dyn std::error:
     }
     
     // Test 4: Get statistics (create new solver instance)
-    let mut solver4 = UnifiedSatSolver::new(backend)?;
+    let mut solver4 = create_solver(&backend)?;
     solver4.configure(&options)?;
     solver4.add_clause(&Clause::new(vec![1, 2]))?;  // x1 ∨ x2
     solver4.add_clause(&Clause::new(vec![-1, 2]))?; // ¬x1 ∨ x2
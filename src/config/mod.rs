@@ -4,5 +4,6 @@ pub mod settings;
 
 pub use settings::{
     Settings, SimulationConfig, SolverConfig, InputConfig, OutputConfig, EncodingConfig,
-    BoundaryCondition, OutputFormat, CliOverrides, SolverBackend
+    BoundaryCondition, OutputFormat, CliOverrides, SolverBackend, TransitionEncoding, Objective,
+    PortfolioMember, CubeConfig, ImageImportConfig, ImageExportConfig
 };
\ No newline at end of file
@@ -1,5 +1,6 @@
 //! Configuration settings for the reverse Game of Life solver
 
+use crate::game_of_life::{RgbColor, Rule};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -17,6 +18,15 @@ pub struct Settings {
 pub struct SimulationConfig {
     pub generations: usize,
     pub boundary_condition: BoundaryCondition,
+    /// Life-like rule in B/S notation (defaults to Conway's B3/S23)
+    #[serde(default)]
+    pub rule: Rule,
+    /// Extra cells per side around the target that predecessors may use (dead boundary only)
+    #[serde(default)]
+    pub margin: usize,
+    /// Leave cells outside the target window free at the final generation instead of dead
+    #[serde(default)]
+    pub free_margin: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -35,19 +45,210 @@ pub struct SolverConfig {
     pub enable_preprocessing: bool,
     pub verbosity: u32,
     pub backend: SolverBackend,
+    #[serde(default)]
+    pub random_seed: Option<u64>,
+    /// Generation whose cells must differ between enumerated solutions (0 = predecessor)
+    #[serde(default)]
+    pub projection_generation: usize,
+    /// Search for predecessors with the smallest or largest population
+    #[serde(default)]
+    pub optimize: Objective,
+    /// Configurations raced by the portfolio backend (empty = `PortfolioMember::default_portfolio`)
+    #[serde(default)]
+    pub portfolio: Vec<PortfolioMember>,
+    /// How the cube-and-conquer backend splits the problem
+    #[serde(default)]
+    pub cubes: CubeConfig,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+/// Splitting used by `SolverBackend::CubeAndConquer`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CubeConfig {
+    /// Cells branched on, giving 2^cube_variables cubes
+    #[serde(default = "default_cube_variables")]
+    pub cube_variables: usize,
+    /// Generation whose central cells are branched on
+    #[serde(default)]
+    pub generation: usize,
+}
+
+fn default_cube_variables() -> usize {
+    6
+}
+
+impl Default for CubeConfig {
+    fn default() -> Self {
+        Self {
+            cube_variables: default_cube_variables(),
+            generation: 0,
+        }
+    }
+}
+
+/// One configuration raced by `SolverBackend::Portfolio`
+///
+/// Unset fields keep the values from the rest of the settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortfolioMember {
+    pub backend: SolverBackend,
+    #[serde(default)]
+    pub random_seed: Option<u64>,
+    #[serde(default)]
+    pub num_threads: Option<usize>,
+    #[serde(default)]
+    pub transition: Option<TransitionEncoding>,
+}
+
+impl PortfolioMember {
+    /// A member running `backend` with the shared settings
+    pub fn new(backend: SolverBackend) -> Self {
+        Self { backend, random_seed: None, num_threads: None, transition: None }
+    }
+
+    /// Use the given random seed
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.random_seed = Some(seed);
+        self
+    }
+
+    /// Use the given number of solver threads
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.num_threads = Some(threads);
+        self
+    }
+
+    /// Use the given transition encoding
+    pub fn with_transition(mut self, transition: TransitionEncoding) -> Self {
+        self.transition = Some(transition);
+        self
+    }
+
+    /// Members raced when `solver.portfolio` is empty
+    ///
+    /// Three differently seeded or encoded CaDiCaL runs next to a ParKissat run
+    /// with the configured threads, all of which work with any rule.
+    pub fn default_portfolio() -> Vec<Self> {
+        vec![
+            Self::new(SolverBackend::Cadical),
+            Self::new(SolverBackend::Cadical).with_seed(1),
+            Self::new(SolverBackend::Cadical).with_seed(2).with_transition(TransitionEncoding::Minimized),
+            Self::new(SolverBackend::Parkissat),
+        ]
+    }
+
+    /// The settings this member solves with
+    pub fn apply(&self, settings: &Settings) -> Settings {
+        let mut settings = settings.clone();
+        settings.solver.backend = self.backend.clone();
+        settings.solver.portfolio = Vec::new();
+        if let Some(seed) = self.random_seed {
+            settings.solver.random_seed = Some(seed);
+        }
+        if let Some(threads) = self.num_threads {
+            settings.solver.num_threads = Some(threads);
+        }
+        if let Some(transition) = self.transition {
+            settings.encoding.transition = transition;
+        }
+        settings
+    }
+}
+
+impl std::fmt::Display for PortfolioMember {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.backend {
+            SolverBackend::External { command, .. } => write!(f, "external {}", command)?,
+            SolverBackend::Custom(name) => write!(f, "custom {}", name)?,
+            backend => write!(f, "{:?}", backend)?,
+        }
+        if let Some(seed) = self.random_seed {
+            write!(f, " seed={}", seed)?;
+        }
+        if let Some(threads) = self.num_threads {
+            write!(f, " threads={}", threads)?;
+        }
+        if let Some(transition) = self.transition {
+            write!(f, " transition={:?}", transition)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Objective {
+    /// Report any predecessors
+    #[default]
+    None,
+    /// Report predecessors with as few live cells as possible
+    MinPopulation,
+    /// Report predecessors with as many live cells as possible
+    MaxPopulation,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum SolverBackend {
     Cadical,
     Parkissat,
+    /// Simulates every predecessor candidate; an oracle for tiny grids
+    BruteForce,
+    /// A backend registered at runtime through `sat::register_backend`
+    Custom(String),
+    /// A solver binary that reads DIMACS CNF and prints `s`/`v` lines
+    ///
+    /// An argument containing `{cnf}` has it replaced by the path of a
+    /// temporary CNF file; otherwise the CNF is written to stdin.
+    External {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// Races the configurations in `solver.portfolio`; the first to finish wins
+    Portfolio,
+    /// Splits the problem into cubes solved in parallel by CaDiCaL instances
+    CubeAndConquer,
 }
 
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InputConfig {
     pub target_state_file: PathBuf,
+    /// How image targets are turned into cells
+    #[serde(default)]
+    pub image: ImageImportConfig,
+}
+
+/// Conversion of PBM and PGM targets to grids
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageImportConfig {
+    /// Darkness from 0 (white) to 1 (black) at which a cell is alive
+    #[serde(default = "default_image_threshold")]
+    pub threshold: f64,
+    /// Side of the square of pixels averaged into each cell
+    #[serde(default = "default_image_downscale")]
+    pub downscale: usize,
+    /// Make light pixels alive instead of dark ones
+    #[serde(default)]
+    pub invert: bool,
+}
+
+fn default_image_threshold() -> f64 {
+    0.5
+}
+
+fn default_image_downscale() -> usize {
+    1
+}
+
+impl Default for ImageImportConfig {
+    fn default() -> Self {
+        Self {
+            threshold: default_image_threshold(),
+            downscale: default_image_downscale(),
+            invert: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +256,61 @@ pub struct OutputConfig {
     pub format: OutputFormat,
     pub save_intermediate: bool,
     pub output_directory: PathBuf,
+    /// How image frames and animations are drawn
+    #[serde(default)]
+    pub image: ImageExportConfig,
+}
+
+/// Drawing of grids as image frames and GIF animations
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageExportConfig {
+    /// Side of the square of pixels drawn for each cell
+    #[serde(default = "default_cell_size")]
+    pub cell_size: usize,
+    /// Color of live cells in PNG frames and animations
+    #[serde(default = "default_alive_color")]
+    pub alive_color: RgbColor,
+    /// Color of dead cells in PNG frames and animations
+    #[serde(default = "default_dead_color")]
+    pub dead_color: RgbColor,
+    /// Time each generation of an animation is shown, in milliseconds
+    #[serde(default = "default_frame_delay_ms")]
+    pub frame_delay_ms: u64,
+    /// Time the final generation is shown before the animation loops, in milliseconds
+    #[serde(default = "default_final_hold_ms")]
+    pub final_hold_ms: u64,
+}
+
+fn default_cell_size() -> usize {
+    8
+}
+
+fn default_alive_color() -> RgbColor {
+    RgbColor::BLACK
+}
+
+fn default_dead_color() -> RgbColor {
+    RgbColor::WHITE
+}
+
+fn default_frame_delay_ms() -> u64 {
+    500
+}
+
+fn default_final_hold_ms() -> u64 {
+    2000
+}
+
+impl Default for ImageExportConfig {
+    fn default() -> Self {
+        Self {
+            cell_size: default_cell_size(),
+            alive_color: default_alive_color(),
+            dead_color: default_dead_color(),
+            frame_delay_ms: default_frame_delay_ms(),
+            final_hold_ms: default_final_hold_ms(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,11 +319,55 @@ pub enum OutputFormat {
     Text,
     Json,
     Visual,
+    /// Predecessors in Golly's RLE format, ready to paste into Golly
+    Rle,
+    /// Predecessors in the LifeWiki plaintext format
+    Cells,
+    /// Predecessors as Life 1.06 coordinate lists
+    Life106,
+    /// Every generation from predecessor to target as PBM frames
+    Pbm,
+    /// Every generation from predecessor to target as PNG frames
+    Png,
+    /// Every generation from predecessor to target as one looping GIF animation
+    Gif,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EncodingConfig {
     pub symmetry_breaking: bool,
+    /// How neighbor counts are encoded in the transition constraints
+    #[serde(default)]
+    pub transition: TransitionEncoding,
+    /// Fewest live cells the predecessor may have
+    #[serde(default)]
+    pub min_population: Option<usize>,
+    /// Most live cells the predecessor may have
+    #[serde(default)]
+    pub max_population: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionEncoding {
+    /// One clause per neighborhood configuration, no auxiliary variables
+    #[default]
+    Direct,
+    /// Totalizer tree producing a unary neighbor count
+    Totalizer,
+    /// Sequential counter producing a unary neighbor count
+    SequentialCounter,
+    /// Full-adder network producing a binary neighbor count
+    Adder,
+    /// Prime-implicant CNF of the 3x3 transition, minimized once per rule
+    Minimized,
+}
+
+impl TransitionEncoding {
+    /// Whether the encoding works on neighbor counts, and so needs a totalistic rule
+    pub fn counts_neighbors(&self) -> bool {
+        matches!(self, Self::Totalizer | Self::SequentialCounter | Self::Adder)
+    }
 }
 
 impl Default for Settings {
@@ -76,6 +376,9 @@ impl Default for Settings {
             simulation: SimulationConfig {
                 generations: 5,
                 boundary_condition: BoundaryCondition::Dead,
+                rule: Rule::conway(),
+                margin: 0,
+                free_margin: false,
             },
             solver: SolverConfig {
                 max_solutions: 10,
@@ -84,17 +387,27 @@ impl Default for Settings {
                 enable_preprocessing: true,
                 verbosity: 0,
                 backend: SolverBackend::Parkissat,
+                random_seed: None,
+                projection_generation: 0,
+                optimize: Objective::None,
+                portfolio: Vec::new(),
+                cubes: CubeConfig::default(),
             },
             input: InputConfig {
                 target_state_file: PathBuf::from("input/target_states/example.txt"),
+                image: ImageImportConfig::default(),
             },
             output: OutputConfig {
                 format: OutputFormat::Text,
                 save_intermediate: false,
                 output_directory: PathBuf::from("output/solutions"),
+                image: ImageExportConfig::default(),
             },
             encoding: EncodingConfig {
                 symmetry_breaking: false,
+                transition: TransitionEncoding::Direct,
+                min_population: None,
+                max_population: None,
             },
         }
     }
@@ -138,6 +451,53 @@ impl Settings {
         if self.solver.max_solutions == 0 {
             anyhow::bail!("Maximum solutions must be positive");
         }
+
+        if self.solver.projection_generation > self.simulation.generations {
+            anyhow::bail!(
+                "Projection generation {} is beyond the last generation {}",
+                self.solver.projection_generation,
+                self.simulation.generations
+            );
+        }
+        
+        self.validate_rule()?;
+        
+        if self.solver.cubes.cube_variables == 0 || self.solver.cubes.cube_variables > 16 {
+            anyhow::bail!("Cube-and-conquer splits on 1 to 16 variables, not {}", self.solver.cubes.cube_variables);
+        }
+        if self.solver.cubes.generation > self.simulation.generations {
+            anyhow::bail!(
+                "Cube generation {} is beyond the last generation {}",
+                self.solver.cubes.generation,
+                self.simulation.generations
+            );
+        }
+        
+        for member in &self.solver.portfolio {
+            if member.backend == SolverBackend::Portfolio {
+                anyhow::bail!("A portfolio cannot contain another portfolio");
+            }
+        }
+        
+        if !(0.0..=1.0).contains(&self.input.image.threshold) {
+            anyhow::bail!("Image threshold must be between 0 and 1, not {}", self.input.image.threshold);
+        }
+        if self.input.image.downscale == 0 {
+            anyhow::bail!("Image downscale factor must be positive");
+        }
+        if self.output.image.cell_size == 0 {
+            anyhow::bail!("Image cell size must be positive");
+        }
+        
+        if self.simulation.margin > 0 && self.simulation.boundary_condition != BoundaryCondition::Dead {
+            anyhow::bail!("A margin requires the dead boundary condition");
+        }
+        
+        if let (Some(min), Some(max)) = (self.encoding.min_population, self.encoding.max_population) {
+            if min > max {
+                anyhow::bail!("Minimum population {} is above the maximum population {}", min, max);
+            }
+        }
         
         if !self.input.target_state_file.exists() {
             anyhow::bail!("Target state file does not exist: {}", self.input.target_state_file.display());
@@ -146,6 +506,30 @@ impl Settings {
         Ok(())
     }
 
+    /// Check only the settings that depend on `simulation.rule`
+    pub fn validate_rule(&self) -> Result<()> {
+        let rule = &self.simulation.rule;
+        if self.encoding.transition.counts_neighbors() && !rule.is_totalistic() {
+            anyhow::bail!(
+                "Rule {} depends on neighbor positions; use the direct or minimized transition encoding",
+                rule
+            );
+        }
+        
+        for member in &self.solver.portfolio {
+            if member.transition.is_some_and(|transition| transition.counts_neighbors()) && !rule.is_totalistic() {
+                anyhow::bail!("Portfolio member '{}' counts neighbors, but rule {} depends on their positions",
+                             member, rule);
+            }
+        }
+        
+        if self.simulation.margin > 0 && rule.next_state(false, 0) {
+            anyhow::bail!("A margin cannot be used with rule {}, which births cells with no live neighbors", rule);
+        }
+        
+        Ok(())
+    }
+
     /// Merge settings with command line overrides
     pub fn merge_with_cli(&mut self, cli_overrides: &CliOverrides) {
         if let Some(generations) = cli_overrides.generations {
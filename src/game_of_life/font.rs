@@ -0,0 +1,201 @@
+//! Built-in bitmap fonts for rendering text as target grids
+//!
+//! Glyphs are rows of `#` (alive) and `.` (dead). Both fonts cover the
+//! capital letters, the digits, the space and a little punctuation;
+//! lowercase letters are drawn as capitals.
+
+use anyhow::Result;
+use std::fmt;
+use std::str::FromStr;
+
+/// A built-in monospaced bitmap font
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Font {
+    /// 3x5 glyphs, the smallest legible size
+    Small,
+    /// 5x7 glyphs
+    #[default]
+    Standard,
+}
+
+impl Font {
+    /// Glyph width in cells
+    pub fn width(self) -> usize {
+        match self {
+            Self::Small => 3,
+            Self::Standard => 5,
+        }
+    }
+
+    /// Glyph height in cells
+    pub fn height(self) -> usize {
+        match self {
+            Self::Small => 5,
+            Self::Standard => 7,
+        }
+    }
+
+    /// Rows of the glyph for `ch`, or `None` if the font does not have it
+    pub fn glyph(self, ch: char) -> Option<&'static [&'static str]> {
+        let table = match self {
+            Self::Small => SMALL_GLYPHS,
+            Self::Standard => STANDARD_GLYPHS,
+        };
+        let ch = ch.to_ascii_uppercase();
+        table.iter().find(|(glyph_char, _)| *glyph_char == ch).map(|(_, rows)| *rows)
+    }
+
+    /// Characters the font can draw, lowercase letters aside
+    pub fn characters(self) -> impl Iterator<Item = char> {
+        let table = match self {
+            Self::Small => SMALL_GLYPHS,
+            Self::Standard => STANDARD_GLYPHS,
+        };
+        table.iter().map(|(ch, _)| *ch)
+    }
+}
+
+impl FromStr for Font {
+    type Err = anyhow::Error;
+
+    /// Parse `3x5` (or `small`) and `5x7` (or `standard`)
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "3x5" | "small" => Ok(Self::Small),
+            "5x7" | "standard" => Ok(Self::Standard),
+            other => anyhow::bail!("Unknown font '{}'; use 3x5 or 5x7", other),
+        }
+    }
+}
+
+impl fmt::Display for Font {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.width(), self.height())
+    }
+}
+
+type GlyphTable = &'static [(char, &'static [&'static str])];
+
+const SMALL_GLYPHS: GlyphTable = &[
+    ('A', &[".#.", "#.#", "###", "#.#", "#.#"]),
+    ('B', &["##.", "#.#", "##.", "#.#", "##."]),
+    ('C', &[".##", "#..", "#..", "#..", ".##"]),
+    ('D', &["##.", "#.#", "#.#", "#.#", "##."]),
+    ('E', &["###", "#..", "##.", "#..", "###"]),
+    ('F', &["###", "#..", "##.", "#..", "#.."]),
+    ('G', &[".##", "#..", "#.#", "#.#", ".##"]),
+    ('H', &["#.#", "#.#", "###", "#.#", "#.#"]),
+    ('I', &["###", ".#.", ".#.", ".#.", "###"]),
+    ('J', &["..#", "..#", "..#", "#.#", ".#."]),
+    ('K', &["#.#", "#.#", "##.", "#.#", "#.#"]),
+    ('L', &["#..", "#..", "#..", "#..", "###"]),
+    ('M', &["#.#", "###", "###", "#.#", "#.#"]),
+    ('N', &["##.", "#.#", "#.#", "#.#", "#.#"]),
+    ('O', &[".#.", "#.#", "#.#", "#.#", ".#."]),
+    ('P', &["##.", "#.#", "##.", "#..", "#.."]),
+    ('Q', &[".#.", "#.#", "#.#", "##.", ".##"]),
+    ('R', &["##.", "#.#", "##.", "#.#", "#.#"]),
+    ('S', &[".##", "#..", ".#.", "..#", "##."]),
+    ('T', &["###", ".#.", ".#.", ".#.", ".#."]),
+    ('U', &["#.#", "#.#", "#.#", "#.#", "###"]),
+    ('V', &["#.#", "#.#", "#.#", "#.#", ".#."]),
+    ('W', &["#.#", "#.#", "###", "###", "#.#"]),
+    ('X', &["#.#", "#.#", ".#.", "#.#", "#.#"]),
+    ('Y', &["#.#", "#.#", ".#.", ".#.", ".#."]),
+    ('Z', &["###", "..#", ".#.", "#..", "###"]),
+    ('0', &["###", "#.#", "#.#", "#.#", "###"]),
+    ('1', &[".#.", "##.", ".#.", ".#.", "###"]),
+    ('2', &["##.", "..#", ".#.", "#..", "###"]),
+    ('3', &["##.", "..#", ".#.", "..#", "##."]),
+    ('4', &["#.#", "#.#", "###", "..#", "..#"]),
+    ('5', &["###", "#..", "##.", "..#", "##."]),
+    ('6', &[".##", "#..", "###", "#.#", "###"]),
+    ('7', &["###", "..#", ".#.", ".#.", ".#."]),
+    ('8', &["###", "#.#", "###", "#.#", "###"]),
+    ('9', &["###", "#.#", "###", "..#", "##."]),
+    (' ', &["...", "...", "...", "...", "..."]),
+    ('.', &["...", "...", "...", "...", ".#."]),
+    (',', &["...", "...", "...", ".#.", "#.."]),
+    ('!', &[".#.", ".#.", ".#.", "...", ".#."]),
+    ('?', &["##.", "..#", ".#.", "...", ".#."]),
+    ('-', &["...", "...", "###", "...", "..."]),
+    (':', &["...", ".#.", "...", ".#.", "..."]),
+    ('\'', &[".#.", ".#.", "...", "...", "..."]),
+];
+
+const STANDARD_GLYPHS: GlyphTable = &[
+    ('A', &[".###.", "#...#", "#...#", "#####", "#...#", "#...#", "#...#"]),
+    ('B', &["####.", "#...#", "#...#", "####.", "#...#", "#...#", "####."]),
+    ('C', &[".###.", "#...#", "#....", "#....", "#....", "#...#", ".###."]),
+    ('D', &["####.", "#...#", "#...#", "#...#", "#...#", "#...#", "####."]),
+    ('E', &["#####", "#....", "#....", "####.", "#....", "#....", "#####"]),
+    ('F', &["#####", "#....", "#....", "####.", "#....", "#....", "#...."]),
+    ('G', &[".###.", "#...#", "#....", "#.###", "#...#", "#...#", ".####"]),
+    ('H', &["#...#", "#...#", "#...#", "#####", "#...#", "#...#", "#...#"]),
+    ('I', &[".###.", "..#..", "..#..", "..#..", "..#..", "..#..", ".###."]),
+    ('J', &["..###", "...#.", "...#.", "...#.", "...#.", "#..#.", ".##.."]),
+    ('K', &["#...#", "#..#.", "#.#..", "##...", "#.#..", "#..#.", "#...#"]),
+    ('L', &["#....", "#....", "#....", "#....", "#....", "#....", "#####"]),
+    ('M', &["#...#", "##.##", "#.#.#", "#.#.#", "#...#", "#...#", "#...#"]),
+    ('N', &["#...#", "#...#", "##..#", "#.#.#", "#..##", "#...#", "#...#"]),
+    ('O', &[".###.", "#...#", "#...#", "#...#", "#...#", "#...#", ".###."]),
+    ('P', &["####.", "#...#", "#...#", "####.", "#....", "#....", "#...."]),
+    ('Q', &[".###.", "#...#", "#...#", "#...#", "#.#.#", "#..#.", ".##.#"]),
+    ('R', &["####.", "#...#", "#...#", "####.", "#.#..", "#..#.", "#...#"]),
+    ('S', &[".####", "#....", "#....", ".###.", "....#", "....#", "####."]),
+    ('T', &["#####", "..#..", "..#..", "..#..", "..#..", "..#..", "..#.."]),
+    ('U', &["#...#", "#...#", "#...#", "#...#", "#...#", "#...#", ".###."]),
+    ('V', &["#...#", "#...#", "#...#", "#...#", "#...#", ".#.#.", "..#.."]),
+    ('W', &["#...#", "#...#", "#...#", "#.#.#", "#.#.#", "#.#.#", ".#.#."]),
+    ('X', &["#...#", "#...#", ".#.#.", "..#..", ".#.#.", "#...#", "#...#"]),
+    ('Y', &["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#..", "..#.."]),
+    ('Z', &["#####", "....#", "...#.", "..#..", ".#...", "#....", "#####"]),
+    ('0', &[".###.", "#...#", "#..##", "#.#.#", "##..#", "#...#", ".###."]),
+    ('1', &["..#..", ".##..", "..#..", "..#..", "..#..", "..#..", ".###."]),
+    ('2', &[".###.", "#...#", "....#", "...#.", "..#..", ".#...", "#####"]),
+    ('3', &["#####", "...#.", "..#..", "...#.", "....#", "#...#", ".###."]),
+    ('4', &["...#.", "..##.", ".#.#.", "#..#.", "#####", "...#.", "...#."]),
+    ('5', &["#####", "#....", "####.", "....#", "....#", "#...#", ".###."]),
+    ('6', &["..##.", ".#...", "#....", "####.", "#...#", "#...#", ".###."]),
+    ('7', &["#####", "....#", "...#.", "..#..", ".#...", ".#...", ".#..."]),
+    ('8', &[".###.", "#...#", "#...#", ".###.", "#...#", "#...#", ".###."]),
+    ('9', &[".###.", "#...#", "#...#", ".####", "....#", "...#.", ".##.."]),
+    (' ', &[".....", ".....", ".....", ".....", ".....", ".....", "....."]),
+    ('.', &[".....", ".....", ".....", ".....", ".....", ".....", "..#.."]),
+    (',', &[".....", ".....", ".....", ".....", "..#..", "..#..", ".#..."]),
+    ('!', &["..#..", "..#..", "..#..", "..#..", "..#..", ".....", "..#.."]),
+    ('?', &[".###.", "#...#", "....#", "...#.", "..#..", ".....", "..#.."]),
+    ('-', &[".....", ".....", ".....", "#####", ".....", ".....", "....."]),
+    (':', &[".....", "..#..", ".....", ".....", ".....", "..#..", "....."]),
+    ('\'', &["..#..", "..#..", ".....", ".....", ".....", ".....", "....."]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glyphs_fit_their_font() {
+        for font in [Font::Small, Font::Standard] {
+            for ch in font.characters() {
+                let glyph = font.glyph(ch).unwrap();
+                assert_eq!(glyph.len(), font.height(), "'{}' in {}", ch, font);
+                assert!(glyph.iter().all(|row| row.len() == font.width() && row.chars().all(|c| c == '#' || c == '.')),
+                        "'{}' in {}", ch, font);
+            }
+        }
+
+        // Both fonts draw the same characters, lowercase as capitals
+        assert!(Font::Small.characters().eq(Font::Standard.characters()));
+        assert_eq!(Font::Standard.glyph('g'), Font::Standard.glyph('G'));
+        assert!(Font::Small.glyph('~').is_none());
+    }
+
+    #[test]
+    fn test_font_names() {
+        assert_eq!("3x5".parse::<Font>().unwrap(), Font::Small);
+        assert_eq!("Standard".parse::<Font>().unwrap(), Font::Standard);
+        assert!("8x8".parse::<Font>().is_err());
+        assert_eq!(Font::Small.to_string(), "3x5");
+    }
+}
@@ -1,7 +1,9 @@
 //! Grid representation and utilities for Game of Life
 
+use super::font::Font;
+use super::rule::NEIGHBOR_OFFSETS;
 use crate::config::BoundaryCondition;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -55,6 +57,45 @@ impl Grid {
         })
     }
 
+    /// Render `text` with a built-in bitmap font, live cells drawing the letters
+    ///
+    /// Letters are `spacing` columns apart, lines (split at `\n`) are
+    /// `spacing` rows apart, and `padding` dead cells surround the text.
+    /// The grid has the dead boundary condition.
+    pub fn from_text(text: &str, font: Font, spacing: usize, padding: usize) -> Result<Self> {
+        let lines = text.lines()
+            .map(|line| {
+                line.chars()
+                    .map(|ch| font.glyph(ch).with_context(|| format!("The {} font has no glyph for '{}'", font, ch)))
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if lines.iter().all(|glyphs| glyphs.is_empty()) {
+            anyhow::bail!("Text to render is empty");
+        }
+
+        let line_width = |glyphs: usize| (glyphs * (font.width() + spacing)).saturating_sub(spacing);
+        let text_width = lines.iter().map(|glyphs| line_width(glyphs.len())).max().unwrap_or(0);
+        let text_height = lines.len() * (font.height() + spacing) - spacing;
+
+        let mut grid = Self::new(text_width + 2 * padding, text_height + 2 * padding, BoundaryCondition::Dead);
+        for (line_index, glyphs) in lines.iter().enumerate() {
+            let top = padding + line_index * (font.height() + spacing);
+            for (glyph_index, glyph) in glyphs.iter().enumerate() {
+                let left = padding + glyph_index * (font.width() + spacing);
+                for (dy, row) in glyph.iter().enumerate() {
+                    for (dx, pixel) in row.chars().enumerate() {
+                        if pixel == '#' {
+                            grid.set(top + dy, left + dx, true)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(grid)
+    }
+
     /// Convert 2D coordinates to 1D index (reused from existing implementation)
     #[inline]
     pub fn index(&self, row: usize, col: usize) -> usize {
@@ -102,6 +143,21 @@ impl Grid {
         count
     }
 
+    /// Neighborhood configuration of a cell: bit `i` is set when the neighbor
+    /// at `rule::NEIGHBOR_OFFSETS[i]` (clockwise from north) is alive
+    pub fn neighbor_configuration(&self, row: usize, col: usize) -> u8 {
+        NEIGHBOR_OFFSETS
+            .iter()
+            .enumerate()
+            .fold(0, |configuration, (bit, (dr, dc))| {
+                if self.is_neighbor_alive(row as isize + dr, col as isize + dc) {
+                    configuration | (1 << bit)
+                } else {
+                    configuration
+                }
+            })
+    }
+
     /// Check if a neighbor at given coordinates is alive, handling boundary conditions
     fn is_neighbor_alive(&self, row: isize, col: isize) -> bool {
         match self.boundary_condition {
@@ -233,6 +289,23 @@ mod tests {
         assert_eq!(grid.count_neighbors(0, 0), 2); // Only 2 because center is dead
     }
 
+    #[test]
+    fn test_from_text() {
+        let grid = Grid::from_text("Hi", Font::Small, 1, 2).unwrap();
+        // Two 3x5 glyphs one column apart, with two cells of padding
+        assert_eq!((grid.width, grid.height), (11, 9));
+        assert!(grid.get(2, 2) && grid.get(4, 3) && !grid.get(2, 3));
+        assert!(grid.get(2, 6) && grid.get(2, 8) && grid.get(6, 7));
+        assert!((0..grid.width).all(|col| !grid.get(0, col) && !grid.get(8, col)));
+
+        let lines = Grid::from_text("A\nBC", Font::Standard, 2, 0).unwrap();
+        assert_eq!((lines.width, lines.height), (12, 16));
+        assert!(lines.get(9, 0) && !lines.get(8, 0));
+
+        assert!(Grid::from_text("", Font::Small, 1, 1).is_err());
+        assert!(Grid::from_text("ä", Font::Small, 1, 1).is_err());
+    }
+
     #[test]
     fn test_boundary_conditions() {
         let cells = vec![
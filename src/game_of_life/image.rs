@@ -0,0 +1,457 @@
+//! Monochrome images as grids
+//!
+//! Targets are read from netpbm bitmaps (P1, P4) and graymaps (P2, P5), with
+//! dark pixels alive. Grids are drawn as PBM, PGM or PNG images, and whole
+//! evolutions as animated GIFs, in which every cell is a square of pixels.
+
+use super::Grid;
+use crate::config::{BoundaryCondition, ImageExportConfig, ImageImportConfig};
+use crate::utils::gif::{self, Frame};
+use crate::utils::png;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// A color written as `#rrggbb`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct RgbColor(pub [u8; 3]);
+
+impl RgbColor {
+    pub const BLACK: Self = Self([0, 0, 0]);
+    pub const WHITE: Self = Self([255, 255, 255]);
+}
+
+impl FromStr for RgbColor {
+    type Err = anyhow::Error;
+
+    /// Parse `#rrggbb`, with or without the `#`
+    fn from_str(s: &str) -> Result<Self> {
+        let hex = s.trim().trim_start_matches('#');
+        if hex.len() != 6 || !hex.chars().all(|ch| ch.is_ascii_hexdigit()) {
+            anyhow::bail!("Invalid color '{}'; use #rrggbb", s);
+        }
+        let channel = |index: usize| u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16)
+            .with_context(|| format!("Invalid color '{}'", s));
+        Ok(Self([channel(0)?, channel(1)?, channel(2)?]))
+    }
+}
+
+impl TryFrom<String> for RgbColor {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl From<RgbColor> for String {
+    fn from(color: RgbColor) -> Self {
+        color.to_string()
+    }
+}
+
+impl fmt::Display for RgbColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [red, green, blue] = self.0;
+        write!(f, "#{:02x}{:02x}{:02x}", red, green, blue)
+    }
+}
+
+/// Image file formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Netpbm bitmap
+    Pbm,
+    /// Netpbm graymap
+    Pgm,
+    /// Portable Network Graphics, written but not read
+    Png,
+}
+
+impl ImageFormat {
+    /// Format named by a file extension
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "pbm" => Some(Self::Pbm),
+            "pgm" => Some(Self::Pgm),
+            "png" => Some(Self::Png),
+            _ => None,
+        }
+    }
+
+    /// Format named by the extension of `path`
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(Self::from_extension)
+    }
+
+    /// Extension written for the format
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Pbm => "pbm",
+            Self::Pgm => "pgm",
+            Self::Png => "png",
+        }
+    }
+}
+
+/// Parse a netpbm bitmap or graymap into a grid
+///
+/// Each `downscale` x `downscale` block of pixels becomes one cell, alive
+/// when its mean darkness reaches the threshold. Blocks cut off by the right
+/// and bottom edges average the pixels they have.
+pub fn parse_netpbm(data: &[u8], options: &ImageImportConfig, boundary_condition: BoundaryCondition) -> Result<Grid> {
+    if options.downscale == 0 {
+        anyhow::bail!("Image downscale factor must be positive");
+    }
+    if !(0.0..=1.0).contains(&options.threshold) {
+        anyhow::bail!("Image threshold must be between 0 and 1, not {}", options.threshold);
+    }
+    let raster = Raster::parse(data)?;
+    let factor = options.downscale;
+
+    let mut cells = vec![vec![false; raster.width.div_ceil(factor)]; raster.height.div_ceil(factor)];
+    for (row, cell_row) in cells.iter_mut().enumerate() {
+        let rows = row * factor..((row + 1) * factor).min(raster.height);
+        for (col, cell) in cell_row.iter_mut().enumerate() {
+            let cols = col * factor..((col + 1) * factor).min(raster.width);
+            let pixels = rows.len() * cols.len();
+            let total: f64 = rows.clone()
+                .flat_map(|y| cols.clone().map(move |x| (y, x)))
+                .map(|(y, x)| raster.darkness[y * raster.width + x])
+                .sum();
+
+            let darkness = total / pixels as f64;
+            let darkness = if options.invert { 1.0 - darkness } else { darkness };
+            *cell = darkness >= options.threshold;
+        }
+    }
+
+    Grid::from_cells(cells, boundary_condition)
+}
+
+/// Draw a grid as an image in `format`
+pub fn grid_to_image(grid: &Grid, format: ImageFormat, style: &ImageExportConfig) -> Result<Vec<u8>> {
+    let scale = style.cell_size;
+    if scale == 0 {
+        anyhow::bail!("Image cell size must be positive");
+    }
+    let (width, height) = (grid.width * scale, grid.height * scale);
+
+    match format {
+        ImageFormat::Pbm => {
+            let row_bytes = width.div_ceil(8);
+            let mut image = format!("P4\n{} {}\n", width, height).into_bytes();
+            for y in 0..height {
+                let mut row = vec![0u8; row_bytes];
+                for x in (0..width).filter(|&x| grid.get(y / scale, x / scale)) {
+                    row[x / 8] |= 0x80 >> (x % 8);
+                }
+                image.extend_from_slice(&row);
+            }
+            Ok(image)
+        }
+        ImageFormat::Pgm => {
+            let mut image = format!("P5\n{} {}\n255\n", width, height).into_bytes();
+            image.extend(cell_pixels(grid, scale).into_iter().map(|index| if index == 1 { 0 } else { 255 }));
+            Ok(image)
+        }
+        ImageFormat::Png => png::encode_indexed(width, height, &palette(style), &cell_pixels(grid, scale)),
+    }
+}
+
+/// Draw an evolution, one grid per frame, as a looping GIF
+///
+/// Every frame stays up for `frame_delay_ms` except the last, which is
+/// held for `final_hold_ms`.
+pub fn evolution_to_gif(grids: &[Grid], style: &ImageExportConfig) -> Result<Vec<u8>> {
+    let scale = style.cell_size;
+    if scale == 0 {
+        anyhow::bail!("Image cell size must be positive");
+    }
+    let Some(first) = grids.first() else {
+        anyhow::bail!("An animation needs at least one generation");
+    };
+    if grids.iter().any(|grid| (grid.width, grid.height) != (first.width, first.height)) {
+        anyhow::bail!("Every generation of an animation must have the same size");
+    }
+
+    let frames: Vec<Frame> = grids.iter()
+        .enumerate()
+        .map(|(generation, grid)| {
+            let delay = if generation + 1 == grids.len() { style.final_hold_ms } else { style.frame_delay_ms };
+            Frame { pixels: cell_pixels(grid, scale), delay: Duration::from_millis(delay) }
+        })
+        .collect();
+    gif::encode_animation(first.width * scale, first.height * scale, &palette(style), &frames)
+}
+
+/// Palette of colored images: dead cells first, then alive ones
+fn palette(style: &ImageExportConfig) -> [[u8; 3]; 2] {
+    [style.dead_color.0, style.alive_color.0]
+}
+
+/// One palette index per pixel, row by row
+fn cell_pixels(grid: &Grid, scale: usize) -> Vec<u8> {
+    let (width, height) = (grid.width * scale, grid.height * scale);
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| (y, x)))
+        .map(|(y, x)| u8::from(grid.get(y / scale, x / scale)))
+        .collect()
+}
+
+/// Pixels of a netpbm image as darkness from 0 (white) to 1 (black)
+struct Raster {
+    width: usize,
+    height: usize,
+    darkness: Vec<f64>,
+}
+
+impl Raster {
+    fn parse(data: &[u8]) -> Result<Self> {
+        let mut reader = NetpbmReader { data, position: 0 };
+        let (plain, bitmap) = match reader.token()? {
+            b"P1" => (true, true),
+            b"P2" => (true, false),
+            b"P4" => (false, true),
+            b"P5" => (false, false),
+            b"P3" | b"P6" => anyhow::bail!("Color netpbm images are not supported; convert to PBM or PGM"),
+            _ => anyhow::bail!("Not a netpbm image"),
+        };
+
+        let width = reader.number()?;
+        let height = reader.number()?;
+        if width == 0 || height == 0 {
+            anyhow::bail!("Image has no pixels ({}x{})", width, height);
+        }
+        let pixels = width.checked_mul(height)
+            .ok_or_else(|| anyhow::anyhow!("Image is too large ({}x{})", width, height))?;
+        let max_value = if bitmap { 1 } else { reader.number()? };
+        if max_value == 0 || max_value > 65_535 {
+            anyhow::bail!("Graymap maximum value must be between 1 and 65535, not {}", max_value);
+        }
+
+        // Bitmaps store 1 for black, graymaps store brightness
+        let darkness = |sample: usize| -> f64 {
+            let level = sample.min(max_value) as f64 / max_value as f64;
+            if bitmap { level } else { 1.0 - level }
+        };
+
+        let samples: Vec<usize> = if plain {
+            (0..pixels)
+                .map(|_| if bitmap { reader.bit() } else { reader.number() })
+                .collect::<Result<_>>()?
+        } else if bitmap {
+            // Rows are packed eight pixels to a byte, most significant bit first
+            let raster = reader.raster()?;
+            let row_bytes = width.div_ceil(8);
+            if raster.len() < row_bytes * height {
+                anyhow::bail!("Bitmap data ends early: expected {} bytes, found {}", row_bytes * height, raster.len());
+            }
+            (0..pixels)
+                .map(|pixel| {
+                    let (y, x) = (pixel / width, pixel % width);
+                    usize::from((raster[y * row_bytes + x / 8] >> (7 - x % 8)) & 1)
+                })
+                .collect()
+        } else {
+            // Samples above 255 take two bytes, most significant first
+            let raster = reader.raster()?;
+            let sample_bytes = if max_value < 256 { 1 } else { 2 };
+            if raster.len() < pixels * sample_bytes {
+                anyhow::bail!("Graymap data ends early: expected {} bytes, found {}", pixels * sample_bytes, raster.len());
+            }
+            raster.chunks(sample_bytes)
+                .take(pixels)
+                .map(|bytes| bytes.iter().fold(0, |sample, &byte| sample << 8 | usize::from(byte)))
+                .collect()
+        };
+
+        Ok(Self { width, height, darkness: samples.into_iter().map(darkness).collect() })
+    }
+}
+
+/// Cursor over the whitespace-separated header and plain data of a netpbm file
+struct NetpbmReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> NetpbmReader<'a> {
+    /// Skip whitespace and `#` comments
+    fn skip_separators(&mut self) {
+        while let Some(&byte) = self.data.get(self.position) {
+            if byte == b'#' {
+                while self.data.get(self.position).is_some_and(|&byte| byte != b'\n') {
+                    self.position += 1;
+                }
+            } else if byte.is_ascii_whitespace() {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn token(&mut self) -> Result<&'a [u8]> {
+        self.skip_separators();
+        let start = self.position;
+        while self.data.get(self.position).is_some_and(|&byte| !byte.is_ascii_whitespace() && byte != b'#') {
+            self.position += 1;
+        }
+        if start == self.position {
+            anyhow::bail!("Image data ends early");
+        }
+        Ok(&self.data[start..self.position])
+    }
+
+    fn number(&mut self) -> Result<usize> {
+        let token = self.token()?;
+        std::str::from_utf8(token).ok()
+            .and_then(|token| token.parse().ok())
+            .ok_or_else(|| anyhow::anyhow!("Expected a number in image data, found '{}'", String::from_utf8_lossy(token)))
+    }
+
+    /// Next pixel of a plain bitmap, whose digits need not be separated
+    fn bit(&mut self) -> Result<usize> {
+        self.skip_separators();
+        let bit = match self.data.get(self.position) {
+            Some(b'0') => 0,
+            Some(b'1') => 1,
+            Some(&byte) => anyhow::bail!("Expected 0 or 1 in bitmap data, found '{}'", byte as char),
+            None => anyhow::bail!("Image data ends early"),
+        };
+        self.position += 1;
+        Ok(bit)
+    }
+
+    /// Binary data after the single whitespace character ending the header
+    fn raster(&self) -> Result<&'a [u8]> {
+        match self.data.get(self.position) {
+            Some(byte) if byte.is_ascii_whitespace() => Ok(&self.data[self.position + 1..]),
+            _ => anyhow::bail!("Image header is not followed by whitespace"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(threshold: f64, downscale: usize, invert: bool) -> ImageImportConfig {
+        ImageImportConfig { threshold, downscale, invert }
+    }
+
+    fn rows(grid: &Grid) -> Vec<String> {
+        (0..grid.height)
+            .map(|row| (0..grid.width).map(|col| if grid.get(row, col) { '1' } else { '0' }).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_netpbm_formats() {
+        let expected = vec!["010".to_string(), "110".to_string()];
+        let defaults = ImageImportConfig::default();
+
+        // Plain bitmap with a comment and unseparated digits
+        let plain = b"P1\n# glider piece\n3 2\n010\n1 1 0\n";
+        assert_eq!(rows(&parse_netpbm(plain, &defaults, BoundaryCondition::Dead).unwrap()), expected);
+
+        // Raw bitmap rows are padded to whole bytes
+        let raw = b"P4 3 2\n\x40\xC0";
+        assert_eq!(rows(&parse_netpbm(raw, &defaults, BoundaryCondition::Dead).unwrap()), expected);
+
+        let plain_gray = b"P2\n3 2\n255\n255 0 255\n10 60 200\n";
+        assert_eq!(rows(&parse_netpbm(plain_gray, &defaults, BoundaryCondition::Dead).unwrap()), expected);
+
+        let raw_gray = b"P5\n3 2\n65535\n\xFF\xFF\x00\x00\xFF\xFF\x00\x00\x10\x00\xFF\xFF";
+        assert_eq!(rows(&parse_netpbm(raw_gray, &defaults, BoundaryCondition::Dead).unwrap()), expected);
+
+        assert!(parse_netpbm(b"P6\n1 1\n255\n\0\0\0", &defaults, BoundaryCondition::Dead).is_err());
+        assert!(parse_netpbm(b"P4\n16 2\n\0\0", &defaults, BoundaryCondition::Dead).is_err());
+        assert!(parse_netpbm(b"P1\n2 2\n0 1 2 0", &defaults, BoundaryCondition::Dead).is_err());
+    }
+
+    #[test]
+    fn test_threshold_downscale_and_invert() {
+        // A 4x2 graymap: black, dark gray, light gray and white columns
+        let image = b"P2\n4 2\n4\n0 1 3 4\n0 1 3 4\n";
+
+        let grid = parse_netpbm(image, &options(0.5, 1, false), BoundaryCondition::Dead).unwrap();
+        assert_eq!(rows(&grid), vec!["1100", "1100"]);
+        let grid = parse_netpbm(image, &options(0.2, 1, false), BoundaryCondition::Dead).unwrap();
+        assert_eq!(rows(&grid), vec!["1110", "1110"]);
+        let grid = parse_netpbm(image, &options(0.5, 1, true), BoundaryCondition::Dead).unwrap();
+        assert_eq!(rows(&grid), vec!["0011", "0011"]);
+
+        // 3x3 blocks: the first averages six pixels, the cut-off second only two
+        let grid = parse_netpbm(image, &options(0.5, 3, false), BoundaryCondition::Dead).unwrap();
+        assert_eq!(rows(&grid), vec!["10"]);
+        assert!(parse_netpbm(image, &options(0.5, 0, false), BoundaryCondition::Dead).is_err());
+    }
+
+    #[test]
+    fn test_image_round_trip() {
+        let grid = Grid::from_cells(vec![
+            vec![false, true, false],
+            vec![true, true, false],
+        ], BoundaryCondition::Dead).unwrap();
+        let style = ImageExportConfig { cell_size: 3, ..ImageExportConfig::default() };
+        let defaults = ImageImportConfig::default();
+
+        for format in [ImageFormat::Pbm, ImageFormat::Pgm] {
+            let image = grid_to_image(&grid, format, &style).unwrap();
+            assert_eq!(parse_netpbm(&image, &options(0.5, 3, false), BoundaryCondition::Dead).unwrap(), grid);
+
+            let full_size = parse_netpbm(&image, &defaults, BoundaryCondition::Dead).unwrap();
+            assert_eq!((full_size.width, full_size.height), (9, 6));
+        }
+
+        let png = grid_to_image(&grid, ImageFormat::Png, &style).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+        assert_eq!(ImageFormat::from_path(Path::new("frames/gen_000.PNG")), Some(ImageFormat::Png));
+    }
+
+    #[test]
+    fn test_colors() {
+        assert_eq!("#FF8000".parse::<RgbColor>().unwrap(), RgbColor([255, 128, 0]));
+        assert_eq!("1e90ff".parse::<RgbColor>().unwrap().to_string(), "#1e90ff");
+        assert!("#12345".parse::<RgbColor>().is_err());
+        assert!("#12345g".parse::<RgbColor>().is_err());
+
+        let style: ImageExportConfig = serde_yaml::from_str("alive_color: \"#00ff00\"").unwrap();
+        assert_eq!(style.alive_color, RgbColor([0, 255, 0]));
+        assert_eq!(style.dead_color, RgbColor::WHITE);
+    }
+
+    #[test]
+    fn test_evolution_to_gif() {
+        let blinker = |vertical: bool| {
+            let cells: Vec<Vec<bool>> = (0..3).map(|row| (0..3).map(|col| if vertical { col == 1 } else { row == 1 }).collect()).collect();
+            Grid::from_cells(cells, BoundaryCondition::Dead).unwrap()
+        };
+        let style = ImageExportConfig {
+            cell_size: 2,
+            alive_color: RgbColor([255, 0, 0]),
+            frame_delay_ms: 250,
+            final_hold_ms: 3000,
+            ..ImageExportConfig::default()
+        };
+
+        let animation = evolution_to_gif(&[blinker(false), blinker(true)], &style).unwrap();
+        // A 6x6 canvas whose two-color palette lists dead cells first
+        assert!(animation.starts_with(b"GIF89a\x06\x00\x06\x00\xF0\x00\x00\xFF\xFF\xFF\xFF\x00\x00"));
+        // The last frame is held longer than the others
+        assert!(animation.windows(6).any(|window| window == [0x21, 0xF9, 0x04, 0x04, 25, 0]));
+        assert!(animation.windows(6).any(|window| window == [0x21, 0xF9, 0x04, 0x04, 44, 1]));
+
+        let smaller = Grid::from_cells(vec![vec![true]], BoundaryCondition::Dead).unwrap();
+        assert!(evolution_to_gif(&[blinker(false), smaller], &style).is_err());
+        assert!(evolution_to_gif(&[], &style).is_err());
+    }
+}
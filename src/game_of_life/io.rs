@@ -1,25 +1,445 @@
 //! File I/O operations for Game of Life grids
+//!
+//! Grids are read from plain `0`/`1` rows, Golly-style RLE, LifeWiki
+//! plaintext (`.cells`) and Life 1.06 coordinate lists. A Life 1.06 header
+//! or an RLE `x = ...` line identifies the format; otherwise the file
+//! extension does. Names and comments in the file are kept as the pattern's
+//! `PatternSource`. PBM and PGM images are recognized by their extension
+//! and converted with an `ImageImportConfig`.
 
-use super::Grid;
-use crate::config::BoundaryCondition;
+use super::image::{grid_to_image, parse_netpbm, ImageFormat};
+use super::{Grid, Rule, TargetPattern};
+use crate::config::{BoundaryCondition, ImageExportConfig, ImageImportConfig, Settings};
 use anyhow::{Context, Result};
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Longest line written in RLE data, as recommended by the format
+const RLE_LINE_LENGTH: usize = 70;
+
+/// Header line of a Life 1.06 file
+const LIFE_106_HEADER: &str = "#Life 1.06";
+
+/// Pattern file formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternFormat {
+    /// Rows of `0` and `1`, with `?` or `*` for don't-care cells
+    Plain,
+    /// Golly's run-length encoding
+    Rle,
+    /// LifeWiki plaintext: `!` comments, then rows of `.` and `O`
+    Cells,
+    /// Life 1.06: one `x y` line per live cell
+    Life106,
+}
+
+impl PatternFormat {
+    /// Format named by a file extension
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "txt" => Some(Self::Plain),
+            "rle" => Some(Self::Rle),
+            "cells" => Some(Self::Cells),
+            "lif" | "life" => Some(Self::Life106),
+            _ => None,
+        }
+    }
+
+    /// Extension written for the format
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Plain => "txt",
+            Self::Rle => "rle",
+            Self::Cells => "cells",
+            Self::Life106 => "lif",
+        }
+    }
+
+    /// Format of a file, from its first lines or else its extension
+    ///
+    /// A Life 1.06 header, a plaintext `!` comment or an RLE `x = ...` line
+    /// wins over the extension, so RLE saved as `.txt` still loads.
+    pub fn detect(path: &Path, content: &str) -> Self {
+        let mut lines = content.lines().map(str::trim).filter(|line| !line.is_empty());
+        match lines.clone().next() {
+            Some(line) if line.starts_with(LIFE_106_HEADER) => return Self::Life106,
+            Some(line) if line.starts_with('!') => return Self::Cells,
+            _ => {}
+        }
+        if lines.find(|line| !line.starts_with('#'))
+            .is_some_and(|line| line.starts_with('x') && line.contains('='))
+        {
+            return Self::Rle;
+        }
+
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(Self::from_extension)
+            .unwrap_or(Self::Plain)
+    }
+}
+
+/// Where a pattern came from
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatternSource {
+    /// File the pattern was loaded from
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+    /// Pattern name given in the file
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Comment lines of the file, without their markers
+    #[serde(default)]
+    pub comments: Vec<String>,
+}
+
+impl PatternSource {
+    /// Check whether nothing is known about the source
+    pub fn is_empty(&self) -> bool {
+        self.file.is_none() && self.name.is_none() && self.comments.is_empty()
+    }
+}
+
+/// A pattern read from a file, with what the file says about it
+#[derive(Debug, Clone)]
+pub struct PatternFile {
+    pub pattern: TargetPattern,
+    /// Rule declared in the file's header, if any
+    pub rule: Option<Rule>,
+    /// Name and comments of the file
+    pub source: PatternSource,
+}
+
+impl PatternFile {
+    /// A file holding `pattern` with nothing else known about it
+    pub fn new(pattern: TargetPattern) -> Self {
+        Self { pattern, rule: None, source: PatternSource::default() }
+    }
+}
 
 /// Load a grid from a text file
-/// Format: Each line represents a row, with '1' for alive cells and '0' for dead cells
+/// Format: Each line represents a row, with '1' for alive cells and '0' for dead cells,
+/// or any other `PatternFormat`
 pub fn load_grid_from_file<P: AsRef<Path>>(
     path: P, 
     boundary_condition: BoundaryCondition
 ) -> Result<Grid> {
-    let content = std::fs::read_to_string(&path)
-        .with_context(|| format!("Failed to read grid file: {}", path.as_ref().display()))?;
-    
-    parse_grid_from_string(&content, boundary_condition)
-        .with_context(|| format!("Failed to parse grid from file: {}", path.as_ref().display()))
+    let pattern = load_pattern_from_file(&path, boundary_condition)?;
+
+    if !pattern.is_fully_specified() {
+        anyhow::bail!("Grid file {} contains {} don't-care cells; '?' and '*' are only allowed in target patterns",
+                     path.as_ref().display(), pattern.dont_care_count());
+    }
+
+    Ok(pattern.grid)
+}
+
+/// Load a target pattern from a text file
+/// Format: as for grids, plus '?' or '*' for cells whose state doesn't matter
+pub fn load_pattern_from_file<P: AsRef<Path>>(
+    path: P,
+    boundary_condition: BoundaryCondition
+) -> Result<TargetPattern> {
+    Ok(load_pattern_file(path, boundary_condition)?.pattern)
+}
+
+/// Load a pattern file in any supported format, keeping its rule, name and comments
+///
+/// Images are converted with the default `ImageImportConfig`.
+pub fn load_pattern_file<P: AsRef<Path>>(
+    path: P,
+    boundary_condition: BoundaryCondition
+) -> Result<PatternFile> {
+    load_pattern_or_image(path.as_ref(), boundary_condition, &ImageImportConfig::default())
+}
+
+fn load_pattern_or_image(path: &Path, boundary_condition: BoundaryCondition, image: &ImageImportConfig) -> Result<PatternFile> {
+    if ImageFormat::from_path(path).is_some() {
+        let grid = load_grid_from_image(path, image, boundary_condition)?;
+        let mut file = PatternFile::new(TargetPattern::from_grid(grid));
+        file.source.file = Some(path.to_path_buf());
+        return Ok(file);
+    }
+
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read pattern file: {}", path.display()))?;
+
+    let format = PatternFormat::detect(path, &content);
+    let mut file = parse_pattern_file(&content, format, boundary_condition)
+        .with_context(|| format!("Failed to parse pattern from file: {}", path.display()))?;
+    file.source.file = Some(path.to_path_buf());
+    Ok(file)
+}
+
+/// Parse the contents of a pattern file in `format`
+pub fn parse_pattern_file(content: &str, format: PatternFormat, boundary_condition: BoundaryCondition) -> Result<PatternFile> {
+    match format {
+        PatternFormat::Plain => parse_pattern_from_string(content, boundary_condition).map(PatternFile::new),
+        PatternFormat::Rle => parse_rle(content, boundary_condition),
+        PatternFormat::Cells => parse_cells(content, boundary_condition),
+        PatternFormat::Life106 => parse_life_106(content, boundary_condition),
+    }
+}
+
+/// Load the target pattern for a run with `settings`
+///
+/// Images are converted with `input.image`. A rule declared in the file
+/// replaces `simulation.rule`, and the rule-dependent settings are checked
+/// again with it.
+pub fn load_target_for_settings<P: AsRef<Path>>(path: P, settings: &mut Settings) -> Result<PatternFile> {
+    let file = load_pattern_or_image(path.as_ref(), settings.simulation.boundary_condition.clone(), &settings.input.image)?;
+
+    if let Some(rule) = &file.rule {
+        if *rule != settings.simulation.rule {
+            println!("Using rule {} from {} instead of {}", rule, path.as_ref().display(), settings.simulation.rule);
+            settings.simulation.rule = rule.clone();
+            settings.validate_rule()
+                .with_context(|| format!("Settings do not support the rule of {}", path.as_ref().display()))?;
+        }
+    }
+
+    Ok(file)
+}
+
+/// Load a grid from a PBM or PGM image
+pub fn load_grid_from_image<P: AsRef<Path>>(
+    path: P,
+    options: &ImageImportConfig,
+    boundary_condition: BoundaryCondition
+) -> Result<Grid> {
+    let path = path.as_ref();
+    if ImageFormat::from_path(path) == Some(ImageFormat::Png) {
+        anyhow::bail!("PNG images cannot be read; convert {} to PBM or PGM", path.display());
+    }
+
+    let data = std::fs::read(path)
+        .with_context(|| format!("Failed to read image file: {}", path.display()))?;
+    parse_netpbm(&data, options, boundary_condition)
+        .with_context(|| format!("Failed to parse image file: {}", path.display()))
+}
+
+/// Parse a pattern in Golly's run-length encoded format
+///
+/// The grid has the size given in the header, so trailing dead cells and
+/// rows left out of the data are still part of it.
+pub fn parse_rle(content: &str, boundary_condition: BoundaryCondition) -> Result<PatternFile> {
+    let mut source = PatternSource::default();
+    for line in content.lines().map(str::trim).take_while(|line| line.is_empty() || line.starts_with('#')) {
+        if let Some(name) = line.strip_prefix("#N") {
+            source.name = Some(name.trim().to_string());
+        } else if let Some(comment) = line.strip_prefix("#C").or_else(|| line.strip_prefix("#c")) {
+            source.comments.push(comment.trim().to_string());
+        } else if let Some(author) = line.strip_prefix("#O") {
+            source.comments.push(format!("Author: {}", author.trim()));
+        }
+    }
+
+    let mut lines = content.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+    let header = lines.next().context("RLE file has no 'x = ..., y = ...' header")?;
+    let (width, height, rule) = parse_rle_header(header)?;
+
+    let mut grid = Grid::new(width, height, boundary_condition);
+    let (mut row, mut col) = (0usize, 0usize);
+    let mut run = String::new();
+    let mut terminated = false;
+
+    'data: for line in lines {
+        for ch in line.chars() {
+            if ch.is_ascii_digit() {
+                run.push(ch);
+                continue;
+            }
+            if ch.is_whitespace() {
+                continue;
+            }
+
+            let count: usize = if run.is_empty() {
+                1
+            } else {
+                run.parse().with_context(|| format!("Invalid run length '{}' in RLE data", run))?
+            };
+            run.clear();
+
+            match ch {
+                'b' | '.' => col = col.saturating_add(count),
+                'o' | 'A' => {
+                    for _ in 0..count {
+                        if row >= height || col >= width {
+                            anyhow::bail!("Live cell ({}, {}) lies outside the {}x{} grid of the RLE header",
+                                         row, col, width, height);
+                        }
+                        grid.set(row, col, true)?;
+                        col += 1;
+                    }
+                }
+                '$' => {
+                    row = row.saturating_add(count);
+                    col = 0;
+                }
+                '!' => {
+                    terminated = true;
+                    break 'data;
+                }
+                _ => anyhow::bail!("Invalid character '{}' in RLE data. Only 'b', 'o', '$', '!' and run lengths are allowed", ch),
+            }
+        }
+    }
+
+    if !terminated {
+        anyhow::bail!("RLE data does not end with '!'");
+    }
+
+    Ok(PatternFile { pattern: TargetPattern::from_grid(grid), rule, source })
+}
+
+/// Parse an RLE header such as `x = 21, y = 9, rule = B3/S23`
+fn parse_rle_header(header: &str) -> Result<(usize, usize, Option<Rule>)> {
+    // The rule comes last and may contain commas of its own
+    let (dimensions, rule) = match header.find("rule") {
+        Some(index) => (&header[..index], Some(&header[index..])),
+        None => (header, None),
+    };
+
+    let (mut width, mut height) = (None, None);
+    for field in dimensions.split(',').map(str::trim).filter(|field| !field.is_empty()) {
+        let (key, value) = field.split_once('=')
+            .with_context(|| format!("Invalid RLE header field '{}'", field))?;
+        let value: usize = value.trim().parse()
+            .with_context(|| format!("Invalid RLE header field '{}'", field))?;
+        match key.trim() {
+            "x" => width = Some(value),
+            "y" => height = Some(value),
+            other => anyhow::bail!("Unknown RLE header field '{}'", other),
+        }
+    }
+
+    let (Some(width), Some(height)) = (width, height) else {
+        anyhow::bail!("RLE header '{}' needs both x and y", header);
+    };
+    if width == 0 || height == 0 {
+        anyhow::bail!("RLE header declares an empty {}x{} grid", width, height);
+    }
+
+    let rule = rule.map(|field| -> Result<Rule> {
+        let (_, value) = field.split_once('=')
+            .with_context(|| format!("Invalid RLE header field '{}'", field))?;
+        // Golly appends a bounded grid after a colon, as in B3/S23:T20,20
+        let value = value.split(':').next().unwrap_or_default().trim();
+        value.parse().with_context(|| format!("Invalid rule '{}' in RLE header", value))
+    }).transpose()?;
+
+    Ok((width, height, rule))
+}
+
+/// Parse a pattern in the LifeWiki plaintext format
+///
+/// Lines starting with `!` are comments, `!Name:` giving the name. Rows
+/// may leave out trailing dead cells, and an empty row is all dead.
+pub fn parse_cells(content: &str, boundary_condition: BoundaryCondition) -> Result<PatternFile> {
+    let mut source = PatternSource::default();
+    let mut rows: Vec<Vec<bool>> = Vec::new();
+
+    for (line_number, line) in content.lines().enumerate() {
+        let line = line.trim_end();
+        if let Some(comment) = line.strip_prefix('!') {
+            match comment.strip_prefix("Name:") {
+                Some(name) => source.name = Some(name.trim().to_string()),
+                None => source.comments.push(comment.trim().to_string()),
+            }
+            continue;
+        }
+        // Blank lines before the first row are just spacing
+        if line.is_empty() && rows.is_empty() {
+            continue;
+        }
+
+        let row = line.chars()
+            .map(|ch| match ch {
+                '.' => Ok(false),
+                'O' => Ok(true),
+                _ => anyhow::bail!("Invalid character '{}' on line {}. Only '.' and 'O' are allowed",
+                                  ch, line_number + 1),
+            })
+            .collect::<Result<Vec<bool>>>()?;
+        rows.push(row);
+    }
+
+    while rows.last().is_some_and(|row| row.is_empty()) {
+        rows.pop();
+    }
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if width == 0 {
+        anyhow::bail!("Plaintext pattern has no cells");
+    }
+    for row in &mut rows {
+        row.resize(width, false);
+    }
+
+    let grid = Grid::from_cells(rows, boundary_condition)?;
+    Ok(PatternFile { pattern: TargetPattern::from_grid(grid), rule: None, source })
+}
+
+/// Parse a pattern in the Life 1.06 format
+///
+/// The grid is the bounding box of the live cells, whose coordinates may be
+/// negative. `#N` names the pattern and `#D` or `#C` lines are comments.
+pub fn parse_life_106(content: &str, boundary_condition: BoundaryCondition) -> Result<PatternFile> {
+    let mut lines = content.lines().map(str::trim).filter(|line| !line.is_empty());
+    if !lines.next().is_some_and(|line| line.starts_with(LIFE_106_HEADER)) {
+        anyhow::bail!("Life 1.06 file does not start with '{}'", LIFE_106_HEADER);
+    }
+
+    let mut source = PatternSource::default();
+    let mut cells: Vec<(i64, i64)> = Vec::new();
+    for line in lines {
+        if let Some(name) = line.strip_prefix("#N") {
+            source.name = Some(name.trim().to_string());
+        } else if let Some(comment) = line.strip_prefix("#D").or_else(|| line.strip_prefix("#C")) {
+            source.comments.push(comment.trim().to_string());
+        } else if !line.starts_with('#') {
+            let coordinates: Vec<&str> = line.split_whitespace().collect();
+            let [x, y] = coordinates.as_slice() else {
+                anyhow::bail!("Expected 'x y' coordinates, found '{}'", line);
+            };
+            let x: i64 = x.parse().with_context(|| format!("Invalid x coordinate in '{}'", line))?;
+            let y: i64 = y.parse().with_context(|| format!("Invalid y coordinate in '{}'", line))?;
+            cells.push((x, y));
+        }
+    }
+
+    if cells.is_empty() {
+        anyhow::bail!("Life 1.06 pattern has no live cells");
+    }
+    let min_x = cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+    let max_x = cells.iter().map(|&(x, _)| x).max().unwrap_or(0);
+    let min_y = cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+    let max_y = cells.iter().map(|&(_, y)| y).max().unwrap_or(0);
+
+    let mut grid = Grid::new((max_x - min_x + 1) as usize, (max_y - min_y + 1) as usize, boundary_condition);
+    for (x, y) in cells {
+        grid.set((y - min_y) as usize, (x - min_x) as usize, true)?;
+    }
+
+    Ok(PatternFile { pattern: TargetPattern::from_grid(grid), rule: None, source })
 }
 
 /// Parse a grid from a string representation
 pub fn parse_grid_from_string(content: &str, boundary_condition: BoundaryCondition) -> Result<Grid> {
+    let pattern = parse_pattern_from_string(content, boundary_condition)?;
+
+    if !pattern.is_fully_specified() {
+        anyhow::bail!("Grid contains {} don't-care cells; '?' and '*' are only allowed in target patterns",
+                     pattern.dont_care_count());
+    }
+
+    Ok(pattern.grid)
+}
+
+/// Parse a target pattern from a string representation
+pub fn parse_pattern_from_string(content: &str, boundary_condition: BoundaryCondition) -> Result<TargetPattern> {
     let lines: Vec<&str> = content.lines()
         .map(|line| line.trim())
         .filter(|line| !line.is_empty())
@@ -37,6 +457,7 @@ pub fn parse_grid_from_string(content: &str, boundary_condition: BoundaryConditi
     }
     
     let mut cells = Vec::with_capacity(height);
+    let mut care = Vec::with_capacity(width * height);
     
     for (row_idx, line) in lines.iter().enumerate() {
         if line.len() != width {
@@ -49,14 +470,16 @@ pub fn parse_grid_from_string(content: &str, boundary_condition: BoundaryConditi
             match ch {
                 '0' => row.push(false),
                 '1' => row.push(true),
-                _ => anyhow::bail!("Invalid character '{}' at position ({}, {}). Only '0' and '1' are allowed", 
+                '?' | '*' => row.push(false),
+                _ => anyhow::bail!("Invalid character '{}' at position ({}, {}). Only '0', '1', '?' and '*' are allowed", 
                                  ch, row_idx, col_idx),
             }
+            care.push(ch == '0' || ch == '1');
         }
         cells.push(row);
     }
     
-    Grid::from_cells(cells, boundary_condition)
+    TargetPattern::new(Grid::from_cells(cells, boundary_condition)?, care)
 }
 
 /// Save a grid to a text file
@@ -90,6 +513,189 @@ pub fn grid_to_string(grid: &Grid) -> String {
     result
 }
 
+/// Convert a target pattern to string representation, with '?' for don't-care cells
+pub fn pattern_to_string(pattern: &TargetPattern) -> String {
+    let grid = &pattern.grid;
+    let mut result = String::with_capacity(grid.height * (grid.width + 1));
+
+    for row in 0..grid.height {
+        for col in 0..grid.width {
+            result.push(match (pattern.cares(row, col), grid.get(row, col)) {
+                (false, _) => '?',
+                (true, true) => '1',
+                (true, false) => '0',
+            });
+        }
+        result.push('\n');
+    }
+
+    result
+}
+
+/// Convert a grid to RLE, with a header giving its size and `rule`
+///
+/// Dead cells at the end of a row and empty rows at the end of the grid are
+/// left out, as in Golly's output; the header keeps the full size.
+pub fn grid_to_rle(grid: &Grid, rule: &Rule) -> String {
+    let mut result = format!("x = {}, y = {}, rule = {}\n", grid.width, grid.height, rule);
+
+    let mut tokens = Vec::new();
+    let mut last_row = None;
+    for row in 0..grid.height {
+        let mut runs: Vec<(bool, usize)> = Vec::new();
+        for col in 0..grid.width {
+            match runs.last_mut() {
+                Some((alive, count)) if *alive == grid.get(row, col) => *count += 1,
+                _ => runs.push((grid.get(row, col), 1)),
+            }
+        }
+        if runs.last().is_some_and(|&(alive, _)| !alive) {
+            runs.pop();
+        }
+        if runs.is_empty() {
+            continue;
+        }
+
+        let skipped = match last_row {
+            Some(last) => row - last,
+            None => row,
+        };
+        if skipped > 0 {
+            tokens.push(rle_run(skipped, '$'));
+        }
+        for (alive, count) in runs {
+            tokens.push(rle_run(count, if alive { 'o' } else { 'b' }));
+        }
+        last_row = Some(row);
+    }
+    tokens.push("!".to_string());
+
+    // Wrap between runs, never inside one
+    let mut line = String::new();
+    for token in tokens {
+        if !line.is_empty() && line.len() + token.len() > RLE_LINE_LENGTH {
+            let _ = writeln!(result, "{}", line);
+            line.clear();
+        }
+        line.push_str(&token);
+    }
+    let _ = writeln!(result, "{}", line);
+
+    result
+}
+
+/// One RLE run, the count left out when it is 1
+fn rle_run(count: usize, tag: char) -> String {
+    if count == 1 {
+        tag.to_string()
+    } else {
+        format!("{}{}", count, tag)
+    }
+}
+
+/// Save a grid to an RLE file
+pub fn save_grid_as_rle<P: AsRef<Path>>(grid: &Grid, rule: &Rule, path: P) -> Result<()> {
+    if let Some(parent) = path.as_ref().parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+
+    std::fs::write(&path, grid_to_rle(grid, rule))
+        .with_context(|| format!("Failed to write RLE to file: {}", path.as_ref().display()))
+}
+
+/// Convert a grid to the LifeWiki plaintext format, with the source's name and comments
+///
+/// Every row is written in full, so the grid keeps its size when read back.
+pub fn grid_to_cells(grid: &Grid, source: &PatternSource) -> String {
+    let mut result = String::new();
+    if let Some(name) = &source.name {
+        let _ = writeln!(result, "!Name: {}", name);
+    }
+    for comment in &source.comments {
+        let _ = writeln!(result, "!{}", comment);
+    }
+
+    for row in 0..grid.height {
+        for col in 0..grid.width {
+            result.push(if grid.get(row, col) { 'O' } else { '.' });
+        }
+        result.push('\n');
+    }
+
+    result
+}
+
+/// Convert a grid to the Life 1.06 format, with the source's name and comments
+///
+/// Coordinates are columns and rows from the top-left corner. The format
+/// only lists live cells, so a grid read back shrinks to their bounding box.
+pub fn grid_to_life_106(grid: &Grid, source: &PatternSource) -> String {
+    let mut result = format!("{}\n", LIFE_106_HEADER);
+    if let Some(name) = &source.name {
+        let _ = writeln!(result, "#N {}", name);
+    }
+    for comment in &source.comments {
+        let _ = writeln!(result, "#D {}", comment);
+    }
+
+    for (row, col) in grid.living_cells() {
+        let _ = writeln!(result, "{} {}", col, row);
+    }
+
+    result
+}
+
+/// Render a pattern file in `format`
+///
+/// Plain text keeps don't-care cells but has no room for names, comments
+/// or a rule. The other formats write don't-care cells as dead, and RLE
+/// falls back to Conway's rule when the file declares none.
+pub fn pattern_file_to_string(file: &PatternFile, format: PatternFormat) -> String {
+    let grid = &file.pattern.grid;
+    match format {
+        PatternFormat::Plain => pattern_to_string(&file.pattern),
+        PatternFormat::Rle => {
+            let mut result = String::new();
+            if let Some(name) = &file.source.name {
+                let _ = writeln!(result, "#N {}", name);
+            }
+            for comment in &file.source.comments {
+                let _ = writeln!(result, "#C {}", comment);
+            }
+            result.push_str(&grid_to_rle(grid, file.rule.as_ref().unwrap_or(&Rule::conway())));
+            result
+        }
+        PatternFormat::Cells => grid_to_cells(grid, &file.source),
+        PatternFormat::Life106 => grid_to_life_106(grid, &file.source),
+    }
+}
+
+/// Save a pattern file in `format`
+pub fn save_pattern_file<P: AsRef<Path>>(file: &PatternFile, format: PatternFormat, path: P) -> Result<()> {
+    if let Some(parent) = path.as_ref().parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+
+    std::fs::write(&path, pattern_file_to_string(file, format))
+        .with_context(|| format!("Failed to write pattern to file: {}", path.as_ref().display()))
+}
+
+/// Save a grid as an image in the format of the path's extension
+pub fn save_grid_as_image<P: AsRef<Path>>(grid: &Grid, style: &ImageExportConfig, path: P) -> Result<()> {
+    let path = path.as_ref();
+    let format = ImageFormat::from_path(path)
+        .with_context(|| format!("Unknown image extension: {}", path.display()))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+
+    std::fs::write(path, grid_to_image(grid, format, style)?)
+        .with_context(|| format!("Failed to write image to file: {}", path.display()))
+}
+
 /// Load multiple grids from a directory
 pub fn load_grids_from_directory<P: AsRef<Path>>(
     dir_path: P,
@@ -105,8 +711,10 @@ pub fn load_grids_from_directory<P: AsRef<Path>>(
         let path = entry.path();
         
         if path.is_file() {
-            if let Some(extension) = path.extension() {
-                if extension == "txt" {
+            if let Some(extension) = path.extension().and_then(|extension| extension.to_str()) {
+                let readable_image = ImageFormat::from_extension(extension)
+                    .is_some_and(|format| format != ImageFormat::Png);
+                if PatternFormat::from_extension(extension).is_some() || readable_image {
                     let filename = path.file_stem()
                         .and_then(|s| s.to_str())
                         .unwrap_or("unknown")
@@ -233,6 +841,187 @@ mod tests {
         assert!(parse_grid_from_string(empty_content, BoundaryCondition::Dead).is_err());
     }
 
+    #[test]
+    fn test_parse_pattern_with_dont_care_cells() {
+        let content = "?1?\n*1*\n010\n";
+        let pattern = parse_pattern_from_string(content, BoundaryCondition::Dead).unwrap();
+
+        assert_eq!((pattern.grid.width, pattern.grid.height), (3, 3));
+        assert_eq!(pattern.dont_care_count(), 4);
+        assert!(!pattern.cares(0, 0));
+        assert!(pattern.cares(0, 1) && pattern.grid.get(0, 1));
+        assert!(pattern.cares(2, 0) && !pattern.grid.get(2, 0));
+        assert_eq!(pattern_to_string(&pattern), "?1?\n?1?\n010\n");
+
+        // Plain grids are fully specified patterns, but grids reject don't-care cells
+        assert!(parse_pattern_from_string("010\n", BoundaryCondition::Dead).unwrap().is_fully_specified());
+        assert!(parse_grid_from_string(content, BoundaryCondition::Dead).is_err());
+    }
+
+    #[test]
+    fn test_rle_round_trip() {
+        let content = "#N Glider\n#C A comment\nx = 5, y = 4, rule = B3/S23\nbo$2bo$3o!\n";
+        let file = parse_rle(content, BoundaryCondition::Dead).unwrap();
+        let grid = &file.pattern.grid;
+        assert_eq!((grid.width, grid.height), (5, 4));
+        assert_eq!(grid.living_count(), 5);
+        assert!(grid.get(0, 1) && grid.get(1, 2) && grid.get(2, 0));
+        assert_eq!(file.rule, Some(Rule::conway()));
+        assert_eq!(grid_to_rle(grid, &Rule::conway()), "x = 5, y = 4, rule = B3/S23\nbo$2bo$3o!\n");
+
+        // Empty rows become runs of '$', and long data wraps between runs
+        let mut sparse = Grid::new(80, 4, BoundaryCondition::Dead);
+        sparse.set(0, 0, true).unwrap();
+        sparse.set(3, 79, true).unwrap();
+        for col in (0..80).step_by(2) {
+            sparse.set(2, col, true).unwrap();
+        }
+        let rle = grid_to_rle(&sparse, &Rule::conway());
+        assert!(rle.starts_with("x = 80, y = 4, rule = B3/S23\no2$obo"));
+        assert!(rle.lines().all(|line| line.len() <= 70));
+        let parsed = parse_rle(&rle, BoundaryCondition::Dead).unwrap();
+        assert_eq!(parsed.pattern.grid, sparse);
+    }
+
+    #[test]
+    fn test_rle_header() {
+        let file = parse_rle("x = 3, y = 1, rule = 23/36:T3,1\n3o!", BoundaryCondition::Dead).unwrap();
+        assert_eq!(file.rule, Some("B36/S23".parse().unwrap()));
+        assert_eq!(parse_rle("x=2,y=2\n2o$2o!", BoundaryCondition::Dead).unwrap().rule, None);
+
+        // Cells outside the declared size, a missing terminator and other states are errors
+        assert!(parse_rle("x = 2, y = 1\n3o!", BoundaryCondition::Dead).is_err());
+        assert!(parse_rle("x = 2, y = 1\n2o", BoundaryCondition::Dead).is_err());
+        assert!(parse_rle("x = 2, y = 1\noB!", BoundaryCondition::Dead).is_err());
+        assert!(parse_rle("x = 2\no!", BoundaryCondition::Dead).is_err());
+    }
+
+    #[test]
+    fn test_rle_files_are_detected() {
+        let temp_dir = tempdir().unwrap();
+        let rle_path = temp_dir.path().join("blinker.rle");
+        let headed_path = temp_dir.path().join("blinker.txt");
+        let mut blinker = Grid::new(3, 3, BoundaryCondition::Dead);
+        for col in 0..3 {
+            blinker.set(1, col, true).unwrap();
+        }
+        let highlife: Rule = "B36/S23".parse().unwrap();
+        save_grid_as_rle(&blinker, &highlife, &rle_path).unwrap();
+        std::fs::copy(&rle_path, &headed_path).unwrap();
+
+        for path in [&rle_path, &headed_path] {
+            assert_eq!(load_grid_from_file(path, BoundaryCondition::Dead).unwrap(), blinker);
+        }
+
+        // The header's rule replaces the configured one
+        let mut settings = Settings::default();
+        let target = load_target_for_settings(&rle_path, &mut settings).unwrap();
+        assert_eq!(target.pattern.grid, blinker);
+        assert_eq!(settings.simulation.rule, highlife);
+
+        // A rule the settings cannot encode is still rejected
+        let b0_path = temp_dir.path().join("b0.rle");
+        save_grid_as_rle(&blinker, &"B03/S23".parse().unwrap(), &b0_path).unwrap();
+        let mut settings = Settings::default();
+        settings.simulation.margin = 1;
+        assert!(load_target_for_settings(&b0_path, &mut settings).is_err());
+
+        let grids = load_grids_from_directory(temp_dir.path(), BoundaryCondition::Dead).unwrap();
+        assert_eq!(grids.len(), 3);
+    }
+
+    #[test]
+    fn test_cells_round_trip() {
+        let content = "!Name: Glider\n!The smallest spaceship\n.O\n..O\nOOO\n\n...\n";
+        let file = parse_cells(content, BoundaryCondition::Dead).unwrap();
+        let grid = &file.pattern.grid;
+        assert_eq!((grid.width, grid.height), (3, 5));
+        assert_eq!(grid.living_count(), 5);
+        assert!(grid.get(0, 1) && !grid.get(0, 2) && grid.get(2, 0));
+        assert_eq!(file.source.name.as_deref(), Some("Glider"));
+        assert_eq!(file.source.comments, vec!["The smallest spaceship"]);
+
+        let written = grid_to_cells(grid, &file.source);
+        assert_eq!(written, "!Name: Glider\n!The smallest spaceship\n.O.\n..O\nOOO\n...\n...\n");
+        assert_eq!(parse_cells(&written, BoundaryCondition::Dead).unwrap().pattern, file.pattern);
+
+        assert!(parse_cells("!Name: Empty\n", BoundaryCondition::Dead).is_err());
+        assert!(parse_cells(".O1\n", BoundaryCondition::Dead).is_err());
+    }
+
+    #[test]
+    fn test_life_106_round_trip() {
+        let content = "#Life 1.06\n#N Blinker\n#D Period 2\n-1 0\n0 0\n1 0\n";
+        let file = parse_life_106(content, BoundaryCondition::Dead).unwrap();
+        let grid = &file.pattern.grid;
+        assert_eq!((grid.width, grid.height), (3, 1));
+        assert_eq!(grid.living_count(), 3);
+        assert_eq!(file.source.name.as_deref(), Some("Blinker"));
+        assert_eq!(file.source.comments, vec!["Period 2"]);
+
+        let written = grid_to_life_106(grid, &file.source);
+        assert_eq!(written, "#Life 1.06\n#N Blinker\n#D Period 2\n0 0\n1 0\n2 0\n");
+        assert_eq!(parse_life_106(&written, BoundaryCondition::Dead).unwrap().pattern, file.pattern);
+
+        assert!(parse_life_106("#Life 1.06\n", BoundaryCondition::Dead).is_err());
+        assert!(parse_life_106("#Life 1.06\n1 2 3\n", BoundaryCondition::Dead).is_err());
+        assert!(parse_life_106("0 0\n", BoundaryCondition::Dead).is_err());
+    }
+
+    #[test]
+    fn test_every_format_loads_from_a_directory() {
+        let temp_dir = tempdir().unwrap();
+        let glider = parse_grid_from_string("010\n001\n111\n", BoundaryCondition::Dead).unwrap();
+        let mut file = PatternFile::new(TargetPattern::from_grid(glider.clone()));
+        file.source.name = Some("Glider".to_string());
+        file.source.comments.push("Found by Richard Guy".to_string());
+
+        for format in [PatternFormat::Plain, PatternFormat::Rle, PatternFormat::Cells, PatternFormat::Life106] {
+            let path = temp_dir.path().join(format!("glider.{}", format.extension()));
+            save_pattern_file(&file, format, &path).unwrap();
+
+            let loaded = load_pattern_file(&path, BoundaryCondition::Dead).unwrap();
+            assert_eq!(loaded.pattern.grid, glider, "{:?}", format);
+            assert_eq!(loaded.source.file.as_deref(), Some(path.as_path()));
+            if format != PatternFormat::Plain {
+                assert_eq!(loaded.source.name.as_deref(), Some("Glider"), "{:?}", format);
+                assert_eq!(loaded.source.comments, file.source.comments, "{:?}", format);
+            }
+
+            // The contents alone identify the format
+            let content = std::fs::read_to_string(&path).unwrap();
+            assert_eq!(PatternFormat::detect(Path::new("pattern"), &content), format);
+        }
+
+        let grids = load_grids_from_directory(temp_dir.path(), BoundaryCondition::Dead).unwrap();
+        assert_eq!(grids.len(), 4);
+        assert!(grids.iter().all(|(name, grid)| name == "glider" && *grid == glider));
+    }
+
+    #[test]
+    fn test_image_targets() {
+        let temp_dir = tempdir().unwrap();
+        let glider = parse_grid_from_string("010\n001\n111\n", BoundaryCondition::Dead).unwrap();
+        let path = temp_dir.path().join("glider.pbm");
+        save_grid_as_image(&glider, &ImageExportConfig { cell_size: 2, ..ImageExportConfig::default() }, &path).unwrap();
+
+        // Defaults keep one cell per pixel; the run's settings can scale back down
+        let loaded = load_pattern_file(&path, BoundaryCondition::Dead).unwrap();
+        assert_eq!((loaded.pattern.grid.width, loaded.pattern.grid.height), (6, 6));
+        assert_eq!(loaded.source.file.as_deref(), Some(path.as_path()));
+
+        let mut settings = Settings::default();
+        settings.input.image.downscale = 2;
+        assert_eq!(load_target_for_settings(&path, &mut settings).unwrap().pattern.grid, glider);
+        settings.input.image.invert = true;
+        let inverted = load_target_for_settings(&path, &mut settings).unwrap().pattern.grid;
+        assert_eq!(inverted.living_count(), 9 - glider.living_count());
+
+        save_grid_as_image(&glider, &ImageExportConfig::default(), temp_dir.path().join("glider.png")).unwrap();
+        assert!(load_pattern_file(temp_dir.path().join("glider.png"), BoundaryCondition::Dead).is_err());
+        assert!(save_grid_as_image(&glider, &ImageExportConfig::default(), temp_dir.path().join("glider.bmp")).is_err());
+    }
+
     #[test]
     fn test_create_example_grids() {
         let temp_dir = tempdir().unwrap();
@@ -1,9 +1,17 @@
 //! Game of Life core functionality
 
 pub mod grid;
+pub mod font;
+pub mod image;
+pub mod rule;
 pub mod rules;
 pub mod io;
+pub mod pattern;
 
 pub use grid::Grid;
+pub use font::Font;
+pub use image::{evolution_to_gif, ImageFormat, RgbColor};
+pub use rule::Rule;
 pub use rules::GameOfLifeRules;
-pub use io::{load_grid_from_file, save_grid_to_file, create_example_grids};
\ No newline at end of file
+pub use io::{load_grid_from_file, load_pattern_from_file, load_pattern_file, load_target_for_settings, save_grid_to_file, save_grid_as_rle, save_pattern_file, load_grid_from_image, save_grid_as_image, create_example_grids, PatternFile, PatternFormat, PatternSource};
+pub use pattern::TargetPattern;
@@ -0,0 +1,119 @@
+//! Target patterns with unconstrained ("don't-care") cells
+
+use super::Grid;
+use anyhow::Result;
+use std::fmt;
+
+/// A target state in which some cells may be left unconstrained
+///
+/// `grid` holds the required state of every specified cell; don't-care cells
+/// are stored as dead there and marked `false` in `care`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetPattern {
+    pub grid: Grid,
+    /// Row-major mask, `true` where the cell's state is specified
+    pub care: Vec<bool>,
+}
+
+impl TargetPattern {
+    /// Create a pattern from a grid and a care mask of the same size
+    pub fn new(grid: Grid, care: Vec<bool>) -> Result<Self> {
+        if care.len() != grid.width * grid.height {
+            anyhow::bail!("Care mask has {} cells, expected {} for a {}x{} grid",
+                         care.len(), grid.width * grid.height, grid.width, grid.height);
+        }
+
+        // Keep don't-care cells dead so equal patterns compare equal
+        let mut grid = grid;
+        for (cell, &cares) in grid.cells.iter_mut().zip(&care) {
+            *cell &= cares;
+        }
+
+        Ok(Self { grid, care })
+    }
+
+    /// A pattern that specifies every cell of `grid`
+    pub fn from_grid(grid: Grid) -> Self {
+        let care = vec![true; grid.width * grid.height];
+        Self { grid, care }
+    }
+
+    /// Check whether the cell at the given coordinates is specified
+    pub fn cares(&self, row: usize, col: usize) -> bool {
+        row < self.grid.height && col < self.grid.width && self.care[self.grid.index(row, col)]
+    }
+
+    /// Number of don't-care cells
+    pub fn dont_care_count(&self) -> usize {
+        self.care.iter().filter(|&&cares| !cares).count()
+    }
+
+    /// Check whether every cell is specified
+    pub fn is_fully_specified(&self) -> bool {
+        self.care.iter().all(|&cares| cares)
+    }
+
+    /// Check whether `grid` agrees with the pattern on every specified cell
+    pub fn matches(&self, grid: &Grid) -> bool {
+        grid.width == self.grid.width
+            && grid.height == self.grid.height
+            && self.care.iter()
+                .zip(grid.cells.iter().zip(&self.grid.cells))
+                .all(|(&cares, (actual, expected))| !cares || actual == expected)
+    }
+}
+
+impl From<Grid> for TargetPattern {
+    fn from(grid: Grid) -> Self {
+        Self::from_grid(grid)
+    }
+}
+
+impl fmt::Display for TargetPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in 0..self.grid.height {
+            for col in 0..self.grid.width {
+                let symbol = if !self.cares(row, col) {
+                    "❔"
+                } else if self.grid.get(row, col) {
+                    "⬛"
+                } else {
+                    "⬜"
+                };
+                write!(f, "{}", symbol)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BoundaryCondition;
+
+    #[test]
+    fn test_pattern_matches_only_specified_cells() {
+        let grid = Grid::from_cells(vec![vec![true, true], vec![false, true]], BoundaryCondition::Dead).unwrap();
+        let pattern = TargetPattern::new(grid, vec![true, false, true, false]).unwrap();
+
+        // Don't-care cells are normalized to dead
+        assert_eq!(pattern.grid.cells, vec![true, false, false, false]);
+        assert_eq!(pattern.dont_care_count(), 2);
+        assert!(!pattern.is_fully_specified());
+
+        for free in 0..4 {
+            let mut candidate = Grid::from_cells(vec![vec![true, false], vec![false, false]], BoundaryCondition::Dead).unwrap();
+            candidate.cells[1] = free & 1 != 0;
+            candidate.cells[3] = free & 2 != 0;
+            assert!(pattern.matches(&candidate));
+        }
+
+        let wrong = Grid::from_cells(vec![vec![false, false], vec![false, false]], BoundaryCondition::Dead).unwrap();
+        assert!(!pattern.matches(&wrong));
+        assert!(!pattern.matches(&Grid::new(3, 2, BoundaryCondition::Dead)));
+
+        assert!(TargetPattern::new(Grid::new(2, 2, BoundaryCondition::Dead), vec![true; 3]).is_err());
+    }
+}
@@ -0,0 +1,412 @@
+//! Life-like cellular automaton rules in B/S and Hensel notation
+//!
+//! A neighborhood configuration is a byte whose bit `i` is set when the
+//! neighbor at `NEIGHBOR_OFFSETS[i]` is alive. Neighbors are numbered
+//! clockwise from north, so rotating the neighborhood by 90 degrees rotates
+//! the byte by two bits.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// (row, column) offsets of the eight neighbors: N, NE, E, SE, S, SW, W, NW
+pub const NEIGHBOR_OFFSETS: [(isize, isize); 8] = [
+    (-1, 0), (-1, 1), (0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1),
+];
+
+/// Hensel letters in canonical order; count `n` uses the first `HENSEL_LETTER_COUNTS[n]`
+const HENSEL_LETTERS: &str = "cekainyqjrtwz";
+
+const HENSEL_LETTER_COUNTS: [usize; 9] = [0, 2, 6, 10, 13, 10, 6, 2, 0];
+
+/// One representative configuration per Hensel letter for 1-4 live neighbors,
+/// indexed like `HENSEL_LETTERS`. Counts 5-7 use the complement of 3-1.
+const HENSEL_REPRESENTATIVES: [[u8; 13]; 5] = [
+    [0; 13],
+    [2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+    [10, 5, 9, 3, 17, 34, 0, 0, 0, 0, 0, 0, 0],
+    [42, 21, 37, 7, 131, 11, 41, 35, 67, 19, 0, 0, 0],
+    [170, 85, 75, 15, 27, 139, 43, 39, 83, 23, 147, 99, 51],
+];
+
+/// A Life-like rule: `B3/S23` (Conway), `B36/S23` (HighLife) or an
+/// isotropic non-totalistic rule such as `B2-a/S12`
+///
+/// A dead cell is born when its neighborhood configuration is in the birth
+/// table and a live cell survives when it is in the survival table.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Rule {
+    birth: [bool; 256],
+    survival: [bool; 256],
+}
+
+impl Rule {
+    /// Create an outer-totalistic rule from birth and survival neighbor counts
+    pub fn new(birth: &[u8], survival: &[u8]) -> Result<Self> {
+        Ok(Self {
+            birth: Self::count_table(birth)?,
+            survival: Self::count_table(survival)?,
+        })
+    }
+
+    /// Conway's Game of Life, `B3/S23`
+    pub fn conway() -> Self {
+        Self::new(&[3], &[2, 3]).expect("B3/S23 is a valid rule")
+    }
+
+    /// Next state of a cell given its current state and neighborhood configuration
+    pub fn next_state(&self, current_state: bool, configuration: u8) -> bool {
+        let table = if current_state { &self.survival } else { &self.birth };
+        table[configuration as usize]
+    }
+
+    /// Check if a cell should be alive in the next generation given its current state and neighbor count
+    ///
+    /// For non-totalistic rules this holds only if every configuration with
+    /// that many live neighbors leads to a live cell.
+    pub fn should_be_alive(&self, current_state: bool, neighbor_count: u8) -> bool {
+        configurations_with_count(neighbor_count)
+            .all(|configuration| self.next_state(current_state, configuration))
+    }
+
+    /// Check whether the rule only depends on the number of live neighbors
+    pub fn is_totalistic(&self) -> bool {
+        [&self.birth, &self.survival].iter().all(|table| {
+            (0..=8).all(|count| {
+                let mut states = configurations_with_count(count).map(|c| table[c as usize]);
+                let first = states.next();
+                states.all(|state| Some(state) == first)
+            })
+        })
+    }
+
+    /// Get neighbor counts that always result in birth (dead -> alive)
+    pub fn birth_counts(&self) -> Vec<u8> {
+        (0..=8).filter(|&count| self.should_be_alive(false, count)).collect()
+    }
+
+    /// Get neighbor counts that always result in survival (alive -> alive)
+    pub fn survival_counts(&self) -> Vec<u8> {
+        (0..=8).filter(|&count| self.should_be_alive(true, count)).collect()
+    }
+
+    fn count_table(counts: &[u8]) -> Result<[bool; 256]> {
+        let mut table = [false; 256];
+        for &count in counts {
+            if count > 8 {
+                anyhow::bail!("Neighbor count {} is out of range 0-8", count);
+            }
+            for configuration in configurations_with_count(count) {
+                table[configuration as usize] = true;
+            }
+        }
+        Ok(table)
+    }
+
+    /// Parse one half of a rulestring, e.g. `23` or `2-a3ik`
+    fn parse_section(section: &str, rule: &str) -> Result<[bool; 256]> {
+        let mut table = [false; 256];
+        let mut chars = section.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            let count = match c.to_digit(10) {
+                Some(d) if d <= 8 => d as u8,
+                _ => anyhow::bail!("Invalid character '{}' in rule '{}'", c, rule),
+            };
+
+            let negated = chars.next_if_eq(&'-').is_some();
+            let mut letters = Vec::new();
+            while let Some(letter) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+                letters.push(letter.to_ascii_lowercase());
+            }
+            if negated && letters.is_empty() {
+                anyhow::bail!("Expected Hensel letters after '{}-' in rule '{}'", count, rule);
+            }
+
+            let mut selected = [letters.is_empty(); 256];
+            for letter in letters {
+                let class = hensel_class(count, letter).ok_or_else(|| {
+                    anyhow::anyhow!("'{}{}' is not a valid neighborhood in rule '{}'", count, letter, rule)
+                })?;
+                for configuration in class {
+                    selected[configuration as usize] = true;
+                }
+            }
+
+            for configuration in configurations_with_count(count) {
+                if selected[configuration as usize] != negated {
+                    table[configuration as usize] = true;
+                }
+            }
+        }
+
+        Ok(table)
+    }
+
+    /// Write one half of a rulestring, preferring the shorter of `2ak` and `2-cein`
+    fn format_section(f: &mut fmt::Formatter<'_>, table: &[bool; 256]) -> fmt::Result {
+        for count in 0..=8u8 {
+            let letters = &HENSEL_LETTERS[..HENSEL_LETTER_COUNTS[count as usize]];
+            let included: String = letters
+                .chars()
+                .filter(|&letter| hensel_class(count, letter).unwrap().iter().all(|&c| table[c as usize]))
+                .collect();
+            let excluded: String = letters.chars().filter(|&letter| !included.contains(letter)).collect();
+
+            if configurations_with_count(count).all(|c| table[c as usize]) {
+                write!(f, "{}", count)?;
+            } else if !included.is_empty() {
+                if excluded.len() < included.len() {
+                    write!(f, "{}-{}", count, excluded)?;
+                } else {
+                    write!(f, "{}{}", count, included)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// All neighborhood configurations with exactly `count` live neighbors
+fn configurations_with_count(count: u8) -> impl Iterator<Item = u8> {
+    (0..=255u8).filter(move |configuration| configuration.count_ones() == count as u32)
+}
+
+/// Mirror a configuration across the north-south axis
+fn reflect(configuration: u8) -> u8 {
+    (0..8).fold(0, |reflected, i| {
+        if configuration & (1 << i) != 0 {
+            reflected | (1 << ((8 - i) % 8))
+        } else {
+            reflected
+        }
+    })
+}
+
+/// All configurations reachable from `configuration` by rotations and reflections
+fn symmetry_orbit(configuration: u8) -> Vec<u8> {
+    let mut orbit = Vec::new();
+    for start in [configuration, reflect(configuration)] {
+        for quarter_turns in 0..4 {
+            let image = start.rotate_left(2 * quarter_turns);
+            if !orbit.contains(&image) {
+                orbit.push(image);
+            }
+        }
+    }
+    orbit
+}
+
+/// Configurations named by a Hensel letter, e.g. `hensel_class(2, 'a')`
+fn hensel_class(count: u8, letter: char) -> Option<Vec<u8>> {
+    let count = count as usize;
+    if count > 8 {
+        return None;
+    }
+    let index = HENSEL_LETTERS[..HENSEL_LETTER_COUNTS[count]].find(letter)?;
+
+    let representative = if count <= 4 {
+        HENSEL_REPRESENTATIVES[count][index]
+    } else {
+        !HENSEL_REPRESENTATIVES[8 - count][index]
+    };
+    Some(symmetry_orbit(representative))
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self::conway()
+    }
+}
+
+impl FromStr for Rule {
+    type Err = anyhow::Error;
+
+    /// Parse `B3/S23`, `b3s23`, `S23/B3`, `B2-a/S12` or the classic survival/birth form `23/3`
+    fn from_str(s: &str) -> Result<Self> {
+        let rule = s.trim();
+
+        if !rule.contains(|c: char| c.eq_ignore_ascii_case(&'b') || c.eq_ignore_ascii_case(&'s')) {
+            let Some((survival, birth)) = rule.split_once('/') else {
+                anyhow::bail!("Rule '{}' is not in B/S notation", rule);
+            };
+            return Ok(Self {
+                birth: Self::parse_section(birth, rule)?,
+                survival: Self::parse_section(survival, rule)?,
+            });
+        }
+
+        let mut birth: Option<String> = None;
+        let mut survival: Option<String> = None;
+        let mut section = None;
+
+        for c in rule.chars() {
+            let upper = c.to_ascii_uppercase();
+            match upper {
+                'B' | 'S' => {
+                    let part = if upper == 'B' { &mut birth } else { &mut survival };
+                    if part.replace(String::new()).is_some() {
+                        anyhow::bail!("Rule '{}' repeats '{}'", rule, c);
+                    }
+                    section = Some(upper);
+                }
+                '/' => section = None,
+                _ => match section {
+                    Some('B') => birth.get_or_insert_with(String::new).push(c),
+                    Some(_) => survival.get_or_insert_with(String::new).push(c),
+                    None => anyhow::bail!("Unexpected '{}' in rule '{}'", c, rule),
+                },
+            }
+        }
+
+        match (birth, survival) {
+            (Some(birth), Some(survival)) => Ok(Self {
+                birth: Self::parse_section(&birth, rule)?,
+                survival: Self::parse_section(&survival, rule)?,
+            }),
+            _ => anyhow::bail!("Rule '{}' needs both a B and an S part", rule),
+        }
+    }
+}
+
+impl TryFrom<String> for Rule {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl From<Rule> for String {
+    fn from(rule: Rule) -> Self {
+        rule.to_string()
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "B")?;
+        Self::format_section(f, &self.birth)?;
+        write!(f, "/S")?;
+        Self::format_section(f, &self.survival)
+    }
+}
+
+impl fmt::Debug for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Rule").field(&self.to_string()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_conway() {
+        let rule: Rule = "B3/S23".parse().unwrap();
+        assert_eq!(rule, Rule::conway());
+        assert_eq!(rule.birth_counts(), vec![3]);
+        assert_eq!(rule.survival_counts(), vec![2, 3]);
+        assert!(rule.is_totalistic());
+
+        // Alternative spellings of the same rule
+        assert_eq!("b3s23".parse::<Rule>().unwrap(), rule);
+        assert_eq!("S23/B3".parse::<Rule>().unwrap(), rule);
+        assert_eq!("23/3".parse::<Rule>().unwrap(), rule);
+    }
+
+    #[test]
+    fn test_parse_other_rules() {
+        let highlife: Rule = "B36/S23".parse().unwrap();
+        assert!(highlife.should_be_alive(false, 6));
+        assert!(!highlife.should_be_alive(true, 6));
+
+        let seeds: Rule = "B2/S".parse().unwrap();
+        assert_eq!(seeds.birth_counts(), vec![2]);
+        assert!(seeds.survival_counts().is_empty());
+
+        let day_and_night: Rule = "B3678/S34678".parse().unwrap();
+        assert_eq!(day_and_night.to_string(), "B3678/S34678");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!("".parse::<Rule>().is_err());
+        assert!("B3".parse::<Rule>().is_err());
+        assert!("B9/S23".parse::<Rule>().is_err());
+        assert!("B3/S23/B4".parse::<Rule>().is_err());
+        assert!("life".parse::<Rule>().is_err());
+        assert!("B1a/S".parse::<Rule>().is_err()); // 1 neighbor has only c and e
+        assert!("B2-/S".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn test_hensel_classes_partition_configurations() {
+        let mut seen = [false; 256];
+        let mut class_count = 0;
+
+        for count in 0..=8u8 {
+            let letters = &HENSEL_LETTERS[..HENSEL_LETTER_COUNTS[count as usize]];
+            if letters.is_empty() {
+                // 0 and 8 neighbors form a single configuration each
+                for configuration in configurations_with_count(count) {
+                    assert!(!seen[configuration as usize]);
+                    seen[configuration as usize] = true;
+                }
+                class_count += 1;
+                continue;
+            }
+
+            for letter in letters.chars() {
+                let class = hensel_class(count, letter).unwrap();
+                class_count += 1;
+                for configuration in class {
+                    assert_eq!(configuration.count_ones(), count as u32, "{}{}", count, letter);
+                    assert!(!seen[configuration as usize], "{}{} overlaps another class", count, letter);
+                    seen[configuration as usize] = true;
+                }
+            }
+        }
+
+        assert!(seen.iter().all(|&covered| covered));
+        assert_eq!(class_count, 51);
+    }
+
+    #[test]
+    fn test_isotropic_rule() {
+        let rule: Rule = "B2-a/S12".parse().unwrap();
+        assert!(!rule.is_totalistic());
+        assert_eq!(rule.to_string(), "B2-a/S12");
+
+        // 2a: north and north-east are adjacent, so no birth
+        assert!(!rule.next_state(false, 0b0000_0011));
+        // 2e: north and east
+        assert!(rule.next_state(false, 0b0000_0101));
+        // Survival is totalistic
+        assert!(rule.next_state(true, 0b1000_0000));
+        assert!(!rule.next_state(true, 0));
+
+        // Listing every letter is the same as the bare digit
+        assert_eq!("B2cekain/S".parse::<Rule>().unwrap(), "B2/S".parse::<Rule>().unwrap());
+        assert_eq!("B2-a/S".parse::<Rule>().unwrap(), "B2ceikn/S".parse::<Rule>().unwrap());
+    }
+
+    #[test]
+    fn test_display_prefers_shorter_form() {
+        let rule: Rule = "B3aceijknqr/S23".parse().unwrap();
+        assert_eq!(rule.to_string(), "B3-y/S23");
+
+        let rule: Rule = "b3-cekainyq/s4w".parse().unwrap();
+        assert_eq!(rule.to_string(), "B3jr/S4w");
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let rule: Rule = serde_yaml::from_str("\"B36/S23\"").unwrap();
+        assert_eq!(rule.to_string(), "B36/S23");
+        assert_eq!(serde_yaml::to_string(&rule).unwrap().trim(), "B36/S23");
+    }
+}
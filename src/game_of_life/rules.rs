@@ -1,6 +1,6 @@
 //! Game of Life rules implementation (adapted from existing implementation)
 
-use super::Grid;
+use super::{Grid, Rule};
 use rayon::prelude::*;
 
 /// Game of Life rules engine
@@ -10,6 +10,11 @@ impl GameOfLifeRules {
     /// Apply Game of Life rules to evolve the grid one generation forward
     /// (Adapted from the existing implementation's update function)
     pub fn evolve(current: &Grid) -> Grid {
+        Self::evolve_with_rule(current, &Rule::conway())
+    }
+
+    /// Evolve the grid one generation forward under the given rule
+    pub fn evolve_with_rule(current: &Grid, rule: &Rule) -> Grid {
         let mut next = Grid::new(current.width, current.height, current.boundary_condition.clone());
         
         // Use parallel processing for better performance on large grids
@@ -17,14 +22,10 @@ impl GameOfLifeRules {
             .into_par_iter()
             .flat_map(|row| {
                 (0..current.width).into_par_iter().map(move |col| {
-                    let neighbors = current.count_neighbors(row, col);
+                    let configuration = current.neighbor_configuration(row, col);
                     let current_cell = current.get(row, col);
                     
-                    // Apply Conway's Game of Life rules
-                    match (current_cell, neighbors) {
-                        (true, 2) | (true, 3) | (false, 3) => true,  // Survive or birth
-                        _ => false,  // Death
-                    }
+                    rule.next_state(current_cell, configuration)
                 })
             })
             .collect();
@@ -34,43 +35,51 @@ impl GameOfLifeRules {
     }
 
     /// Evolve the grid for multiple generations
-    pub fn evolve_generations(mut grid: Grid, generations: usize) -> Grid {
+    pub fn evolve_generations(grid: Grid, generations: usize) -> Grid {
+        Self::evolve_generations_with_rule(grid, generations, &Rule::conway())
+    }
+
+    /// Evolve the grid for multiple generations under the given rule
+    pub fn evolve_generations_with_rule(mut grid: Grid, generations: usize, rule: &Rule) -> Grid {
         for _ in 0..generations {
-            grid = Self::evolve(&grid);
+            grid = Self::evolve_with_rule(&grid, rule);
         }
         grid
     }
 
     /// Check if a cell should be alive in the next generation given its current state and neighbor count
+    /// (Conway's rule; use `Rule::should_be_alive` for other rules)
     pub fn should_be_alive(current_state: bool, neighbor_count: u8) -> bool {
-        match (current_state, neighbor_count) {
-            (true, 2) | (true, 3) | (false, 3) => true,
-            _ => false,
-        }
+        Rule::conway().should_be_alive(current_state, neighbor_count)
     }
 
     /// Get all possible neighbor counts that would result in a live cell
     pub fn live_neighbor_counts() -> Vec<u8> {
-        vec![2, 3] // For live cells: 2 or 3 neighbors to survive
+        Self::survival_neighbor_counts()
     }
 
-    /// Get neighbor counts that would result in birth (dead -> alive)
+    /// Get neighbor counts that would result in birth (dead -> alive) under Conway's rule
     pub fn birth_neighbor_counts() -> Vec<u8> {
-        vec![3] // For dead cells: exactly 3 neighbors for birth
+        Rule::conway().birth_counts()
     }
 
-    /// Get neighbor counts that would result in survival (alive -> alive)
+    /// Get neighbor counts that would result in survival (alive -> alive) under Conway's rule
     pub fn survival_neighbor_counts() -> Vec<u8> {
-        vec![2, 3] // For live cells: 2 or 3 neighbors to survive
+        Rule::conway().survival_counts()
     }
 
     /// Validate that a predecessor state correctly evolves to the target state
     pub fn validate_evolution(predecessor: &Grid, target: &Grid, generations: usize) -> bool {
+        Self::validate_evolution_with_rule(predecessor, target, generations, &Rule::conway())
+    }
+
+    /// Validate that a predecessor state evolves to the target state under the given rule
+    pub fn validate_evolution_with_rule(predecessor: &Grid, target: &Grid, generations: usize, rule: &Rule) -> bool {
         if predecessor.width != target.width || predecessor.height != target.height {
             return false;
         }
         
-        let evolved = Self::evolve_generations(predecessor.clone(), generations);
+        let evolved = Self::evolve_generations_with_rule(predecessor.clone(), generations, rule);
         evolved == *target
     }
 
@@ -169,6 +178,21 @@ mod tests {
         assert!(!GameOfLifeRules::validate_evolution(&predecessor, &target, 2)); // Should be back to original after 2 steps
     }
 
+    #[test]
+    fn test_highlife_six_neighbor_birth() {
+        // Six neighbors: dead in Conway, born in HighLife
+        let cells = vec![
+            vec![true, true, true],
+            vec![false, false, false],
+            vec![true, true, true],
+        ];
+        let grid = Grid::from_cells(cells, BoundaryCondition::Dead).unwrap();
+        let highlife: Rule = "B36/S23".parse().unwrap();
+
+        assert!(!GameOfLifeRules::evolve(&grid).get(1, 1));
+        assert!(GameOfLifeRules::evolve_with_rule(&grid, &highlife).get(1, 1));
+    }
+
     #[test]
     fn test_neighbor_count_constants() {
         assert_eq!(GameOfLifeRules::max_neighbor_count(), 8);
@@ -10,7 +10,7 @@ pub mod reverse;
 pub mod utils;
 
 pub use config::Settings;
-pub use reverse::{ReverseProblem, Solution};
+pub use reverse::{GardenOfEdenReport, ReverseProblem, Solution};
 
 use anyhow::Result;
 
@@ -18,4 +18,11 @@ use anyhow::Result;
 pub fn solve_reverse(settings: Settings) -> Result<Vec<Solution>> {
     let mut problem = ReverseProblem::new(settings)?;
     problem.solve()
-}
\ No newline at end of file
+}
+
+/// Check whether the configured target state is a Garden of Eden or orphan
+pub fn detect_garden_of_eden(mut settings: Settings) -> Result<GardenOfEdenReport> {
+    let target_file = settings.input.target_state_file.clone();
+    let target = game_of_life::load_target_for_settings(&target_file, &mut settings)?.pattern;
+    reverse::check_garden_of_eden(&settings, target)
+}
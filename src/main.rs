@@ -3,9 +3,13 @@
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use game_of_life_reverse::{
-    config::{Settings, CliOverrides},
-    game_of_life::{create_example_grids, load_grid_from_file},
-    reverse::ReverseProblem,
+    config::{BoundaryCondition, CliOverrides, ImageImportConfig, Settings},
+    game_of_life::{
+        create_example_grids, load_grid_from_file, load_grid_from_image, load_target_for_settings, save_pattern_file,
+        Font, Grid, PatternFile, PatternFormat, TargetPattern,
+    },
+    sat::{parse_model, CellFormula},
+    reverse::{check_garden_of_eden, count_predecessors, explain_unreachable, EdenStatus, ReverseProblem, CROSS_CHECK_MAX_CELLS},
     utils::{SolutionFormatter, ColorOutput},
 };
 use std::path::PathBuf;
@@ -93,6 +97,165 @@ enum Commands {
         #[arg(short, long)]
         target: PathBuf,
     },
+    
+    /// Check whether a target is a Garden of Eden, with a checked proof
+    GardenOfEden {
+        /// Configuration file path
+        #[arg(short, long, default_value = "config/default.yaml")]
+        config: PathBuf,
+        
+        /// Target state file
+        #[arg(short, long)]
+        target: PathBuf,
+        
+        /// Extra cells per side the predecessor may use (overrides config)
+        #[arg(short, long)]
+        margin: Option<usize>,
+        
+        /// Write the DRAT proof to this file
+        #[arg(long)]
+        proof: Option<PathBuf>,
+        
+        /// Write the refuted formula in DIMACS CNF format to this file
+        #[arg(long)]
+        cnf: Option<PathBuf>,
+    },
+    
+    /// Explain which target cells make a target unreachable
+    Explain {
+        /// Configuration file path
+        #[arg(short, long, default_value = "config/default.yaml")]
+        config: PathBuf,
+        
+        /// Target state file
+        #[arg(short, long)]
+        target: PathBuf,
+        
+        /// Number of generations (overrides config)
+        #[arg(short, long)]
+        generations: Option<usize>,
+    },
+    
+    /// Count the distinct predecessors of a target without enumerating them
+    Count {
+        /// Configuration file path
+        #[arg(short, long, default_value = "config/default.yaml")]
+        config: PathBuf,
+        
+        /// Target state file
+        #[arg(short, long)]
+        target: PathBuf,
+        
+        /// Number of generations (overrides config)
+        #[arg(short, long)]
+        generations: Option<usize>,
+        
+        /// Extra cells per side the predecessor may use (overrides config)
+        #[arg(short, long)]
+        margin: Option<usize>,
+        
+        /// Cross-check the count by brute force (small grids only)
+        #[arg(long)]
+        check: bool,
+    },
+    
+    /// Write the SAT formula for a target to a DIMACS CNF file, for an external solver
+    ExportCnf {
+        /// Configuration file path
+        #[arg(short, long, default_value = "config/default.yaml")]
+        config: PathBuf,
+        
+        /// Target state file
+        #[arg(short, long)]
+        target: PathBuf,
+        
+        /// Number of generations (overrides config)
+        #[arg(short, long)]
+        generations: Option<usize>,
+        
+        /// Extra cells per side the predecessor may use (overrides config)
+        #[arg(short, long)]
+        margin: Option<usize>,
+        
+        /// CNF file to write
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    
+    /// Read an external solver's model of an exported CNF back as a solution
+    ImportModel {
+        /// Configuration file path
+        #[arg(short, long, default_value = "config/default.yaml")]
+        config: PathBuf,
+        
+        /// Target state file the CNF was exported for
+        #[arg(short, long)]
+        target: PathBuf,
+        
+        /// Number of generations (overrides config)
+        #[arg(short, long)]
+        generations: Option<usize>,
+        
+        /// Extra cells per side the predecessor may use (overrides config)
+        #[arg(short, long)]
+        margin: Option<usize>,
+        
+        /// CNF file written by export-cnf
+        #[arg(long)]
+        cnf: PathBuf,
+        
+        /// Solver output with 's' and 'v' lines
+        #[arg(long)]
+        model: PathBuf,
+        
+        /// Output directory (overrides config)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    
+    /// Render text with a built-in bitmap font into a target state file
+    Text {
+        /// Text to render; "\n" starts a new line
+        text: String,
+        
+        /// Font: 3x5 or 5x7
+        #[arg(short, long, default_value = "5x7")]
+        font: String,
+        
+        /// Dead columns between letters and dead rows between lines
+        #[arg(short, long, default_value_t = 1)]
+        spacing: usize,
+        
+        /// Dead cells around the text
+        #[arg(short, long, default_value_t = 2)]
+        padding: usize,
+        
+        /// Target file to write, in the format of its extension
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    
+    /// Convert a PBM or PGM image into a target state file
+    Image {
+        /// Image to convert; dark pixels become live cells
+        image: PathBuf,
+        
+        /// Darkness from 0 (white) to 1 (black) at which a cell is alive
+        #[arg(short, long, default_value_t = 0.5)]
+        threshold: f64,
+        
+        /// Side of the square of pixels merged into each cell
+        #[arg(short, long, default_value_t = 1)]
+        downscale: usize,
+        
+        /// Make light pixels alive instead of dark ones
+        #[arg(short, long)]
+        invert: bool,
+        
+        /// Target file to write, in the format of its extension
+        #[arg(short, long)]
+        output: PathBuf,
+    },
 }
 
 fn main() -> Result<()> {
@@ -117,6 +280,27 @@ fn main() -> Result<()> {
         Commands::Analyze { config, target } => {
             analyze_command(config, target)
         }
+        Commands::GardenOfEden { config, target, margin, proof, cnf } => {
+            garden_of_eden_command(config, target, margin, proof, cnf)
+        }
+        Commands::Explain { config, target, generations } => {
+            explain_command(config, target, generations)
+        }
+        Commands::Count { config, target, generations, margin, check } => {
+            count_command(config, target, generations, margin, check)
+        }
+        Commands::ExportCnf { config, target, generations, margin, output } => {
+            export_cnf_command(config, target, generations, margin, output)
+        }
+        Commands::ImportModel { config, target, generations, margin, cnf, model, output } => {
+            import_model_command(config, target, generations, margin, cnf, model, output)
+        }
+        Commands::Text { text, font, spacing, padding, output } => {
+            text_command(text, font, spacing, padding, output)
+        }
+        Commands::Image { image, threshold, downscale, invert, output } => {
+            image_command(image, ImageImportConfig { threshold, downscale, invert }, output)
+        }
     }
 }
 
@@ -212,7 +396,7 @@ fn solve_command(
     
     // Save solutions
     println!("\n{}", ColorOutput::info("💾 Saving solutions..."));
-    SolutionFormatter::save_solutions(&solutions, &settings.output.output_directory, &settings.output.format)
+    SolutionFormatter::save_solutions(&solutions, &settings.output.output_directory, &settings.output)
         .context("Failed to save solutions")?;
     
     println!("{}", ColorOutput::success(&format!(
@@ -295,7 +479,7 @@ fn validate_command(
     println!("{}", ColorOutput::info("🔍 Validating solution..."));
     
     // Load configuration
-    let settings = if config_path.exists() {
+    let mut settings = if config_path.exists() {
         Settings::from_file(&config_path)?
     } else {
         Settings::default()
@@ -305,12 +489,13 @@ fn validate_command(
     let predecessor = load_grid_from_file(&predecessor_path, settings.simulation.boundary_condition.clone())
         .with_context(|| format!("Failed to load predecessor from {}", predecessor_path.display()))?;
     
-    let target = load_grid_from_file(&target_path, settings.simulation.boundary_condition.clone())
-        .with_context(|| format!("Failed to load target from {}", target_path.display()))?;
+    let target = load_target_for_settings(&target_path, &mut settings)
+        .with_context(|| format!("Failed to load target from {}", target_path.display()))?
+        .pattern;
     
     // Validate
     let validator = game_of_life_reverse::reverse::SolutionValidator::new(settings);
-    let result = validator.validate(&predecessor, &target)
+    let result = validator.validate_pattern(&predecessor, &target)
         .context("Validation failed")?;
     
     println!("{}", result);
@@ -339,25 +524,34 @@ fn analyze_command(config_path: PathBuf, target_path: PathBuf) -> Result<()> {
     println!("{}", ColorOutput::info("🔬 Analyzing target state..."));
     
     // Load configuration
-    let settings = if config_path.exists() {
+    let mut settings = if config_path.exists() {
         Settings::from_file(&config_path)?
     } else {
         Settings::default()
     };
     
-    // Load target grid
-    let target = load_grid_from_file(&target_path, settings.simulation.boundary_condition.clone())
-        .with_context(|| format!("Failed to load target from {}", target_path.display()))?;
+    // Load target pattern
+    let target = load_target_for_settings(&target_path, &mut settings)
+        .with_context(|| format!("Failed to load target from {}", target_path.display()))?
+        .pattern;
+    let grid = &target.grid;
     
-    println!("Target Grid ({}x{}):", target.width, target.height);
-    println!("{}", SolutionFormatter::format_grid_with_coords(&target));
+    println!("Target Grid ({}x{}):", grid.width, grid.height);
+    if target.is_fully_specified() {
+        println!("{}", SolutionFormatter::format_grid_with_coords(grid));
+    } else {
+        println!("{}", target);
+    }
     
     println!("Grid Statistics:");
-    println!("  Living cells: {}", target.living_count());
-    println!("  Density: {:.1}%", (target.living_count() as f64 / (target.width * target.height) as f64) * 100.0);
+    println!("  Living cells: {}", grid.living_count());
+    println!("  Density: {:.1}%", (grid.living_count() as f64 / (grid.width * grid.height) as f64) * 100.0);
+    if !target.is_fully_specified() {
+        println!("  Don't-care cells: {}", target.dont_care_count());
+    }
     
     // Create problem for analysis
-    let problem = ReverseProblem::with_target_grid(settings, target)
+    let problem = ReverseProblem::with_target_pattern(settings, target)
         .context("Failed to create problem for analysis")?;
     
     let estimate = problem.estimate_solvability();
@@ -369,6 +563,312 @@ fn analyze_command(config_path: PathBuf, target_path: PathBuf) -> Result<()> {
     Ok(())
 }
 
+fn garden_of_eden_command(
+    config_path: PathBuf,
+    target_path: PathBuf,
+    margin: Option<usize>,
+    proof_path: Option<PathBuf>,
+    cnf_path: Option<PathBuf>,
+) -> Result<()> {
+    println!("{}", ColorOutput::info("🌱 Checking for a Garden of Eden..."));
+    
+    // Load configuration
+    let mut settings = if config_path.exists() {
+        Settings::from_file(&config_path)?
+    } else {
+        Settings::default()
+    };
+    if let Some(margin) = margin {
+        settings.simulation.margin = margin;
+    }
+    settings.input.target_state_file = target_path.clone();
+    settings.validate()
+        .context("Configuration validation failed")?;
+    
+    let target = load_target_for_settings(&target_path, &mut settings)
+        .with_context(|| format!("Failed to load target from {}", target_path.display()))?
+        .pattern;
+    
+    println!("Boundary: {:?}, margin: {}", settings.simulation.boundary_condition, settings.simulation.margin);
+    
+    let start_time = Instant::now();
+    let report = check_garden_of_eden(&settings, target)
+        .context("Garden of Eden check failed")?;
+    
+    println!("{}", report);
+    
+    match report.status {
+        EdenStatus::GardenOfEden | EdenStatus::Orphan => {
+            println!("{}", ColorOutput::success(&format!(
+                "✅ Certified in {:.3}s", start_time.elapsed().as_secs_f64()
+            )));
+        }
+        EdenStatus::HasPredecessor => {
+            if let Some(predecessor) = &report.predecessor {
+                println!("Predecessor:");
+                println!("{}", SolutionFormatter::format_grid_compact(predecessor));
+            }
+        }
+        EdenStatus::Unknown => {
+            println!("{}", ColorOutput::warning("⏱️  Timed out before reaching an answer"));
+        }
+    }
+    
+    if let Some(certificate) = &report.certificate {
+        if let Some(path) = proof_path {
+            std::fs::write(&path, certificate.drat())
+                .with_context(|| format!("Failed to write proof to {}", path.display()))?;
+            println!("Proof saved to {}", path.display());
+        }
+        if let Some(path) = cnf_path {
+            std::fs::write(&path, certificate.dimacs())
+                .with_context(|| format!("Failed to write formula to {}", path.display()))?;
+            println!("Formula saved to {}", path.display());
+        }
+    }
+    
+    Ok(())
+}
+
+fn explain_command(config_path: PathBuf, target_path: PathBuf, generations: Option<usize>) -> Result<()> {
+    println!("{}", ColorOutput::info("🧩 Looking for the cells that make the target unreachable..."));
+    
+    // Load configuration
+    let mut settings = if config_path.exists() {
+        Settings::from_file(&config_path)?
+    } else {
+        Settings::default()
+    };
+    if let Some(generations) = generations {
+        settings.simulation.generations = generations;
+    }
+    settings.input.target_state_file = target_path.clone();
+    settings.validate()
+        .context("Configuration validation failed")?;
+    
+    let target = load_target_for_settings(&target_path, &mut settings)
+        .with_context(|| format!("Failed to load target from {}", target_path.display()))?
+        .pattern;
+    
+    let core = explain_unreachable(&settings, target)
+        .context("Failed to explain the target")?;
+    
+    match core {
+        Some(core) => {
+            println!("{}", ColorOutput::warning(&format!(
+                "❌ No predecessor {} generation(s) back; these {} cell(s) are already unreachable together:",
+                settings.simulation.generations, core.cells.len()
+            )));
+            println!("{}", SolutionFormatter::format_core(&core));
+            println!("▓▓/░░ core cell (alive/dead), ██/·· other cell, ?? don't-care");
+            if core.minimal {
+                println!("Marking any one core cell as don't-care (?) makes the rest reachable.");
+            } else {
+                println!("{}", ColorOutput::warning(
+                    "⏱️  Timed out while shrinking the core; some of its cells may not be needed"
+                ));
+            }
+        }
+        None => {
+            println!("{}", ColorOutput::success("✅ The target has a predecessor, nothing to explain"));
+        }
+    }
+    
+    Ok(())
+}
+
+fn count_command(
+    config_path: PathBuf,
+    target_path: PathBuf,
+    generations: Option<usize>,
+    margin: Option<usize>,
+    check: bool,
+) -> Result<()> {
+    println!("{}", ColorOutput::info("🔢 Counting predecessors..."));
+    
+    // Load configuration
+    let mut settings = if config_path.exists() {
+        Settings::from_file(&config_path)?
+    } else {
+        Settings::default()
+    };
+    if let Some(generations) = generations {
+        settings.simulation.generations = generations;
+    }
+    if let Some(margin) = margin {
+        settings.simulation.margin = margin;
+    }
+    settings.input.target_state_file = target_path.clone();
+    settings.validate()
+        .context("Configuration validation failed")?;
+    
+    let target = load_target_for_settings(&target_path, &mut settings)
+        .with_context(|| format!("Failed to load target from {}", target_path.display()))?
+        .pattern;
+    
+    if check {
+        println!("Cross-checking by brute force (at most {} predecessor cells)", CROSS_CHECK_MAX_CELLS);
+    }
+    let counted = count_predecessors(&settings, target, check)
+        .context("Failed to count predecessors")?;
+    println!("Counted over {} clauses: {} decisions, {} components, {} cache hits",
+            counted.statistics.clauses,
+            counted.statistics.decisions,
+            counted.statistics.components,
+            counted.statistics.cache_hits);
+    
+    match counted.count {
+        Some(count) => {
+            println!("{}", ColorOutput::success(&format!(
+                "✅ {} distinct predecessor(s) {} generation(s) back, counted in {:.3}s",
+                count, settings.simulation.generations, counted.count_time.as_secs_f64()
+            )));
+            if counted.brute_force.is_some() {
+                println!("{}", ColorOutput::success("✅ Brute force agrees"));
+            }
+        }
+        None => {
+            println!("{}", ColorOutput::warning("⏱️  Timed out before the count finished"));
+        }
+    }
+    
+    Ok(())
+}
+
+/// Load the settings and target shared by `export-cnf` and `import-model`
+fn load_cnf_problem(
+    config_path: PathBuf,
+    target_path: PathBuf,
+    generations: Option<usize>,
+    margin: Option<usize>,
+) -> Result<ReverseProblem> {
+    let mut settings = if config_path.exists() {
+        Settings::from_file(&config_path)?
+    } else {
+        Settings::default()
+    };
+    if let Some(generations) = generations {
+        settings.simulation.generations = generations;
+    }
+    if let Some(margin) = margin {
+        settings.simulation.margin = margin;
+    }
+    settings.input.target_state_file = target_path.clone();
+    settings.validate()
+        .context("Configuration validation failed")?;
+    
+    let target = load_target_for_settings(&target_path, &mut settings)
+        .with_context(|| format!("Failed to load target from {}", target_path.display()))?;
+    
+    Ok(ReverseProblem::with_target_pattern(settings, target.pattern)
+        .context("Failed to create reverse problem")?
+        .with_source(target.source))
+}
+
+fn export_cnf_command(
+    config_path: PathBuf,
+    target_path: PathBuf,
+    generations: Option<usize>,
+    margin: Option<usize>,
+    output_path: PathBuf,
+) -> Result<()> {
+    println!("{}", ColorOutput::info("📝 Exporting the SAT formula..."));
+    
+    let mut problem = load_cnf_problem(config_path, target_path, generations, margin)?;
+    let formula = problem.export_cnf()
+        .context("Failed to generate the formula")?;
+    
+    std::fs::write(&output_path, formula.to_dimacs())
+        .with_context(|| format!("Failed to write formula to {}", output_path.display()))?;
+    
+    println!("{}", ColorOutput::success(&format!(
+        "✅ {} clauses over {} variables ({} cells) saved to {}",
+        formula.clauses.len(), formula.variable_count, formula.cells.len(), output_path.display()
+    )));
+    
+    Ok(())
+}
+
+fn import_model_command(
+    config_path: PathBuf,
+    target_path: PathBuf,
+    generations: Option<usize>,
+    margin: Option<usize>,
+    cnf_path: PathBuf,
+    model_path: PathBuf,
+    output_dir: Option<PathBuf>,
+) -> Result<()> {
+    println!("{}", ColorOutput::info("📥 Importing a solver model..."));
+    
+    let problem = load_cnf_problem(config_path, target_path, generations, margin)?;
+    
+    let cnf = std::fs::read_to_string(&cnf_path)
+        .with_context(|| format!("Failed to read {}", cnf_path.display()))?;
+    let formula = CellFormula::from_dimacs(&cnf)
+        .with_context(|| format!("Failed to parse formula from {}", cnf_path.display()))?;
+    let output = std::fs::read_to_string(&model_path)
+        .with_context(|| format!("Failed to read {}", model_path.display()))?;
+    let model = parse_model(&output)
+        .with_context(|| format!("Failed to parse solver output from {}", model_path.display()))?;
+    
+    let solution = problem.solution_from_model(&formula, &model)?;
+    println!("{}", ColorOutput::success("✅ The model is a valid predecessor"));
+    println!("{}", SolutionFormatter::format_solution(&solution, false));
+    
+    let output_dir = output_dir.unwrap_or_else(|| problem.settings().output.output_directory.clone());
+    SolutionFormatter::save_solutions(&[solution], &output_dir, &problem.settings().output)
+        .context("Failed to save solution")?;
+    println!("{}", ColorOutput::success(&format!("Solution saved to {}", output_dir.display())));
+    
+    Ok(())
+}
+
+fn text_command(text: String, font: String, spacing: usize, padding: usize, output_path: PathBuf) -> Result<()> {
+    println!("{}", ColorOutput::info("🔤 Rendering text..."));
+    
+    let font: Font = font.parse()?;
+    let text = text.replace("\\n", "\n");
+    let grid = Grid::from_text(&text, font, spacing, padding)
+        .context("Failed to render text")?;
+    println!("{}", SolutionFormatter::format_grid_compact(&grid));
+    println!("{}x{} grid with {} living cells", grid.width, grid.height, grid.living_count());
+    
+    let format = output_path.extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(PatternFormat::from_extension)
+        .unwrap_or(PatternFormat::Plain);
+    let mut file = PatternFile::new(TargetPattern::from_grid(grid));
+    file.source.name = Some(text.replace('\n', " "));
+    file.source.comments.push(format!("Rendered in the {} font", font));
+    save_pattern_file(&file, format, &output_path)?;
+    println!("{}", ColorOutput::success(&format!("Target saved to {}", output_path.display())));
+    
+    Ok(())
+}
+
+fn image_command(image_path: PathBuf, options: ImageImportConfig, output_path: PathBuf) -> Result<()> {
+    println!("{}", ColorOutput::info("🖼️ Converting image..."));
+    
+    let grid = load_grid_from_image(&image_path, &options, BoundaryCondition::Dead)?;
+    println!("{}", SolutionFormatter::format_grid_compact(&grid));
+    println!("{}x{} grid with {} living cells", grid.width, grid.height, grid.living_count());
+    
+    let format = output_path.extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(PatternFormat::from_extension)
+        .unwrap_or(PatternFormat::Plain);
+    let mut file = PatternFile::new(TargetPattern::from_grid(grid));
+    file.source.name = image_path.file_stem().map(|stem| stem.to_string_lossy().into_owned());
+    file.source.comments.push(format!(
+        "Converted from {} at threshold {}, downscaled {}x{}",
+        image_path.display(), options.threshold, options.downscale, options.downscale
+    ));
+    save_pattern_file(&file, format, &output_path)?;
+    println!("{}", ColorOutput::success(&format!("Target saved to {}", output_path.display())));
+    
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -387,6 +887,35 @@ mod tests {
         assert!(cli.is_ok());
     }
 
+    #[test]
+    fn test_text_command() {
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir.path().join("hi.rle");
+        text_command("Hi\\n42".to_string(), "3x5".to_string(), 1, 1, output.clone()).unwrap();
+
+        let file = game_of_life_reverse::game_of_life::load_pattern_file(&output, game_of_life_reverse::config::BoundaryCondition::Dead).unwrap();
+        assert_eq!(file.pattern.grid, Grid::from_text("Hi\n42", Font::Small, 1, 1).unwrap());
+        assert_eq!(file.source.name.as_deref(), Some("Hi 42"));
+        assert!(text_command("Hi".to_string(), "9x9".to_string(), 1, 1, output).is_err());
+    }
+
+    #[test]
+    fn test_image_command() {
+        let temp_dir = tempdir().unwrap();
+        let image = temp_dir.path().join("logo.pgm");
+        std::fs::write(&image, "P2\n4 2\n255\n0 0 255 255\n0 0 255 200\n").unwrap();
+        let output = temp_dir.path().join("logo.cells");
+
+        let options = ImageImportConfig { downscale: 2, ..ImageImportConfig::default() };
+        image_command(image.clone(), options, output.clone()).unwrap();
+        let file = game_of_life_reverse::game_of_life::load_pattern_file(&output, BoundaryCondition::Dead).unwrap();
+        assert_eq!(file.pattern.grid, Grid::from_cells(vec![vec![true, false]], BoundaryCondition::Dead).unwrap());
+        assert_eq!(file.source.name.as_deref(), Some("logo"));
+
+        let options = ImageImportConfig { threshold: 1.5, ..ImageImportConfig::default() };
+        assert!(image_command(image, options, output).is_err());
+    }
+
     #[test]
     fn test_setup_command() {
         let temp_dir = tempdir().unwrap();
@@ -0,0 +1,180 @@
+//! Enlarged search canvas for predecessors that spill outside the target
+//!
+//! With a margin of `m` cells per side, the target window sits in the middle
+//! of a larger canvas. Generation `t` may only have live cells within
+//! `m - t` cells of the window, so the live area shrinks toward the target.
+//! Cells past that bound are constrained dead, and since those constraints go
+//! through the transition clauses, nothing can be born outside the canvas
+//! either: every predecessor found evolves the same way on the infinite plane.
+//!
+//! When cells outside the window are left free at the final generation, only
+//! the light cone of the window matters: a cell more than `T - t` cells away
+//! at generation `t` cannot affect it, so the margin is capped at the number
+//! of generations and such cells are not constrained.
+
+use crate::config::SimulationConfig;
+use crate::game_of_life::{Grid, TargetPattern};
+
+/// Geometry of the canvas around a target window
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Canvas {
+    window_width: usize,
+    window_height: usize,
+    generations: usize,
+    margin: usize,
+    free_margin: bool,
+}
+
+impl Canvas {
+    /// Canvas for a target of the given size under the simulation settings
+    pub fn new(simulation: &SimulationConfig, window_width: usize, window_height: usize) -> Self {
+        let margin = if simulation.free_margin {
+            simulation.margin.min(simulation.generations)
+        } else {
+            simulation.margin
+        };
+
+        Self {
+            window_width,
+            window_height,
+            generations: simulation.generations,
+            margin,
+            free_margin: simulation.free_margin,
+        }
+    }
+
+    /// Cells added on each side of the window
+    ///
+    /// One more than the margin, so cells that would be born just outside the
+    /// allowed area are represented and can be constrained dead.
+    pub fn padding(&self) -> usize {
+        if self.margin == 0 { 0 } else { self.margin + 1 }
+    }
+
+    /// Canvas width
+    pub fn width(&self) -> usize {
+        self.window_width + 2 * self.padding()
+    }
+
+    /// Canvas height
+    pub fn height(&self) -> usize {
+        self.window_height + 2 * self.padding()
+    }
+
+    /// Check whether the canvas is just the target window
+    pub fn is_window(&self) -> bool {
+        self.padding() == 0
+    }
+
+    /// How far from the window generation `t` may have live cells
+    pub fn generation_margin(&self, t: usize) -> usize {
+        self.margin.saturating_sub(t)
+    }
+
+    /// Chebyshev distance from canvas cell `(x, y)` to the target window
+    pub fn distance_to_window(&self, x: usize, y: usize) -> usize {
+        let padding = self.padding();
+        let axis_distance = |position: usize, length: usize| {
+            if position < padding {
+                padding - position
+            } else {
+                position.saturating_sub(padding + length - 1)
+            }
+        };
+        axis_distance(x, self.window_width).max(axis_distance(y, self.window_height))
+    }
+
+    /// The target placed in the middle of the canvas
+    ///
+    /// Surrounding cells must be dead, or are don't-care with a free margin.
+    pub fn embed(&self, target: &TargetPattern) -> TargetPattern {
+        let padding = self.padding();
+        let mut grid = Grid::new(self.width(), self.height(), target.grid.boundary_condition.clone());
+        let mut care = vec![!self.free_margin; self.width() * self.height()];
+
+        for row in 0..self.window_height {
+            for col in 0..self.window_width {
+                let index = grid.index(row + padding, col + padding);
+                grid.cells[index] = target.grid.get(row, col);
+                care[index] = target.cares(row, col);
+            }
+        }
+
+        TargetPattern { grid, care }
+    }
+
+    /// Cells `(x, y, t)` that must be dead before the final generation
+    pub fn dead_cells(&self) -> Vec<(usize, usize, usize)> {
+        let mut cells = Vec::new();
+
+        for t in 0..self.generations {
+            for y in 0..self.height() {
+                for x in 0..self.width() {
+                    let distance = self.distance_to_window(x, y);
+                    // Outside the light cone a free margin leaves cells alone,
+                    // except in the predecessor, which must fit the canvas
+                    let matters = !self.free_margin || t == 0 || distance <= self.generations - t;
+                    if distance > self.generation_margin(t) && matters {
+                        cells.push((x, y, t));
+                    }
+                }
+            }
+        }
+
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{BoundaryCondition, Settings};
+
+    fn canvas(margin: usize, generations: usize, free_margin: bool) -> Canvas {
+        let mut simulation = Settings::default().simulation;
+        simulation.margin = margin;
+        simulation.generations = generations;
+        simulation.free_margin = free_margin;
+        Canvas::new(&simulation, 3, 1)
+    }
+
+    #[test]
+    fn test_canvas_geometry() {
+        assert!(canvas(0, 2, false).is_window());
+        assert!(canvas(0, 2, false).dead_cells().is_empty());
+
+        let canvas = canvas(2, 3, false);
+        assert_eq!((canvas.width(), canvas.height()), (9, 7));
+        assert_eq!(canvas.distance_to_window(3, 3), 0);
+        assert_eq!(canvas.distance_to_window(5, 3), 0);
+        assert_eq!(canvas.distance_to_window(8, 0), 3);
+        assert_eq!((0..4).map(|t| canvas.generation_margin(t)).collect::<Vec<_>>(), vec![2, 1, 0, 0]);
+
+        // Only the outer ring is dead in the predecessor, more later on
+        let dead = canvas.dead_cells();
+        let count_at = |t| dead.iter().filter(|cell| cell.2 == t).count();
+        assert_eq!(count_at(0), 9 * 7 - 7 * 5);
+        assert_eq!(count_at(1), 9 * 7 - 5 * 3);
+        assert_eq!(count_at(2), 9 * 7 - 3);
+        assert_eq!(count_at(3), 0);
+
+        let target = TargetPattern::from_grid(Grid::from_cells(vec![vec![true; 3]], BoundaryCondition::Dead).unwrap());
+        let embedded = canvas.embed(&target);
+        assert!(embedded.is_fully_specified());
+        assert_eq!(embedded.grid.living_cells(), vec![(3, 3), (3, 4), (3, 5)]);
+    }
+
+    #[test]
+    fn test_free_margin_follows_light_cone() {
+        // The margin is capped at the number of generations
+        let canvas = canvas(5, 2, true);
+        assert_eq!((canvas.width(), canvas.height()), (9, 7));
+
+        // Within the light cone nothing beyond the shrinking margin is constrained
+        let dead = canvas.dead_cells();
+        assert!(dead.iter().all(|&(x, y, t)| t == 0 && canvas.distance_to_window(x, y) == 3));
+
+        let target = TargetPattern::from_grid(Grid::new(3, 1, BoundaryCondition::Dead));
+        assert_eq!(canvas.embed(&target).dont_care_count(), 9 * 7 - 3);
+    }
+}
@@ -0,0 +1,139 @@
+//! Exact counts of the predecessors of a target
+//!
+//! Solution enumeration stops at `max_solutions`, so it cannot tell how many
+//! predecessors a pattern has once there are millions. Counting runs a
+//! projected model counter over the same CNF the solver sees, projected onto
+//! the predecessor's cells, so each distinct generation-0 grid on the canvas
+//! counts once. Small canvases can be cross-checked by simulating every
+//! candidate predecessor.
+
+use super::{Canvas, ReverseProblem};
+use crate::config::{Objective, Settings};
+use crate::game_of_life::TargetPattern;
+use crate::sat::{BruteForceSearch, CounterStatistics};
+use anyhow::{Context, Result};
+use std::time::{Duration, Instant};
+
+/// Most predecessor cells `brute_force_count` will enumerate, enough for a 5x5 grid
+pub const CROSS_CHECK_MAX_CELLS: usize = 25;
+
+/// Result of counting the predecessors of a target
+#[derive(Debug, Clone)]
+pub struct PredecessorCount {
+    /// Number of distinct predecessors, `None` if counting was interrupted
+    pub count: Option<u128>,
+    /// The count found by brute force, when a cross-check was asked for
+    pub brute_force: Option<u128>,
+    /// Time spent by the model counter
+    pub count_time: Duration,
+    /// How the model counter got there
+    pub statistics: CounterStatistics,
+}
+
+/// Count the predecessors of `target` within the configured generations
+///
+/// Symmetry breaking and population optimization are disabled, since both
+/// would leave predecessors out; population bounds still apply. With
+/// `cross_check`, the count is compared against `brute_force_count` and a
+/// mismatch is an error.
+pub fn count_predecessors(settings: &Settings, target: TargetPattern, cross_check: bool) -> Result<PredecessorCount> {
+    let mut settings = settings.clone();
+    settings.encoding.symmetry_breaking = false;
+    settings.solver.optimize = Objective::None;
+
+    let start_time = Instant::now();
+    let mut problem = ReverseProblem::with_target_pattern(settings.clone(), target.clone())?;
+    let (count, statistics) = problem.count_predecessors()?;
+    let count_time = start_time.elapsed();
+
+    let brute_force = if cross_check {
+        Some(brute_force_count(&settings, &target)?)
+    } else {
+        None
+    };
+
+    if let (Some(count), Some(expected)) = (count, brute_force) {
+        if count != expected {
+            anyhow::bail!("Model counting found {} predecessors, but brute force found {}", count, expected);
+        }
+    }
+
+    Ok(PredecessorCount { count, brute_force, count_time, statistics })
+}
+
+/// Count the predecessors of `target` by simulating every candidate on the canvas
+///
+/// Cells the canvas requires dead in the predecessor are left out, and the
+/// rest may hold at most `CROSS_CHECK_MAX_CELLS` cells.
+pub fn brute_force_count(settings: &Settings, target: &TargetPattern) -> Result<u128> {
+    let canvas = Canvas::new(&settings.simulation, target.grid.width, target.grid.height);
+    let search = BruteForceSearch::new(settings, canvas.width(), canvas.height())
+        .with_dead_cells(canvas.dead_cells());
+
+    let free = search.free_cells().len();
+    if free > CROSS_CHECK_MAX_CELLS {
+        anyhow::bail!(
+            "Brute force is limited to {} predecessor cells, but the canvas has {}",
+            CROSS_CHECK_MAX_CELLS,
+            free
+        );
+    }
+
+    search.count(&canvas.embed(target))?
+        .context("Brute-force count was interrupted")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BoundaryCondition;
+    use crate::game_of_life::io::parse_pattern_from_string;
+    use crate::game_of_life::Grid;
+
+    fn settings(generations: usize, margin: usize) -> Settings {
+        let mut settings = Settings::default();
+        settings.solver.backend = crate::config::SolverBackend::Cadical;
+        settings.simulation.generations = generations;
+        settings.simulation.margin = margin;
+        settings
+    }
+
+    #[test]
+    fn test_counts_match_brute_force() {
+        for (content, generations, margin) in [
+            ("010\n010\n010\n", 1, 0),
+            ("?1?\n?1?\n?1?\n", 1, 0),
+            ("0000\n0110\n0110\n0000\n", 1, 0),
+            ("000\n010\n000\n", 2, 0),
+            ("111\n", 1, 1),
+            ("0100\n0010\n1110\n0000\n", 1, 0),
+        ] {
+            let target = parse_pattern_from_string(content, BoundaryCondition::Dead).unwrap();
+            let counted = count_predecessors(&settings(generations, margin), target, true).unwrap();
+            assert_eq!(counted.count, counted.brute_force, "{:?}", content);
+        }
+
+        // A Garden of Eden has none
+        let target = parse_pattern_from_string("111\n", BoundaryCondition::Dead).unwrap();
+        assert_eq!(count_predecessors(&settings(1, 0), target, false).unwrap().count, Some(0));
+    }
+
+    #[test]
+    fn test_population_bounds_restrict_the_count() {
+        // The block has predecessors of many populations, from three cells up
+        let target = parse_pattern_from_string("0000\n0110\n0110\n0000\n", BoundaryCondition::Dead).unwrap();
+        let all = count_predecessors(&settings(1, 0), target.clone(), false).unwrap().count.unwrap();
+
+        let mut bounded = settings(1, 0);
+        bounded.encoding.max_population = Some(4);
+        let expected = brute_force_count(&bounded, &target).unwrap();
+        assert!(expected > 0 && expected < all);
+        assert_eq!(count_predecessors(&bounded, target, false).unwrap().count, Some(expected));
+    }
+
+    #[test]
+    fn test_brute_force_limit() {
+        let target = TargetPattern::from_grid(Grid::new(6, 5, BoundaryCondition::Dead));
+        assert!(brute_force_count(&settings(1, 0), &target).is_err());
+    }
+}
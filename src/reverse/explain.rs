@@ -0,0 +1,83 @@
+//! Explanations of why a target has no predecessor
+//!
+//! The specified target cells are handed to the solver as assumptions, and
+//! the assumptions it reports as failed are shrunk to a locally minimal core:
+//! a set of cells that is already unreachable on its own, and that becomes
+//! reachable as soon as any one of them is dropped. Editing one of those
+//! cells is the place to start when making a pattern reversible.
+
+use super::ReverseProblem;
+use crate::config::Settings;
+use crate::game_of_life::TargetPattern;
+use anyhow::Result;
+
+/// A set of target cells that no predecessor can produce together
+#[derive(Debug, Clone)]
+pub struct UnreachableCore {
+    /// The target that was explained
+    pub target: TargetPattern,
+    /// `(row, col)` of the cells in the core
+    pub cells: Vec<(usize, usize)>,
+    /// Whether shrinking finished; if the timeout stopped it, some cells may be droppable
+    pub minimal: bool,
+}
+
+impl UnreachableCore {
+    /// The target restricted to the core, with every other cell don't-care
+    pub fn pattern(&self) -> TargetPattern {
+        let mut care = vec![false; self.target.care.len()];
+        for &(row, col) in &self.cells {
+            care[self.target.grid.index(row, col)] = true;
+        }
+        TargetPattern::new(self.target.grid.clone(), care).expect("the core mask has one entry per cell")
+    }
+
+    /// Check whether the cell at the given coordinates is in the core
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        self.cells.contains(&(row, col))
+    }
+}
+
+/// Explain why `target` has no predecessor within the configured generations
+///
+/// Returns `None` when a predecessor exists. Symmetry breaking is disabled,
+/// since it is only sound when the whole target is required.
+pub fn explain_unreachable(settings: &Settings, target: TargetPattern) -> Result<Option<UnreachableCore>> {
+    let mut settings = settings.clone();
+    settings.encoding.symmetry_breaking = false;
+
+    let mut problem = ReverseProblem::with_target_pattern(settings, target.clone())?;
+    let core = problem.find_unreachable_core()?;
+
+    Ok(core.map(|core| UnreachableCore { target, cells: core.cells, minimal: core.minimal }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BoundaryCondition;
+    use crate::game_of_life::io::parse_pattern_from_string;
+
+    #[test]
+    fn test_core_is_unreachable_and_minimal() {
+        // A block with a stray cell below it has no predecessor within its box
+        let mut settings = Settings::default();
+        settings.solver.backend = crate::config::SolverBackend::Cadical;
+        settings.simulation.generations = 1;
+        let target = parse_pattern_from_string("0000\n0110\n0110\n0000\n1000\n", BoundaryCondition::Dead).unwrap();
+
+        let core = explain_unreachable(&settings, target).unwrap().unwrap();
+        assert!(!core.cells.is_empty() && core.minimal);
+
+        // The core alone has no predecessor, but loses that once any cell is dropped
+        assert!(explain_unreachable(&settings, core.pattern()).unwrap().is_some());
+        for index in 0..core.cells.len() {
+            let mut smaller = core.clone();
+            smaller.cells.remove(index);
+            assert!(explain_unreachable(&settings, smaller.pattern()).unwrap().is_none());
+        }
+
+        let reachable = parse_pattern_from_string("000\n111\n000\n", BoundaryCondition::Dead).unwrap();
+        assert!(explain_unreachable(&settings, reachable).unwrap().is_none());
+    }
+}
@@ -0,0 +1,171 @@
+//! Garden of Eden and orphan detection with checkable certificates
+//!
+//! A target without any predecessor is a Garden of Eden; when only some of
+//! its cells are specified (don't-care cells or a free margin), the specified
+//! part is an orphan, which no state can produce regardless of what surrounds
+//! it. Both claims hold for the configured boundary condition and margin. A
+//! negative answer comes with the CNF formula and a DRAT proof refuting it,
+//! checked before the claim is reported.
+
+use super::ReverseProblem;
+use crate::config::{Settings, SolverBackend};
+use crate::game_of_life::{Grid, TargetPattern};
+use crate::sat::constraints::Clause;
+use crate::sat::encoder::PredecessorSearch;
+use crate::sat::{formula_to_dimacs, verify_proof, DratProof, ProofSummary};
+use anyhow::{Context, Result};
+use std::fmt;
+
+/// What the search established about the target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EdenStatus {
+    /// The fully specified target has no predecessor
+    GardenOfEden,
+    /// No predecessor produces the specified cells of the target
+    Orphan,
+    /// A predecessor exists
+    HasPredecessor,
+    /// The search stopped before reaching an answer
+    Unknown,
+}
+
+/// A checked refutation of the predecessor formula
+#[derive(Debug, Clone)]
+pub struct Certificate {
+    pub formula: Vec<Clause>,
+    pub variable_count: usize,
+    pub proof: DratProof,
+    pub summary: ProofSummary,
+}
+
+impl Certificate {
+    /// The refuted formula in DIMACS CNF format
+    pub fn dimacs(&self) -> String {
+        formula_to_dimacs(&self.formula, self.variable_count)
+    }
+
+    /// The proof in textual DRAT format
+    pub fn drat(&self) -> String {
+        self.proof.to_string()
+    }
+}
+
+/// Result of a Garden of Eden check
+#[derive(Debug, Clone)]
+pub struct GardenOfEdenReport {
+    pub status: EdenStatus,
+    /// A predecessor on the search canvas, when one exists
+    pub predecessor: Option<Grid>,
+    /// The proof that none exists, when the target is a Garden of Eden or orphan
+    pub certificate: Option<Certificate>,
+}
+
+/// Check whether `target` has a predecessor one generation back
+///
+/// The boundary condition, margin and rule come from `settings`; the number
+/// of generations is fixed to one and symmetry breaking is disabled, so the
+/// certified formula is the plain transition encoding. The search always runs
+/// on CaDiCaL, the backend that records proofs.
+pub fn check_garden_of_eden(settings: &Settings, target: TargetPattern) -> Result<GardenOfEdenReport> {
+    let mut settings = settings.clone();
+    settings.solver.backend = SolverBackend::Cadical;
+    settings.simulation.generations = 1;
+    settings.solver.projection_generation = 0;
+    settings.encoding.symmetry_breaking = false;
+
+    let mut problem = ReverseProblem::with_target_pattern(settings, target)?;
+    let partial = !problem.canvas().embed(problem.target_pattern()).is_fully_specified();
+
+    let report = match problem.find_predecessor_with_proof()? {
+        PredecessorSearch::Found(predecessor) => GardenOfEdenReport {
+            status: EdenStatus::HasPredecessor,
+            predecessor: Some(predecessor),
+            certificate: None,
+        },
+        PredecessorSearch::Refuted { formula, variable_count, proof } => {
+            let summary = verify_proof(&formula, &proof)
+                .context("The solver found no predecessor, but its proof does not check")?;
+            GardenOfEdenReport {
+                status: if partial { EdenStatus::Orphan } else { EdenStatus::GardenOfEden },
+                predecessor: None,
+                certificate: Some(Certificate { formula, variable_count, proof, summary }),
+            }
+        }
+        PredecessorSearch::Interrupted => GardenOfEdenReport {
+            status: EdenStatus::Unknown,
+            predecessor: None,
+            certificate: None,
+        },
+    };
+
+    Ok(report)
+}
+
+impl fmt::Display for GardenOfEdenReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.status {
+            EdenStatus::GardenOfEden => writeln!(f, "Garden of Eden: the target has no predecessor")?,
+            EdenStatus::Orphan => writeln!(f, "Orphan: no predecessor produces the specified cells")?,
+            EdenStatus::HasPredecessor => writeln!(f, "Not a Garden of Eden: a predecessor exists")?,
+            EdenStatus::Unknown => writeln!(f, "Unknown: the search stopped before an answer")?,
+        }
+        if let Some(certificate) = &self.certificate {
+            writeln!(f, "  Formula: {} variables, {} clauses",
+                     certificate.variable_count, certificate.formula.len())?;
+            writeln!(f, "  Proof verified: {}", certificate.summary)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BoundaryCondition;
+    use crate::game_of_life::io::parse_pattern_from_string;
+    use crate::game_of_life::GameOfLifeRules;
+
+    fn check(content: &str, margin: usize) -> GardenOfEdenReport {
+        let mut settings = Settings::default();
+        settings.simulation.margin = margin;
+        let target = parse_pattern_from_string(content, BoundaryCondition::Dead).unwrap();
+        check_garden_of_eden(&settings, target).unwrap()
+    }
+
+    #[test]
+    fn test_certified_garden_of_eden() {
+        // A lone blinker cannot be produced inside its own 3x1 box
+        let report = check("111\n", 0);
+        assert_eq!(report.status, EdenStatus::GardenOfEden);
+        let certificate = report.certificate.unwrap();
+        assert!(certificate.proof.derives_empty_clause());
+
+        // The exported proof checks against the exported formula
+        let proof = DratProof::parse(&certificate.drat()).unwrap();
+        assert!(verify_proof(&certificate.formula, &proof).is_ok());
+        assert!(certificate.dimacs().starts_with(&format!("p cnf {} ", certificate.variable_count)));
+
+        // but not once the target cells are dropped from the formula
+        let unpinned: Vec<Clause> = certificate.formula.iter().filter(|c| !c.is_unit()).cloned().collect();
+        assert!(verify_proof(&unpinned, &proof).is_err());
+    }
+
+    #[test]
+    fn test_margin_finds_predecessor() {
+        let report = check("111\n", 1);
+        assert_eq!(report.status, EdenStatus::HasPredecessor);
+        assert!(report.certificate.is_none());
+
+        let predecessor = report.predecessor.unwrap();
+        let evolved = GameOfLifeRules::evolve(&predecessor);
+        assert_eq!(evolved.living_count(), 3);
+    }
+
+    #[test]
+    fn test_orphan_with_dont_care_cells() {
+        // Both ends of a 3x1 box have a single neighbor, whatever the middle holds
+        let report = check("1?1\n", 0);
+        assert_eq!(report.status, EdenStatus::Orphan);
+        assert!(report.certificate.is_some());
+    }
+}
@@ -1,9 +1,17 @@
 //! Reverse Game of Life problem definition and solution handling
 
+pub mod canvas;
+pub mod count;
+pub mod explain;
+pub mod garden_of_eden;
 pub mod problem;
 pub mod solution;
 pub mod validator;
 
+pub use canvas::Canvas;
+pub use count::{brute_force_count, count_predecessors, PredecessorCount, CROSS_CHECK_MAX_CELLS};
+pub use explain::{explain_unreachable, UnreachableCore};
+pub use garden_of_eden::{check_garden_of_eden, EdenStatus, GardenOfEdenReport};
 pub use problem::ReverseProblem;
 pub use solution::Solution;
 pub use validator::SolutionValidator;
\ No newline at end of file
@@ -1,74 +1,102 @@
 //! Reverse Game of Life problem definition
 
 use crate::config::Settings;
-use crate::game_of_life::{Grid, load_grid_from_file};
-use crate::sat::SatEncoder;
-use super::{Solution, SolutionValidator};
+use crate::game_of_life::{Grid, PatternSource, TargetPattern, load_target_for_settings};
+use crate::sat::encoder::{CellCore, PredecessorSearch};
+use crate::sat::{CancellationToken, CellFormula, CounterStatistics, DimacsModel, SatEncoder, SolverResultType};
+use super::solution::Optimality;
+use super::{Canvas, Solution, SolutionValidator};
 use anyhow::{Context, Result};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 /// Represents a reverse Game of Life problem
 pub struct ReverseProblem {
     settings: Settings,
-    target_grid: Grid,
+    target: TargetPattern,
+    canvas: Canvas,
     encoder: SatEncoder,
     validator: SolutionValidator,
+    source: PatternSource,
 }
 
 impl ReverseProblem {
     /// Create a new reverse problem from settings
-    pub fn new(settings: Settings) -> Result<Self> {
-        // Load the target grid from file
-        let target_grid = load_grid_from_file(
-            &settings.input.target_state_file,
-            settings.simulation.boundary_condition.clone(),
-        ).context("Failed to load target state file")?;
-
-        let encoder = SatEncoder::new(settings.clone(), &target_grid);
-        let validator = SolutionValidator::new(settings.clone());
+    pub fn new(mut settings: Settings) -> Result<Self> {
+        // Load the target pattern from file, along with any rule it declares
+        let target_file = settings.input.target_state_file.clone();
+        let target = load_target_for_settings(&target_file, &mut settings)
+            .context("Failed to load target state file")?;
 
-        Ok(Self {
-            settings,
-            target_grid,
-            encoder,
-            validator,
-        })
+        Ok(Self::with_target_pattern(settings, target.pattern)?.with_source(target.source))
     }
 
     /// Create a problem with an explicit target grid (useful for testing)
     pub fn with_target_grid(settings: Settings, target_grid: Grid) -> Result<Self> {
-        let encoder = SatEncoder::new(settings.clone(), &target_grid);
+        Self::with_target_pattern(settings, TargetPattern::from_grid(target_grid))
+    }
+
+    /// Create a problem with an explicit target pattern, which may leave cells unconstrained
+    ///
+    /// With a `simulation.margin`, predecessors are searched on a canvas larger than the target.
+    pub fn with_target_pattern(settings: Settings, target: TargetPattern) -> Result<Self> {
+        let canvas = Canvas::new(&settings.simulation, target.grid.width, target.grid.height);
+        let encoder = SatEncoder::new(settings.clone(), &canvas.embed(&target).grid)
+            .context("Failed to create SAT encoder")?
+            .with_dead_cells(canvas.dead_cells());
         let validator = SolutionValidator::new(settings.clone());
 
         Ok(Self {
             settings,
-            target_grid,
+            target,
+            canvas,
             encoder,
             validator,
+            source: PatternSource::default(),
         })
     }
 
+    /// Record where the target came from, so solutions keep its name and comments
+    pub fn with_source(mut self, source: PatternSource) -> Self {
+        self.source = source;
+        self
+    }
+
     /// Solve the reverse problem and return all valid solutions
     pub fn solve(&mut self) -> Result<Vec<Solution>> {
         let start_time = Instant::now();
 
         println!("Solving reverse Game of Life problem...");
         println!("Target grid: {}x{}, {} generations back", 
-                self.target_grid.width, 
-                self.target_grid.height, 
+                self.target.grid.width, 
+                self.target.grid.height, 
                 self.settings.simulation.generations);
-        println!("Target has {} living cells", self.target_grid.living_count());
+        println!("Target has {} living cells", self.target.grid.living_count());
+        if !self.target.is_fully_specified() {
+            println!("Target leaves {} cells unconstrained", self.target.dont_care_count());
+        }
+        println!("Rule: {}", self.settings.simulation.rule);
+        if !self.canvas.is_window() {
+            println!("Searching a {}x{} canvas, {} cells around the target",
+                    self.canvas.width(),
+                    self.canvas.height(),
+                    self.canvas.generation_margin(0));
+        }
 
         // Show complexity estimate
-        let complexity = self.encoder.estimate_complexity(&self.target_grid);
+        let complexity = self.encoder.estimate_complexity(&self.target.grid);
         println!("{}", complexity);
 
         // Solve using SAT encoding
-        let predecessor_grids = self.encoder.solve(&self.target_grid)
+        let search_target = self.canvas.embed(&self.target);
+        let predecessor_grids = self.encoder.solve_pattern(&search_target)
             .context("SAT solving failed")?;
 
         let solve_time = start_time.elapsed();
 
+        if self.encoder.was_interrupted() {
+            println!("Search stopped after {:.3}s; results may be incomplete", solve_time.as_secs_f64());
+        }
+
         if predecessor_grids.is_empty() {
             println!("No solutions found!");
             return Ok(Vec::new());
@@ -78,21 +106,40 @@ impl ReverseProblem {
                 predecessor_grids.len(), 
                 solve_time.as_secs_f64());
 
+        let optimality = match self.encoder.population_optimum() {
+            Some(optimum) if optimum.proven => {
+                println!("Predecessor population {} is optimal", optimum.population);
+                Optimality::Proven
+            }
+            Some(optimum) => {
+                println!("Best predecessor population found: {} (not proven optimal)", optimum.population);
+                Optimality::Unproven
+            }
+            None => Optimality::NotOptimized,
+        };
+
         // Convert grids to Solution objects and validate
         let mut solutions = Vec::new();
         for (i, predecessor_grid) in predecessor_grids.into_iter().enumerate() {
             println!("Validating solution {}...", i + 1);
 
-            match self.validator.validate(&predecessor_grid, &self.target_grid) {
+            match self.validator.validate_pattern(&predecessor_grid, &search_target) {
                 Ok(validation_result) => {
                     if validation_result.is_valid {
-                        let solution = Solution::new(
+                        // The reached state, which fills in any don't-care cells of the target
+                        let final_state = validation_result.evolution_path.last()
+                            .cloned()
+                            .unwrap_or_else(|| search_target.grid.clone());
+                        let mut solution = Solution::new(
                             predecessor_grid,
-                            self.target_grid.clone(),
+                            final_state,
                             self.settings.simulation.generations,
                             validation_result.evolution_path,
                             solve_time,
                         );
+                        solution.metadata.optimality = optimality;
+                        solution.metadata.rule = self.settings.simulation.rule.clone();
+                        solution.metadata.source = self.source.clone();
                         solutions.push(solution);
                         println!("Solution {} is valid", i + 1);
                     } else {
@@ -111,9 +158,115 @@ impl ReverseProblem {
         Ok(solutions)
     }
 
-    /// Get the target grid
+    /// Look for one predecessor on the canvas, with a DRAT proof if there is none
+    pub fn find_predecessor_with_proof(&mut self) -> Result<PredecessorSearch> {
+        let search_target = self.canvas.embed(&self.target);
+        self.encoder.find_predecessor_with_proof(&search_target)
+    }
+
+    /// Find a locally minimal set of specified target cells with no predecessor
+    ///
+    /// Cells are `(row, col)` in the target window; the surroundings required
+    /// by the canvas stay fixed. Returns `None` when the target is reachable.
+    pub fn find_unreachable_core(&mut self) -> Result<Option<CellCore>> {
+        let search_target = self.canvas.embed(&self.target);
+        let padding = self.canvas.padding();
+
+        let mut candidates = Vec::new();
+        for row in 0..self.target.grid.height {
+            for col in 0..self.target.grid.width {
+                if self.target.cares(row, col) {
+                    candidates.push((row + padding, col + padding));
+                }
+            }
+        }
+
+        let core = self.encoder.find_unreachable_core(&search_target, &candidates)?;
+        Ok(core.map(|core| CellCore {
+            cells: core.cells.into_iter().map(|(row, col)| (row - padding, col - padding)).collect(),
+            minimal: core.minimal,
+        }))
+    }
+
+    /// Count the distinct predecessors on the canvas, without enumerating them
+    ///
+    /// The count is `None` when it is stopped by timeout or cancellation.
+    pub fn count_predecessors(&mut self) -> Result<(Option<u128>, CounterStatistics)> {
+        let search_target = self.canvas.embed(&self.target);
+        self.encoder.count_predecessors(&search_target)
+    }
+
+    /// Generate the CNF for the predecessors on the canvas, to be solved offline
+    pub fn export_cnf(&mut self) -> Result<CellFormula> {
+        let search_target = self.canvas.embed(&self.target);
+        self.encoder.export_cnf(&search_target)
+    }
+
+    /// Turn a model of the exported CNF, found by an external solver, into a validated solution
+    ///
+    /// Fails if the solver found no model, the formula was exported for a
+    /// different canvas, or the predecessor does not evolve to the target.
+    pub fn solution_from_model(&self, formula: &CellFormula, model: &DimacsModel) -> Result<Solution> {
+        if model.status != SolverResultType::Satisfiable {
+            anyhow::bail!("The solver reported {:?}, so there is no predecessor to import", model.status);
+        }
+        if (formula.width, formula.height) != (self.canvas.width(), self.canvas.height())
+            || formula.time_steps != self.settings.simulation.generations + 1
+        {
+            anyhow::bail!(
+                "The CNF encodes a {}x{} grid over {} time steps, but the problem needs {}x{} over {}",
+                formula.width, formula.height, formula.time_steps,
+                self.canvas.width(), self.canvas.height(), self.settings.simulation.generations + 1
+            );
+        }
+
+        let predecessor = formula.grid_from_model(&model.assignment, 0, self.settings.simulation.boundary_condition.clone())
+            .context("Failed to read the predecessor from the model")?;
+        let search_target = self.canvas.embed(&self.target);
+        let validation = self.validator.validate_pattern(&predecessor, &search_target)?;
+        if !validation.is_valid {
+            anyhow::bail!("The imported predecessor failed validation: {}",
+                         validation.error_message.unwrap_or_else(|| "Unknown error".to_string()));
+        }
+
+        let final_state = validation.evolution_path.last()
+            .cloned()
+            .unwrap_or_else(|| search_target.grid.clone());
+        let mut solution = Solution::new(
+            predecessor,
+            final_state,
+            self.settings.simulation.generations,
+            validation.evolution_path,
+            Duration::ZERO,
+        );
+        solution.metadata.rule = self.settings.simulation.rule.clone();
+        solution.metadata.source = self.source.clone();
+        Ok(solution)
+    }
+
+    /// Get a handle that stops `solve` when cancelled, even from another thread
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.encoder.cancellation_token()
+    }
+
+    /// Check whether the last solve stopped early on timeout or cancellation
+    pub fn was_interrupted(&self) -> bool {
+        self.encoder.was_interrupted()
+    }
+
+    /// Get the target grid, with don't-care cells shown dead
     pub fn target_grid(&self) -> &Grid {
-        &self.target_grid
+        &self.target.grid
+    }
+
+    /// Get the target pattern, including which cells are specified
+    pub fn target_pattern(&self) -> &TargetPattern {
+        &self.target
+    }
+
+    /// Get the canvas predecessors are searched on
+    pub fn canvas(&self) -> &Canvas {
+        &self.canvas
     }
 
     /// Get the problem settings
@@ -128,9 +281,9 @@ impl ReverseProblem {
 
     /// Check if the problem is likely solvable
     pub fn estimate_solvability(&self) -> SolvabilityEstimate {
-        let complexity = self.encoder.estimate_complexity(&self.target_grid);
-        let living_cells = self.target_grid.living_count();
-        let total_cells = self.target_grid.width * self.target_grid.height;
+        let complexity = self.encoder.estimate_complexity(&self.target.grid);
+        let living_cells = self.target.grid.living_count();
+        let total_cells = self.target.grid.width * self.target.grid.height;
 
         // Heuristics for solvability
         let density = living_cells as f64 / total_cells as f64;
@@ -168,7 +321,7 @@ impl ReverseProblem {
     /// Detect known Game of Life patterns in the target grid
     fn detect_known_patterns(&self) -> bool {
         // Simple pattern detection - could be expanded
-        let living_cells = self.target_grid.living_count();
+        let living_cells = self.target.grid.living_count();
         
         // Check for common still lifes
         if living_cells == 4 {
@@ -192,7 +345,7 @@ impl ReverseProblem {
 
     /// Detect blinker pattern (3 cells in a row)
     fn detect_blinker_pattern(&self) -> bool {
-        let living_cells = self.target_grid.living_cells();
+        let living_cells = self.target.grid.living_cells();
         if living_cells.len() != 3 {
             return false;
         }
@@ -222,7 +375,7 @@ impl ReverseProblem {
     fn detect_glider_pattern(&self) -> bool {
         // This is a simplified check - a full implementation would check
         // all rotations and reflections of the glider pattern
-        self.target_grid.living_count() == 5
+        self.target.grid.living_count() == 5
     }
 
     /// Estimate solve time based on complexity
@@ -321,6 +474,7 @@ impl std::fmt::Display for EstimatedTime {
 mod tests {
     use super::*;
     use crate::config::*;
+    use crate::game_of_life::{GameOfLifeRules, Rule};
     use std::path::PathBuf;
 
     fn create_test_settings() -> Settings {
@@ -328,6 +482,9 @@ mod tests {
             simulation: SimulationConfig {
                 generations: 1,
                 boundary_condition: BoundaryCondition::Dead,
+                rule: Rule::conway(),
+                margin: 0,
+                free_margin: false,
             },
             solver: SolverConfig {
                 max_solutions: 5,
@@ -336,17 +493,27 @@ mod tests {
                 enable_preprocessing: false,
                 verbosity: 0,
                 backend: SolverBackend::Cadical,
+                random_seed: None,
+                projection_generation: 0,
+                optimize: Objective::None,
+                portfolio: Vec::new(),
+                cubes: CubeConfig::default(),
             },
             input: InputConfig {
                 target_state_file: PathBuf::from("test.txt"),
+                image: ImageImportConfig::default(),
             },
             output: OutputConfig {
                 format: OutputFormat::Text,
                 save_intermediate: false,
                 output_directory: PathBuf::from("output"),
+                image: ImageExportConfig::default(),
             },
             encoding: EncodingConfig {
                 symmetry_breaking: false,
+                transition: TransitionEncoding::Direct,
+                min_population: None,
+                max_population: None,
             },
         }
     }
@@ -397,4 +564,129 @@ mod tests {
         assert!(problem.detect_blinker_pattern());
     }
 
-}
\ No newline at end of file
+    /// Place `grid` in the middle of a much larger dead-boundary grid
+    fn on_plane(grid: &Grid, padding: usize) -> Grid {
+        let mut plane = Grid::new(grid.width + 2 * padding, grid.height + 2 * padding, BoundaryCondition::Dead);
+        for (row, col) in grid.living_cells() {
+            plane.set(row + padding, col + padding, true).unwrap();
+        }
+        plane
+    }
+
+    #[test]
+    fn test_margin_finds_predecessors_outside_the_target() {
+        // A lone horizontal blinker has no predecessor inside its own 3x1 box
+        let target = Grid::from_cells(vec![vec![true; 3]], BoundaryCondition::Dead).unwrap();
+        let mut settings = create_test_settings();
+        settings.solver.max_solutions = 1024;
+        let mut problem = ReverseProblem::with_target_grid(settings.clone(), target.clone()).unwrap();
+        assert!(problem.solve().unwrap().is_empty());
+
+        settings.simulation.margin = 1;
+        let mut problem = ReverseProblem::with_target_grid(settings, target.clone()).unwrap();
+        let canvas = problem.canvas().clone();
+        assert_eq!((canvas.width(), canvas.height()), (7, 5));
+        let mut found: Vec<Vec<bool>> = problem.solve().unwrap()
+            .into_iter()
+            .map(|solution| solution.predecessor.cells)
+            .collect();
+        found.sort();
+
+        // Brute force over every pattern within one cell of the target, on the plane
+        let expected_target = on_plane(&canvas.embed(&TargetPattern::from_grid(target)).grid, 4);
+        let region: Vec<(usize, usize)> = (0..canvas.height())
+            .flat_map(|y| (0..canvas.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| canvas.distance_to_window(x, y) <= 1)
+            .collect();
+        let mut expected: Vec<Vec<bool>> = (0..1u32 << region.len())
+            .map(|bits| {
+                let mut grid = Grid::new(canvas.width(), canvas.height(), BoundaryCondition::Dead);
+                for (i, &(x, y)) in region.iter().enumerate() {
+                    grid.set(y, x, bits & (1 << i) != 0).unwrap();
+                }
+                grid
+            })
+            .filter(|grid| GameOfLifeRules::evolve(&on_plane(grid, 4)) == expected_target)
+            .map(|grid| grid.cells)
+            .collect();
+        expected.sort();
+
+        assert!(!expected.is_empty());
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_free_margin_only_constrains_the_window() {
+        let target = Grid::from_cells(vec![vec![true; 3]], BoundaryCondition::Dead).unwrap();
+        let mut settings = create_test_settings();
+        settings.simulation.generations = 2;
+        settings.simulation.margin = 3;
+        settings.simulation.free_margin = true;
+        settings.solver.max_solutions = 10;
+
+        let mut problem = ReverseProblem::with_target_grid(settings, target).unwrap();
+        let padding = problem.canvas().padding();
+        assert_eq!(padding, 3); // margin capped at the two generations
+
+        let solutions = problem.solve().unwrap();
+        assert_eq!(solutions.len(), 10);
+        for solution in &solutions {
+            let evolved = GameOfLifeRules::evolve_generations(on_plane(&solution.predecessor, 4), 2);
+            assert!((0..3).all(|col| evolved.get(padding + 4, padding + 4 + col)));
+        }
+    }
+
+    #[test]
+    fn test_exported_cnf_model_imports_as_solution() {
+        let target = Grid::from_cells(vec![vec![true; 3]], BoundaryCondition::Dead).unwrap();
+        let mut settings = create_test_settings();
+        settings.simulation.margin = 1;
+        let mut problem = ReverseProblem::with_target_grid(settings, target).unwrap();
+        let formula = CellFormula::from_dimacs(&problem.export_cnf().unwrap().to_dimacs()).unwrap();
+
+        // Solve the file as an external solver would, and print its model
+        let (clauses, variable_count) = crate::sat::parse_dimacs(&formula.to_dimacs()).unwrap();
+        let mut solver = crate::sat::create_solver(&SolverBackend::Cadical).unwrap();
+        solver.add_clauses(&clauses).unwrap();
+        let solution = solver.solve().unwrap().unwrap();
+        let values: Vec<String> = (1..=variable_count as i32)
+            .map(|var| if solution.assignment.get(&var) == Some(&true) { var } else { -var })
+            .map(|literal| literal.to_string())
+            .collect();
+        let output = format!("c external run\ns SATISFIABLE\nv {} 0\n", values.join(" "));
+
+        let model = crate::sat::parse_model(&output).unwrap();
+        let imported = problem.solution_from_model(&formula, &model).unwrap();
+        assert_eq!(imported.predecessor.width, problem.canvas().width());
+        assert!(imported.target.living_count() >= 3);
+
+        // A model that violates the transition is rejected
+        let mut wrong = model.clone();
+        for id in formula.cells.iter().filter(|(cell, _)| cell.2 == 0).map(|(_, id)| id) {
+            wrong.assignment.insert(*id, false);
+        }
+        assert!(problem.solution_from_model(&formula, &wrong).is_err());
+        let unsat = crate::sat::parse_model("s UNSATISFIABLE\n").unwrap();
+        assert!(problem.solution_from_model(&formula, &unsat).is_err());
+    }
+
+    #[test]
+    fn test_solutions_keep_the_target_source() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let target_path = temp_dir.path().join("blinker.cells");
+        std::fs::write(&target_path, "!Name: Blinker\n!Period 2 oscillator\n...\nOOO\n...\n").unwrap();
+
+        let mut settings = create_test_settings();
+        settings.input.target_state_file = target_path.clone();
+        let mut problem = ReverseProblem::new(settings).unwrap();
+        let solutions = problem.solve().unwrap();
+
+        assert!(!solutions.is_empty());
+        for solution in &solutions {
+            let source = &solution.metadata.source;
+            assert_eq!(source.file.as_deref(), Some(target_path.as_path()));
+            assert_eq!(source.name.as_deref(), Some("Blinker"));
+            assert_eq!(source.comments, vec!["Period 2 oscillator"]);
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! Solution representation for reverse Game of Life problems
 
-use crate::game_of_life::Grid;
+use crate::game_of_life::{Grid, PatternSource, Rule};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
@@ -39,6 +39,28 @@ pub struct SolutionMetadata {
     pub stability: StabilityAnalysis,
     /// Quality score of the solution (0.0 to 1.0, higher is better)
     pub quality_score: f64,
+    /// Whether the predecessor population is known to be optimal
+    #[serde(default)]
+    pub optimality: Optimality,
+    /// Rule the predecessor evolves under
+    #[serde(default)]
+    pub rule: Rule,
+    /// File, name and comments of the target
+    #[serde(default)]
+    pub source: PatternSource,
+}
+
+/// How the predecessor population compares to every other predecessor
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Optimality {
+    /// The population was not optimized
+    #[default]
+    NotOptimized,
+    /// The solver proved that no predecessor has a better population
+    Proven,
+    /// The best population found before the search stopped
+    Unproven,
 }
 
 /// Analysis of solution stability
@@ -189,6 +211,9 @@ impl SolutionMetadata {
             contains_known_patterns,
             stability,
             quality_score,
+            optimality: Optimality::NotOptimized,
+            rule: Rule::conway(),
+            source: PatternSource::default(),
         }
     }
 
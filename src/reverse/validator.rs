@@ -1,7 +1,7 @@
 //! Solution validation for reverse Game of Life problems
 
 use crate::config::Settings;
-use crate::game_of_life::{Grid, GameOfLifeRules};
+use crate::game_of_life::{Grid, GameOfLifeRules, TargetPattern};
 use anyhow::Result;
 
 /// Validates solutions to reverse Game of Life problems
@@ -55,6 +55,12 @@ impl SolutionValidator {
 
     /// Validate that a predecessor correctly evolves to the target
     pub fn validate(&self, predecessor: &Grid, target: &Grid) -> Result<ValidationResult> {
+        self.validate_pattern(predecessor, &TargetPattern::from_grid(target.clone()))
+    }
+
+    /// Validate that a predecessor evolves to match the specified cells of `pattern`
+    pub fn validate_pattern(&self, predecessor: &Grid, pattern: &TargetPattern) -> Result<ValidationResult> {
+        let target = &pattern.grid;
         let start_time = std::time::Instant::now();
         
         // Check grid dimensions
@@ -88,7 +94,7 @@ impl SolutionValidator {
         let mut rule_violations = Vec::new();
 
         for generation in 0..self.settings.simulation.generations {
-            let next_grid = GameOfLifeRules::evolve(&current_grid);
+            let next_grid = GameOfLifeRules::evolve_with_rule(&current_grid, &self.settings.simulation.rule);
             evolution_path.push(next_grid.clone());
 
             // Validate each transition follows Game of Life rules
@@ -98,7 +104,7 @@ impl SolutionValidator {
             current_grid = next_grid;
         }
 
-        let final_state_matches = GameOfLifeRules::grids_equal(&current_grid, target);
+        let final_state_matches = pattern.matches(&current_grid);
         let intermediate_states_valid = rule_violations.is_empty();
         let is_valid = final_state_matches && intermediate_states_valid;
 
@@ -140,8 +146,9 @@ impl SolutionValidator {
                 let current_cell = current.get(y, x);
                 let next_cell = next.get(y, x);
                 let neighbor_count = current.count_neighbors(y, x);
+                let configuration = current.neighbor_configuration(y, x);
 
-                let expected_next = GameOfLifeRules::should_be_alive(current_cell, neighbor_count);
+                let expected_next = self.settings.simulation.rule.next_state(current_cell, configuration);
 
                 if next_cell != expected_next {
                     violations.push(RuleViolation {
@@ -228,9 +235,10 @@ impl SolutionValidator {
 
     /// Quick validation that only checks the final state
     pub fn quick_validate(&self, predecessor: &Grid, target: &Grid) -> Result<bool> {
-        let evolved = GameOfLifeRules::evolve_generations(
+        let evolved = GameOfLifeRules::evolve_generations_with_rule(
             predecessor.clone(),
             self.settings.simulation.generations,
+            &self.settings.simulation.rule,
         );
         Ok(GameOfLifeRules::grids_equal(&evolved, target))
     }
@@ -369,6 +377,7 @@ impl std::fmt::Display for GridValidationResult {
 mod tests {
     use super::*;
     use crate::config::*;
+    use crate::game_of_life::Rule;
     use std::path::PathBuf;
 
     fn create_test_settings() -> Settings {
@@ -376,6 +385,9 @@ mod tests {
             simulation: SimulationConfig {
                 generations: 1,
                 boundary_condition: BoundaryCondition::Dead,
+                rule: Rule::conway(),
+                margin: 0,
+                free_margin: false,
             },
             solver: SolverConfig {
                 max_solutions: 5,
@@ -384,17 +396,27 @@ mod tests {
                 enable_preprocessing: false,
                 verbosity: 0,
                 backend: SolverBackend::Cadical,
+                random_seed: None,
+                projection_generation: 0,
+                optimize: Objective::None,
+                portfolio: Vec::new(),
+                cubes: CubeConfig::default(),
             },
             input: InputConfig {
                 target_state_file: PathBuf::from("test.txt"),
+                image: ImageImportConfig::default(),
             },
             output: OutputConfig {
                 format: OutputFormat::Text,
                 save_intermediate: false,
                 output_directory: PathBuf::from("output"),
+                image: ImageExportConfig::default(),
             },
             encoding: EncodingConfig {
                 symmetry_breaking: false,
+                transition: TransitionEncoding::Direct,
+                min_population: None,
+                max_population: None,
             },
         }
     }
@@ -445,6 +467,33 @@ mod tests {
         assert!(!result.validation_details.final_state_matches);
     }
 
+    #[test]
+    fn test_pattern_validation_ignores_dont_care_cells() {
+        let settings = create_test_settings();
+        let validator = SolutionValidator::new(settings);
+
+        // Vertical blinker -> horizontal blinker, with only the middle row specified
+        let predecessor_cells = vec![
+            vec![false, true, false],
+            vec![false, true, false],
+            vec![false, true, false],
+        ];
+        let predecessor = Grid::from_cells(predecessor_cells, BoundaryCondition::Dead).unwrap();
+        let mut required = Grid::new(3, 3, BoundaryCondition::Dead);
+        for col in 0..3 {
+            required.set(1, col, true).unwrap();
+        }
+        let care = (0..9).map(|i| i / 3 == 1).collect();
+        let pattern = TargetPattern::new(required, care).unwrap();
+
+        let result = validator.validate_pattern(&predecessor, &pattern).unwrap();
+        assert!(result.is_valid);
+
+        // The empty grid still fails on the specified cells
+        let result = validator.validate_pattern(&Grid::new(3, 3, BoundaryCondition::Dead), &pattern).unwrap();
+        assert!(!result.validation_details.final_state_matches);
+    }
+
     #[test]
     fn test_dimension_mismatch() {
         let settings = create_test_settings();
@@ -0,0 +1,308 @@
+//! Pluggable SAT backend interface
+
+use super::cancellation::CancellationToken;
+use super::constraints::Clause;
+use super::drat::DratProof;
+use super::solver::{SolverOptions, SolverResultType, SolverSolution, SolverStatistics, UnsatCore};
+use crate::config::SolverBackend;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Common interface implemented by every SAT solver backend
+///
+/// Implementors only provide the primitive operations; solving without
+/// assumptions and enumerating several solutions are built on top of them.
+pub trait SatBackend {
+    /// Get the backend type this solver implements
+    fn backend(&self) -> SolverBackend;
+
+    /// Set solver configuration options
+    fn configure(&mut self, options: &SolverOptions) -> Result<()>;
+
+    /// Add a single clause to the solver
+    fn add_clause(&mut self, clause: &Clause) -> Result<()>;
+
+    /// Solve under the given assumption literals
+    fn solve_with_assumptions(&mut self, assumptions: &[i32]) -> Result<SolverResultType>;
+
+    /// Value of a variable in the most recent model, if one exists
+    fn model_value(&self, var: i32) -> Option<bool>;
+
+    /// Get a handle to the token checked during search
+    ///
+    /// The handle can be cancelled from another thread to interrupt a running solve.
+    fn cancellation_token(&self) -> CancellationToken;
+
+    /// Replace the token checked during search
+    ///
+    /// A deadline on the token bounds every solve call until the token is
+    /// replaced; without one, the configured timeout applies to each call.
+    fn set_cancellation_token(&mut self, token: CancellationToken);
+
+    /// Get solver statistics
+    fn statistics(&self) -> SolverStatistics;
+
+    /// Get the number of variables
+    fn variable_count(&self) -> usize;
+
+    /// Get the number of clauses
+    fn clause_count(&self) -> usize;
+
+    /// Record a DRAT proof of the clauses learned from now on
+    ///
+    /// Must be called before any clauses are added. Backends that cannot
+    /// produce proofs return an error.
+    fn enable_proof(&mut self) -> Result<()> {
+        anyhow::bail!("The {:?} backend cannot produce proofs", self.backend())
+    }
+
+    /// Take the proof recorded since `enable_proof`, if recording
+    ///
+    /// After an unsatisfiable solve without assumptions, the lemmas together
+    /// with the empty clause refute the clauses added to the solver.
+    fn take_proof(&mut self) -> Option<DratProof> {
+        None
+    }
+
+    /// Check whether an assumption took part in the last unsatisfiable result
+    ///
+    /// Backends that cannot tell report every assumption as failed.
+    fn failed(&self, _literal: i32) -> bool {
+        true
+    }
+
+    /// Suggest variables to split the search on, best first
+    ///
+    /// Only splitting backends such as cube-and-conquer use the hint; the
+    /// others ignore it.
+    fn suggest_split(&mut self, _variables: &[i32]) {}
+
+    /// Ask the solver to stop searching as soon as possible
+    ///
+    /// Termination is sticky: every later solve call returns `SolverResultType::Timeout`.
+    fn terminate(&self) {
+        self.cancellation_token().cancel();
+    }
+
+    /// Add clauses to the solver
+    fn add_clauses(&mut self, clauses: &[Clause]) -> Result<()> {
+        for clause in clauses {
+            self.add_clause(clause)?;
+        }
+        Ok(())
+    }
+
+    /// Solve the SAT problem and return the first solution
+    fn solve(&mut self) -> Result<Option<SolverSolution>> {
+        let start_time = Instant::now();
+
+        match self.solve_with_assumptions(&[])? {
+            SolverResultType::Satisfiable => Ok(Some(SolverSolution {
+                assignment: self.extract_assignment(),
+                solve_time: start_time.elapsed(),
+            })),
+            SolverResultType::Unsatisfiable => Ok(None),
+            result => anyhow::bail!("Solver returned no answer ({:?})", result),
+        }
+    }
+
+    /// Solve and find multiple solutions up to a limit
+    ///
+    /// Solutions are distinct over every variable; use `solve_multiple_projected`
+    /// when only some variables matter.
+    fn solve_multiple(&mut self, max_solutions: usize) -> Result<Vec<SolverSolution>> {
+        let all_variables: Vec<i32> = (1..=self.variable_count() as i32).collect();
+        self.solve_multiple_projected(max_solutions, &all_variables)
+    }
+
+    /// Find up to `max_solutions` solutions that differ on the projection variables
+    ///
+    /// Blocking clauses only mention the projected variables, so models that
+    /// agree on them but differ elsewhere are reported once. If the search is
+    /// interrupted, the solutions found so far are returned and
+    /// `statistics().result` is `SolverResultType::Timeout`.
+    fn solve_multiple_projected(&mut self, max_solutions: usize, projection: &[i32]) -> Result<Vec<SolverSolution>> {
+        let mut solutions = Vec::new();
+        let start_time = Instant::now();
+
+        while solutions.len() < max_solutions {
+            match self.solve_with_assumptions(&[])? {
+                SolverResultType::Satisfiable => {
+                    solutions.push(SolverSolution {
+                        assignment: self.extract_assignment(),
+                        solve_time: start_time.elapsed(),
+                    });
+
+                    // With nothing to project on, every model is the same solution
+                    if projection.is_empty() {
+                        break;
+                    }
+
+                    // Add blocking clause to prevent finding the same solution again
+                    self.add_blocking_clause(projection)?;
+                }
+                SolverResultType::Unsatisfiable | SolverResultType::Timeout => break,
+                result => {
+                    anyhow::bail!("Solver returned no answer during multiple solution search ({:?})", result)
+                }
+            }
+        }
+
+        Ok(solutions)
+    }
+
+    /// Find a locally minimal subset of `assumptions` that is unsatisfiable with the clauses
+    ///
+    /// Returns `None` when the clauses are satisfiable under all assumptions.
+    /// The failed assumptions of the first call are shrunk by dropping one
+    /// literal at a time and keeping the drop whenever the rest stays
+    /// unsatisfiable, so removing any single literal from the result makes the
+    /// formula satisfiable. If the search is interrupted during minimization,
+    /// the smallest core found so far is returned, marked as not minimal.
+    fn unsat_core(&mut self, assumptions: &[i32]) -> Result<Option<UnsatCore>> {
+        match self.solve_with_assumptions(assumptions)? {
+            SolverResultType::Satisfiable => return Ok(None),
+            SolverResultType::Unsatisfiable => {}
+            result => anyhow::bail!("Solver returned no answer ({:?})", result),
+        }
+
+        let mut core: Vec<i32> = assumptions.iter().copied().filter(|&literal| self.failed(literal)).collect();
+        let mut index = 0;
+        let mut minimal = true;
+        while index < core.len() {
+            let mut candidate = core.clone();
+            candidate.remove(index);
+
+            match self.solve_with_assumptions(&candidate)? {
+                // The failed assumptions may drop more than the one literal
+                SolverResultType::Unsatisfiable => {
+                    core = candidate.into_iter().filter(|&literal| self.failed(literal)).collect();
+                }
+                SolverResultType::Satisfiable => index += 1,
+                _ => {
+                    minimal = false;
+                    break;
+                }
+            }
+        }
+
+        Ok(Some(UnsatCore { literals: core, minimal }))
+    }
+
+    /// Extract the full variable assignment of the most recent model
+    fn extract_assignment(&self) -> HashMap<i32, bool> {
+        (1..=self.variable_count() as i32)
+            .filter_map(|var| self.model_value(var).map(|value| (var, value)))
+            .collect()
+    }
+
+    /// Add a blocking clause excluding the current model's values on the given variables
+    fn add_blocking_clause(&mut self, variables: &[i32]) -> Result<()> {
+        let blocking_literals = variables
+            .iter()
+            .map(|&var| if self.model_value(var).unwrap_or(false) { -var } else { var })
+            .collect();
+
+        self.add_clause(&Clause::new(blocking_literals))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Backend with a single free variable that gives up after a fixed number of answers
+    struct LimitedBackend {
+        answers_left: usize,
+        model: Option<bool>,
+        last_result: SolverResultType,
+        cancellation: CancellationToken,
+    }
+
+    impl LimitedBackend {
+        fn new(answers: usize) -> Self {
+            Self {
+                answers_left: answers,
+                model: None,
+                last_result: SolverResultType::Error,
+                cancellation: CancellationToken::new(),
+            }
+        }
+    }
+
+    impl SatBackend for LimitedBackend {
+        fn backend(&self) -> SolverBackend {
+            SolverBackend::Custom("limited".to_string())
+        }
+
+        fn configure(&mut self, _options: &SolverOptions) -> Result<()> {
+            Ok(())
+        }
+
+        fn add_clause(&mut self, _clause: &Clause) -> Result<()> {
+            Ok(())
+        }
+
+        fn solve_with_assumptions(&mut self, _assumptions: &[i32]) -> Result<SolverResultType> {
+            self.last_result = if self.answers_left == 0 || self.cancellation.is_cancelled() {
+                SolverResultType::Timeout
+            } else {
+                self.answers_left -= 1;
+                self.model = Some(!self.model.unwrap_or(false));
+                SolverResultType::Satisfiable
+            };
+            Ok(self.last_result.clone())
+        }
+
+        fn model_value(&self, var: i32) -> Option<bool> {
+            if var == 1 { self.model } else { None }
+        }
+
+        fn cancellation_token(&self) -> CancellationToken {
+            self.cancellation.clone()
+        }
+
+        fn set_cancellation_token(&mut self, token: CancellationToken) {
+            self.cancellation = token;
+        }
+
+        fn statistics(&self) -> SolverStatistics {
+            SolverStatistics {
+                variable_count: 1,
+                clause_count: 0,
+                solve_time: Duration::ZERO,
+                result: self.last_result.clone(),
+            }
+        }
+
+        fn variable_count(&self) -> usize {
+            1
+        }
+
+        fn clause_count(&self) -> usize {
+            0
+        }
+    }
+
+    #[test]
+    fn test_interrupted_enumeration_keeps_partial_results() {
+        let mut solver = LimitedBackend::new(2);
+
+        let solutions = solver.solve_multiple(5).unwrap();
+        assert_eq!(solutions.len(), 2);
+        assert_eq!(solver.statistics().result, SolverResultType::Timeout);
+    }
+
+    #[test]
+    fn test_terminate_cancels_token() {
+        let mut solver = LimitedBackend::new(5);
+        let token = solver.cancellation_token();
+
+        solver.terminate();
+        assert!(token.is_cancelled());
+        assert!(solver.solve_multiple(5).unwrap().is_empty());
+        assert!(solver.solve().is_err());
+    }
+}
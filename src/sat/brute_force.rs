@@ -0,0 +1,304 @@
+//! Brute-force predecessor search, used as an oracle for the SAT encodings
+//!
+//! Every generation-0 grid is simulated forward under the rule and kept if
+//! it reaches the target, in parallel with rayon. No CNF is involved, so the
+//! result is independent of every encoding and backend it is compared with.
+//! The search space doubles with each cell, which limits it to tiny grids.
+
+use super::backend::SatBackend;
+use super::cancellation::CancellationToken;
+use super::constraints::Clause;
+use super::solver::{SolverOptions, SolverResultType, SolverStatistics};
+use crate::config::{BoundaryCondition, Settings, SolverBackend};
+use crate::game_of_life::{Grid, Rule, TargetPattern};
+use anyhow::Result;
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Most predecessor cells the brute-force search will enumerate
+pub const BRUTE_FORCE_MAX_CELLS: usize = 30;
+
+/// Exhaustive search over the predecessors of a grid
+#[derive(Debug, Clone)]
+pub struct BruteForceSearch {
+    width: usize,
+    height: usize,
+    generations: usize,
+    boundary_condition: BoundaryCondition,
+    rule: Rule,
+    dead_cells: Vec<(usize, usize, usize)>,
+    min_population: Option<usize>,
+    max_population: Option<usize>,
+    cancellation: CancellationToken,
+}
+
+/// Predecessors found by a brute-force search
+#[derive(Debug, Clone)]
+pub struct BruteForceResult {
+    /// Matching predecessors, in increasing order of their cells read as binary
+    pub predecessors: Vec<Grid>,
+    /// Whether the search stopped on timeout or cancellation before trying every grid
+    pub interrupted: bool,
+}
+
+impl BruteForceSearch {
+    /// Search `width` x `height` grids under the rule, generations and population bounds in the settings
+    pub fn new(settings: &Settings, width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            generations: settings.simulation.generations,
+            boundary_condition: settings.simulation.boundary_condition.clone(),
+            rule: settings.simulation.rule.clone(),
+            dead_cells: Vec::new(),
+            min_population: settings.encoding.min_population,
+            max_population: settings.encoding.max_population,
+            cancellation: CancellationToken::new(),
+        }
+    }
+
+    /// Require the cells `(x, y, t)` to be dead, as `ConstraintGenerator::with_dead_cells` does
+    ///
+    /// Cells dead in the predecessor are not enumerated.
+    pub fn with_dead_cells(mut self, dead_cells: Vec<(usize, usize, usize)>) -> Self {
+        self.dead_cells = dead_cells;
+        self
+    }
+
+    /// Stop once `token` is cancelled or past its deadline
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Indices of the predecessor cells that are enumerated
+    pub fn free_cells(&self) -> Vec<usize> {
+        (0..self.width * self.height)
+            .filter(|&index| !self.dead_cells.contains(&(index % self.width, index / self.width, 0)))
+            .collect()
+    }
+
+    /// Find every predecessor that evolves to match `target` on its specified cells
+    pub fn predecessors(&self, target: &TargetPattern) -> Result<BruteForceResult> {
+        let free = self.free_cells();
+        let interrupted = AtomicBool::new(false);
+        let predecessors = self.candidates(target, &free)?
+            .filter(|&bits| self.is_predecessor(&free, bits, target, &interrupted))
+            .map(|bits| self.grid(&free, bits))
+            .collect();
+
+        Ok(BruteForceResult { predecessors, interrupted: interrupted.into_inner() })
+    }
+
+    /// Count the predecessors that evolve to match `target`, or `None` if interrupted
+    pub fn count(&self, target: &TargetPattern) -> Result<Option<u128>> {
+        let free = self.free_cells();
+        let interrupted = AtomicBool::new(false);
+        let count = self.candidates(target, &free)?
+            .filter(|&bits| self.is_predecessor(&free, bits, target, &interrupted))
+            .count();
+
+        Ok(if interrupted.into_inner() { None } else { Some(count as u128) })
+    }
+
+    /// Every assignment to the `free` cells, as bits in the same order
+    fn candidates(&self, target: &TargetPattern, free: &[usize]) -> Result<rayon::range::Iter<u32>> {
+        if (target.grid.width, target.grid.height) != (self.width, self.height) {
+            anyhow::bail!("Target grid dimensions ({}, {}) don't match problem dimensions ({}, {})",
+                         target.grid.width, target.grid.height, self.width, self.height);
+        }
+
+        if free.len() > BRUTE_FORCE_MAX_CELLS {
+            anyhow::bail!(
+                "Brute force is limited to {} predecessor cells, but the grid has {}",
+                BRUTE_FORCE_MAX_CELLS,
+                free.len()
+            );
+        }
+
+        Ok((0..1u32 << free.len()).into_par_iter())
+    }
+
+    /// The predecessor with the given `free` cells alive
+    fn grid(&self, free: &[usize], bits: u32) -> Grid {
+        let mut grid = Grid::new(self.width, self.height, self.boundary_condition.clone());
+        for (bit, &index) in free.iter().enumerate() {
+            grid.cells[index] = bits & (1 << bit) != 0;
+        }
+        grid
+    }
+
+    /// Check whether the candidate respects the bounds and dead cells and reaches the target
+    fn is_predecessor(&self, free: &[usize], bits: u32, target: &TargetPattern, interrupted: &AtomicBool) -> bool {
+        if interrupted.load(Ordering::Relaxed) {
+            return false;
+        }
+        if self.cancellation.is_cancelled() {
+            interrupted.store(true, Ordering::Relaxed);
+            return false;
+        }
+
+        let population = bits.count_ones() as usize;
+        if self.min_population.is_some_and(|min| population < min)
+            || self.max_population.is_some_and(|max| population > max)
+        {
+            return false;
+        }
+
+        let mut grid = self.grid(free, bits);
+        for t in 1..=self.generations {
+            grid = self.evolve(&grid);
+            let mut dead = self.dead_cells.iter().filter(|cell| cell.2 == t);
+            if dead.any(|&(x, y, _)| grid.get(y, x)) {
+                return false;
+            }
+        }
+
+        target.matches(&grid)
+    }
+
+    /// One generation under the rule
+    ///
+    /// Candidates are already spread over rayon's threads, so cells are
+    /// updated sequentially rather than through `GameOfLifeRules::evolve_with_rule`.
+    fn evolve(&self, grid: &Grid) -> Grid {
+        let mut next = Grid::new(grid.width, grid.height, grid.boundary_condition.clone());
+        for row in 0..grid.height {
+            for col in 0..grid.width {
+                next.cells[grid.index(row, col)] = self.rule.next_state(grid.get(row, col), grid.neighbor_configuration(row, col));
+            }
+        }
+        next
+    }
+}
+
+/// Backend registered for `SolverBackend::BruteForce`
+///
+/// Brute force searches grids rather than models of a CNF formula, so
+/// `SatEncoder` runs a `BruteForceSearch` itself when this backend is
+/// selected. The backend only keeps track of the clauses it is given, and
+/// asking it to solve them is an error.
+#[derive(Debug, Default)]
+pub struct BruteForceSolver {
+    variable_count: usize,
+    clause_count: usize,
+    cancellation: CancellationToken,
+}
+
+impl BruteForceSolver {
+    /// Create a new brute-force backend
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SatBackend for BruteForceSolver {
+    fn backend(&self) -> SolverBackend {
+        SolverBackend::BruteForce
+    }
+
+    fn configure(&mut self, _options: &SolverOptions) -> Result<()> {
+        Ok(())
+    }
+
+    fn add_clause(&mut self, clause: &Clause) -> Result<()> {
+        for &literal in &clause.literals {
+            self.variable_count = self.variable_count.max(literal.unsigned_abs() as usize);
+        }
+        self.clause_count += 1;
+        Ok(())
+    }
+
+    fn solve_with_assumptions(&mut self, _assumptions: &[i32]) -> Result<SolverResultType> {
+        anyhow::bail!("The brute-force backend enumerates grids and cannot solve CNF formulas")
+    }
+
+    fn model_value(&self, _var: i32) -> Option<bool> {
+        None
+    }
+
+    fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancellation = token;
+    }
+
+    fn statistics(&self) -> SolverStatistics {
+        SolverStatistics {
+            variable_count: self.variable_count,
+            clause_count: self.clause_count,
+            solve_time: Duration::ZERO,
+            result: SolverResultType::Error,
+        }
+    }
+
+    fn variable_count(&self) -> usize {
+        self.variable_count
+    }
+
+    fn clause_count(&self) -> usize {
+        self.clause_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_of_life::io::parse_pattern_from_string;
+    use crate::game_of_life::GameOfLifeRules;
+
+    fn search(generations: usize) -> BruteForceSearch {
+        let mut settings = Settings::default();
+        settings.simulation.generations = generations;
+        BruteForceSearch::new(&settings, 3, 3)
+    }
+
+    #[test]
+    fn test_predecessors_evolve_to_target() {
+        let target = parse_pattern_from_string("000\n111\n000\n", BoundaryCondition::Dead).unwrap();
+        let result = search(1).predecessors(&target).unwrap();
+        assert!(!result.interrupted);
+        assert!(!result.predecessors.is_empty());
+        for predecessor in &result.predecessors {
+            assert!(target.matches(&GameOfLifeRules::evolve(predecessor)));
+        }
+
+        // The vertical blinker is one of them
+        let vertical = parse_pattern_from_string("010\n010\n010\n", BoundaryCondition::Dead).unwrap().grid;
+        assert!(result.predecessors.contains(&vertical));
+        assert_eq!(search(1).count(&target).unwrap(), Some(result.predecessors.len() as u128));
+    }
+
+    #[test]
+    fn test_dead_cells_are_not_enumerated() {
+        let target = TargetPattern::from_grid(Grid::new(3, 3, BoundaryCondition::Dead));
+        let dead: Vec<(usize, usize, usize)> = (0..3).flat_map(|x| [(x, 0, 0), (x, 2, 0)]).collect();
+        let search = search(1).with_dead_cells(dead);
+        assert_eq!(search.free_cells(), vec![3, 4, 5]);
+
+        // A lone row of up to three cells dies out unless it is a full blinker
+        assert_eq!(search.count(&target).unwrap(), Some(7));
+    }
+
+    #[test]
+    fn test_limits_and_cancellation() {
+        let mut settings = Settings::default();
+        settings.simulation.generations = 1;
+        let target = TargetPattern::from_grid(Grid::new(8, 4, BoundaryCondition::Dead));
+        assert!(BruteForceSearch::new(&settings, 8, 4).count(&target).is_err());
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let target = TargetPattern::from_grid(Grid::new(3, 3, BoundaryCondition::Dead));
+        let result = search(1).with_cancellation(token).predecessors(&target).unwrap();
+        assert!(result.interrupted);
+
+        let mut solver = BruteForceSolver::new();
+        solver.add_clause(&Clause::new(vec![1, -3])).unwrap();
+        assert_eq!(solver.variable_count(), 3);
+        assert!(solver.solve().is_err());
+    }
+}
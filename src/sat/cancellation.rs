@@ -0,0 +1,141 @@
+//! Cooperative cancellation for long-running solves
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Shareable handle that stops a solve when cancelled or past its deadline
+///
+/// Clones share the cancellation flag, so a token handed to a solver can be
+/// tripped from another thread. Cancellation is sticky; the deadline is
+/// per clone and is set with `with_deadline` or `with_timeout`. A `child`
+/// token can be cancelled on its own, and is also cancelled with its parent.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+    parent: Option<Arc<CancellationToken>>,
+}
+
+impl CancellationToken {
+    /// Create a token that is not cancelled and has no deadline
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation of every solve using this token or its clones
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Check whether cancellation was requested or the deadline has passed
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self.is_past_deadline()
+            || self.parent.as_ref().is_some_and(|parent| parent.is_cancelled())
+    }
+
+    /// Token with a flag of its own that is also cancelled whenever this one is
+    ///
+    /// Cancelling the child leaves this token alone, which lets a parallel
+    /// search stop its workers without cancelling the caller. The child
+    /// starts with this token's deadline.
+    pub fn child(&self) -> Self {
+        Self {
+            cancelled: Arc::new(AtomicBool::new(false)),
+            deadline: self.deadline,
+            parent: Some(Arc::new(self.clone())),
+        }
+    }
+
+    /// Check whether the deadline has passed
+    pub fn is_past_deadline(&self) -> bool {
+        self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Get the deadline, if any
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Time left until the deadline, if any
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Clone sharing the cancellation flag, with the given deadline
+    pub fn with_deadline(&self, deadline: Instant) -> Self {
+        Self {
+            cancelled: Arc::clone(&self.cancelled),
+            deadline: Some(deadline),
+            parent: self.parent.clone(),
+        }
+    }
+
+    /// Clone sharing the cancellation flag, with a deadline `timeout` from now
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    /// Clone that keeps an existing deadline, or else gets one `timeout` from now
+    pub fn or_timeout(&self, timeout: Option<Duration>) -> Self {
+        match (self.deadline, timeout) {
+            (None, Some(timeout)) => self.with_timeout(timeout),
+            _ => self.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_is_shared_between_clones() {
+        let token = CancellationToken::new();
+        let clone = token.with_timeout(Duration::from_secs(60));
+        assert!(!clone.is_cancelled());
+
+        token.cancel();
+        assert!(clone.is_cancelled());
+        assert!(!clone.is_past_deadline());
+    }
+
+    #[test]
+    fn test_deadline() {
+        let token = CancellationToken::new();
+        let expired = token.with_deadline(Instant::now());
+        assert!(expired.is_cancelled());
+        assert_eq!(expired.remaining(), Some(Duration::ZERO));
+
+        // The deadline belongs to the clone, not the shared flag
+        assert!(!token.is_cancelled());
+        assert_eq!(token.remaining(), None);
+    }
+
+    #[test]
+    fn test_child_is_cancelled_with_its_parent() {
+        let parent = CancellationToken::new();
+        let child = parent.child();
+        child.cancel();
+        assert!(child.is_cancelled());
+        assert!(!parent.is_cancelled());
+
+        let child = parent.child().with_timeout(Duration::from_secs(60));
+        parent.cancel();
+        assert!(child.is_cancelled());
+        assert!(!child.is_past_deadline());
+    }
+
+    #[test]
+    fn test_or_timeout_keeps_existing_deadline() {
+        let token = CancellationToken::new();
+        assert_eq!(token.or_timeout(None).deadline(), None);
+        assert!(token.or_timeout(Some(Duration::from_secs(1))).deadline().is_some());
+
+        let deadline = Instant::now() + Duration::from_secs(60);
+        let bounded = token.with_deadline(deadline);
+        assert_eq!(bounded.or_timeout(Some(Duration::from_secs(1))).deadline(), Some(deadline));
+    }
+}
@@ -0,0 +1,383 @@
+//! Cardinality networks for counting live neighbors with auxiliary variables
+//!
+//! Every network defines its outputs in both directions, so the outputs are
+//! functions of the inputs and can be used on either side of an implication.
+
+use super::constraints::Clause;
+use super::VariableManager;
+use anyhow::Result;
+
+/// A literal or a constant, so networks can fold fixed values away
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    False,
+    True,
+    Literal(i32),
+}
+
+impl Signal {
+    /// Logical negation
+    pub fn negate(self) -> Self {
+        match self {
+            Signal::False => Signal::True,
+            Signal::True => Signal::False,
+            Signal::Literal(literal) => Signal::Literal(-literal),
+        }
+    }
+}
+
+/// Number of true inputs, as produced by a cardinality network
+#[derive(Debug, Clone)]
+pub enum InputCount {
+    /// `outputs[i]` is true iff at least `i + 1` inputs are true
+    Unary(Vec<Signal>),
+    /// Binary count, least significant bit first
+    Binary(Vec<Signal>),
+}
+
+impl InputCount {
+    /// Signal that holds iff at least `k` inputs are true
+    ///
+    /// Only unary counts expose this as a single signal; binary counts give `None`.
+    /// For a count built with `totalizer_up_to`, `k` must not exceed the limit.
+    pub fn at_least(&self, k: usize) -> Option<Signal> {
+        match self {
+            InputCount::Unary(outputs) => Some(unary_at(outputs, k)),
+            InputCount::Binary(_) => None,
+        }
+    }
+
+    /// Signals whose conjunction holds iff exactly `k` inputs are true
+    pub fn equals(&self, k: usize) -> Vec<Signal> {
+        match self {
+            InputCount::Unary(outputs) => {
+                if k > outputs.len() {
+                    return vec![Signal::False];
+                }
+                let mut conditions = Vec::new();
+                if k > 0 {
+                    conditions.push(outputs[k - 1]);
+                }
+                if k < outputs.len() {
+                    conditions.push(outputs[k].negate());
+                }
+                conditions
+            }
+            InputCount::Binary(bits) => {
+                if k >> bits.len() != 0 {
+                    return vec![Signal::False];
+                }
+                bits.iter()
+                    .enumerate()
+                    .map(|(i, &bit)| if k & (1 << i) != 0 { bit } else { bit.negate() })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Builds cardinality networks, allocating outputs from a `VariableManager`
+pub struct CardinalityEncoder<'a> {
+    variables: &'a mut VariableManager,
+    clauses: Vec<Clause>,
+}
+
+impl<'a> CardinalityEncoder<'a> {
+    /// Create an encoder that takes auxiliary variables from `variables`
+    pub fn new(variables: &'a mut VariableManager) -> Self {
+        Self {
+            variables,
+            clauses: Vec::new(),
+        }
+    }
+
+    /// Take the clauses generated so far
+    pub fn into_clauses(self) -> Vec<Clause> {
+        self.clauses
+    }
+
+    /// Add a clause over signals, dropping false signals and skipping satisfied clauses
+    pub fn add_clause(&mut self, signals: &[Signal]) {
+        let mut literals = Vec::with_capacity(signals.len());
+        for signal in signals {
+            match signal {
+                Signal::True => return,
+                Signal::False => {}
+                Signal::Literal(literal) => literals.push(*literal),
+            }
+        }
+        self.clauses.push(Clause::new(literals));
+    }
+
+    /// Count inputs with a totalizer: a balanced tree of unary adders
+    pub fn totalizer(&mut self, inputs: &[i32]) -> Result<InputCount> {
+        self.totalizer_up_to(inputs, inputs.len())
+    }
+
+    /// Totalizer that only counts up to `limit`
+    ///
+    /// Every node keeps at most `limit` outputs, so bounding a large sum
+    /// costs `O(n * limit)` clauses instead of `O(n^2)`. The outputs mean
+    /// "at least k inputs are true" for `k <= limit`; larger counts are not
+    /// represented.
+    pub fn totalizer_up_to(&mut self, inputs: &[i32], limit: usize) -> Result<InputCount> {
+        let inputs: Vec<Signal> = inputs.iter().map(|&literal| Signal::Literal(literal)).collect();
+        Ok(InputCount::Unary(self.totalize(&inputs, limit)?))
+    }
+
+    /// Count inputs with a sequential counter: a running unary count, one input at a time
+    pub fn sequential_counter(&mut self, inputs: &[i32]) -> Result<InputCount> {
+        let mut counts: Vec<Signal> = Vec::new();
+
+        for &input in inputs {
+            let input = Signal::Literal(input);
+            if counts.is_empty() {
+                counts.push(input);
+                continue;
+            }
+
+            let previous = |j: usize| unary_at(&counts, j);
+            let mut next = Vec::with_capacity(counts.len() + 1);
+            for j in 1..=counts.len() + 1 {
+                // at_least_j <-> previous_j or (input and previous_{j-1})
+                let output = self.fresh()?;
+                self.add_clause(&[previous(j).negate(), output]);
+                self.add_clause(&[input.negate(), previous(j - 1).negate(), output]);
+                self.add_clause(&[output.negate(), previous(j), input]);
+                self.add_clause(&[output.negate(), previous(j), previous(j - 1)]);
+                next.push(output);
+            }
+            counts = next;
+        }
+
+        Ok(InputCount::Unary(counts))
+    }
+
+    /// Count inputs in binary with a network of full and half adders
+    pub fn adder(&mut self, inputs: &[i32]) -> Result<InputCount> {
+        let mut columns: Vec<Vec<Signal>> =
+            vec![inputs.iter().map(|&literal| Signal::Literal(literal)).collect()];
+        let mut bits = Vec::new();
+
+        let mut weight = 0;
+        while weight < columns.len() {
+            while columns[weight].len() > 1 {
+                let take = columns[weight].len().min(3);
+                let group: Vec<Signal> = columns[weight].drain(..take).collect();
+
+                let sum = self.fresh()?;
+                let carry = self.fresh()?;
+                self.define_parity(sum, &group);
+                self.define_carry(carry, &group);
+
+                columns[weight].push(sum);
+                if columns.len() == weight + 1 {
+                    columns.push(Vec::new());
+                }
+                columns[weight + 1].push(carry);
+            }
+            bits.push(columns[weight].first().copied().unwrap_or(Signal::False));
+            weight += 1;
+        }
+
+        Ok(InputCount::Binary(bits))
+    }
+
+    fn fresh(&mut self) -> Result<Signal> {
+        Ok(Signal::Literal(self.variables.new_auxiliary_variable()?))
+    }
+
+    fn totalize(&mut self, inputs: &[Signal], limit: usize) -> Result<Vec<Signal>> {
+        if inputs.len() <= 1 {
+            return Ok(inputs.iter().copied().take(limit).collect());
+        }
+
+        let (left, right) = inputs.split_at(inputs.len() / 2);
+        let left = self.totalize(left, limit)?;
+        let right = self.totalize(right, limit)?;
+
+        let outputs = (0..(left.len() + right.len()).min(limit))
+            .map(|_| self.fresh())
+            .collect::<Result<Vec<_>>>()?;
+
+        for i in 0..=left.len() {
+            for j in 0..=right.len() {
+                // left >= i and right >= j implies sum >= i + j (capped at the limit)
+                if i + j > 0 && !outputs.is_empty() {
+                    self.add_clause(&[
+                        unary_at(&left, i).negate(),
+                        unary_at(&right, j).negate(),
+                        outputs[(i + j).min(outputs.len()) - 1],
+                    ]);
+                }
+                // left <= i and right <= j implies sum <= i + j
+                if i + j < outputs.len() {
+                    self.add_clause(&[
+                        unary_at(&left, i + 1),
+                        unary_at(&right, j + 1),
+                        outputs[i + j].negate(),
+                    ]);
+                }
+            }
+        }
+
+        Ok(outputs)
+    }
+
+    /// `output <-> xor(inputs)`, one clause per input assignment
+    fn define_parity(&mut self, output: Signal, inputs: &[Signal]) {
+        for assignment in 0..1usize << inputs.len() {
+            let mut clause: Vec<Signal> = inputs
+                .iter()
+                .enumerate()
+                .map(|(i, &input)| if assignment & (1 << i) != 0 { input.negate() } else { input })
+                .collect();
+            let odd = assignment.count_ones() % 2 == 1;
+            clause.push(if odd { output } else { output.negate() });
+            self.add_clause(&clause);
+        }
+    }
+
+    /// `output <-> at least two of inputs` (AND for two inputs, majority for three)
+    fn define_carry(&mut self, output: Signal, inputs: &[Signal]) {
+        for i in 0..inputs.len() {
+            for j in i + 1..inputs.len() {
+                self.add_clause(&[inputs[i].negate(), inputs[j].negate(), output]);
+            }
+        }
+        // Two true inputs leave at least one true among any other n-1 of them
+        for skipped in 0..inputs.len() {
+            let mut clause: Vec<Signal> = inputs
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| i != skipped)
+                .map(|(_, &input)| input)
+                .collect();
+            clause.push(output.negate());
+            self.add_clause(&clause);
+        }
+    }
+}
+
+/// `at least j` from a unary count, with the constant ends filled in
+fn unary_at(outputs: &[Signal], j: usize) -> Signal {
+    if j == 0 {
+        Signal::True
+    } else if j > outputs.len() {
+        Signal::False
+    } else {
+        outputs[j - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sat::{SatBackend, SatSolver};
+
+    type Network = fn(&mut CardinalityEncoder, &[i32]) -> Result<InputCount>;
+
+    const NETWORKS: [(&str, Network); 3] = [
+        ("totalizer", |encoder, inputs| encoder.totalizer(inputs)),
+        ("sequential counter", |encoder, inputs| encoder.sequential_counter(inputs)),
+        ("adder", |encoder, inputs| encoder.adder(inputs)),
+    ];
+
+    /// Solve with the inputs fixed and check the count is forced to the right value
+    fn check_count(network: Network, n: usize, assignment: usize) -> bool {
+        let mut variables = VariableManager::new(n.max(1), 1, 1, true);
+        let inputs: Vec<i32> = (0..n).map(|x| variables.cell_variable(x, 0, 0).unwrap()).collect();
+
+        let mut encoder = CardinalityEncoder::new(&mut variables);
+        let count = network(&mut encoder, &inputs).unwrap();
+        for (i, &input) in inputs.iter().enumerate() {
+            let value = if assignment & (1 << i) != 0 { input } else { -input };
+            encoder.add_clause(&[Signal::Literal(value)]);
+        }
+        let expected = assignment.count_ones() as usize;
+
+        // Every model agrees on the count: asking for any other count is unsatisfiable
+        (0..=n).all(|k| {
+            let mut solver = SatSolver::new();
+            solver.add_clauses(&encoder.clauses).unwrap();
+            for condition in count.equals(k) {
+                match condition {
+                    Signal::True => {}
+                    Signal::False => solver.add_clause(&Clause::new(vec![])).unwrap(),
+                    Signal::Literal(literal) => solver.add_clause(&Clause::unit(literal)).unwrap(),
+                }
+            }
+            let satisfiable = solver.solve().unwrap().is_some();
+            satisfiable == (k == expected)
+        })
+    }
+
+    #[test]
+    fn test_networks_count_exactly() {
+        for (name, network) in NETWORKS {
+            for n in 0..=5 {
+                for assignment in 0..1usize << n {
+                    assert!(check_count(network, n, assignment), "{} with {} inputs, assignment {:b}", name, n, assignment);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_networks_count_eight_neighbors() {
+        for (name, network) in NETWORKS {
+            for assignment in [0, 0b1, 0b1011, 0b0111_0110, 0b1111_1110, 0b1111_1111] {
+                assert!(check_count(network, 8, assignment), "{} assignment {:b}", name, assignment);
+            }
+        }
+    }
+
+    #[test]
+    fn test_truncated_totalizer() {
+        for n in 0..=5 {
+            for limit in 0..=n {
+                for assignment in 0..1usize << n {
+                    let mut variables = VariableManager::new(n.max(1), 1, 1, true);
+                    let inputs: Vec<i32> = (0..n).map(|x| variables.cell_variable(x, 0, 0).unwrap()).collect();
+                    let mut encoder = CardinalityEncoder::new(&mut variables);
+                    let count = encoder.totalizer_up_to(&inputs, limit).unwrap();
+                    let clauses = encoder.into_clauses();
+                    let true_inputs = assignment.count_ones() as usize;
+
+                    // Every "at least k" output up to the limit is forced to the right value
+                    for k in 0..=limit {
+                        for claimed in [false, true] {
+                            let mut solver = SatSolver::new();
+                            solver.add_clauses(&clauses).unwrap();
+                            for (i, &input) in inputs.iter().enumerate() {
+                                solver.add_clause(&Clause::unit(if assignment & (1 << i) != 0 { input } else { -input })).unwrap();
+                            }
+                            let satisfiable = match count.at_least(k).unwrap() {
+                                Signal::True => claimed,
+                                Signal::False => !claimed,
+                                Signal::Literal(literal) => {
+                                    solver.add_clause(&Clause::unit(if claimed { literal } else { -literal })).unwrap();
+                                    solver.solve().unwrap().is_some()
+                                }
+                            };
+                            assert_eq!(satisfiable, claimed == (true_inputs >= k),
+                                       "n {} limit {} assignment {:b} k {}", n, limit, assignment, k);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_constant_folding() {
+        let mut variables = VariableManager::new(1, 1, 1, true);
+        let mut encoder = CardinalityEncoder::new(&mut variables);
+
+        encoder.add_clause(&[Signal::Literal(1), Signal::True]);
+        assert!(encoder.clauses.is_empty());
+
+        encoder.add_clause(&[Signal::Literal(1), Signal::False]);
+        assert_eq!(encoder.into_clauses(), vec![Clause::unit(1)]);
+    }
+}
@@ -1,26 +1,14 @@
 //! Constraint generation for Game of Life SAT encoding
 
-use super::VariableManager;
-use crate::config::BoundaryCondition;
-use crate::game_of_life::{Grid, GameOfLifeRules};
+use super::{minimized_transition, CardinalityEncoder, InputCount, Signal, TransitionCnf, VariableManager};
+use crate::config::{BoundaryCondition, TransitionEncoding};
+use super::symmetry::{pattern_symmetries, GridSymmetry};
+use super::truth_table::CURRENT_CELL_INPUT;
+use crate::game_of_life::rule::NEIGHBOR_OFFSETS;
+use crate::game_of_life::{Grid, Rule, TargetPattern};
 use anyhow::Result;
-
-/// Constraint strength levels for adaptive symmetry breaking
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum ConstraintStrength {
-    Full,    // Maximum constraints for early time steps
-    Medium,  // Balanced constraints for middle time steps
-    Light,   // Minimal constraints for later time steps
-}
-
-/// Types of symmetry breaking constraints
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum SymmetryType {
-    Lexicographic,  // Lexicographic ordering constraints
-    Rotational,     // Rotational symmetry breaking
-    Reflectional,   // Reflection symmetry breaking
-    Translational,  // Translation symmetry breaking
-}
+use std::collections::HashSet;
+use std::sync::Arc;
 
 /// Represents a SAT clause (disjunction of literals)
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -63,6 +51,16 @@ pub struct ConstraintGenerator {
     time_steps: usize,
     boundary_condition: BoundaryCondition,
     symmetry_breaking: bool,
+    rule: Rule,
+    transition: TransitionEncoding,
+    minimized: Option<Arc<TransitionCnf>>,
+    symmetry_generation: usize,
+    symmetries: Vec<GridSymmetry>,
+    dead_cells: Vec<(usize, usize, usize)>,
+    min_population: Option<usize>,
+    max_population: Option<usize>,
+    count_population: bool,
+    clause_count: usize,
 }
 
 impl ConstraintGenerator {
@@ -74,7 +72,8 @@ impl ConstraintGenerator {
         boundary_condition: BoundaryCondition,
         symmetry_breaking: bool,
     ) -> Self {
-        let variable_manager = VariableManager::new(width, height, time_steps, false);
+        // Lex-leader symmetry breaking needs auxiliary variables
+        let variable_manager = VariableManager::new(width, height, time_steps, symmetry_breaking);
         
         Self {
             variable_manager,
@@ -83,31 +82,210 @@ impl ConstraintGenerator {
             time_steps,
             boundary_condition,
             symmetry_breaking,
-        }
+            rule: Rule::conway(),
+            transition: TransitionEncoding::Direct,
+            minimized: None,
+            symmetry_generation: 0,
+            symmetries: Vec::new(),
+            dead_cells: Vec::new(),
+            min_population: None,
+            max_population: None,
+            count_population: false,
+            clause_count: 0,
+        }
+    }
+
+    /// Encode transitions under the given rule instead of Conway's B3/S23
+    pub fn with_rule(mut self, rule: Rule) -> Self {
+        self.rule = rule;
+        self.minimized = None;
+        self
+    }
+
+    /// Break symmetries on the given generation instead of the predecessor
+    ///
+    /// Use the generation solutions are projected onto, so one representative
+    /// of every orbit of distinct solutions is kept.
+    pub fn with_symmetry_generation(mut self, generation: usize) -> Self {
+        self.symmetry_generation = generation;
+        self
+    }
+
+    /// Require the cells `(x, y, t)` to be dead, e.g. outside the area a margin allows
+    pub fn with_dead_cells(mut self, dead_cells: Vec<(usize, usize, usize)>) -> Self {
+        self.dead_cells = dead_cells;
+        self
+    }
+
+    /// Encode neighbor counts with the given transition encoding
+    ///
+    /// Must be called before any constraints are generated, since counting
+    /// encodings need a variable manager that allows auxiliary variables.
+    pub fn with_transition_encoding(mut self, transition: TransitionEncoding) -> Self {
+        self.transition = transition;
+        self.reset_variable_manager();
+        self
+    }
+
+    /// Bound the number of live cells in the predecessor
+    ///
+    /// Like the transition encoding, this must be set before any constraints
+    /// are generated.
+    pub fn with_population_bounds(mut self, min: Option<usize>, max: Option<usize>) -> Self {
+        self.min_population = min;
+        self.max_population = max;
+        self.reset_variable_manager();
+        self
+    }
+
+    /// Allow `generate_population_counter` after the constraints are generated
+    pub fn with_population_counter(mut self) -> Self {
+        self.count_population = true;
+        self.reset_variable_manager();
+        self
+    }
+
+    /// Start over with a variable manager that allows the auxiliary variables now needed
+    fn reset_variable_manager(&mut self) {
+        let use_auxiliary = self.transition.counts_neighbors()
+            || self.symmetry_breaking
+            || self.count_population
+            || self.min_population.is_some()
+            || self.max_population.is_some();
+        self.variable_manager = VariableManager::new(self.width, self.height, self.time_steps, use_auxiliary);
     }
 
     /// Generate all constraints for the reverse Game of Life problem
     pub fn generate_all_constraints(&mut self, target_grid: &Grid) -> Result<Vec<Clause>> {
+        self.generate_all_constraints_for_pattern(&TargetPattern::from_grid(target_grid.clone()))
+    }
+
+    /// Generate all constraints for a target whose don't-care cells are left free
+    pub fn generate_all_constraints_for_pattern(&mut self, target: &TargetPattern) -> Result<Vec<Clause>> {
+        let (clauses, _) = self.generate_constraints_with_assumptions(target, &[])?;
+        Ok(clauses)
+    }
+
+    /// Generate all constraints, leaving the target cells in `assumed` to assumptions
+    ///
+    /// Instead of unit clauses, the specified `(row, col)` cells of the final
+    /// state get a literal fixing them to their target state, returned in the
+    /// order of `assumed` for the solver to assume. Symmetry breaking relies on
+    /// the whole target holding, so it is only sound when every assumption is
+    /// made.
+    pub fn generate_constraints_with_assumptions(
+        &mut self,
+        target: &TargetPattern,
+        assumed: &[(usize, usize)],
+    ) -> Result<(Vec<Clause>, Vec<i32>)> {
         let mut clauses = Vec::new();
+        let final_time = self.time_steps - 1;
+
+        // Assumed cells are left out of the unit clauses
+        let mut fixed = target.clone();
+        let mut assumptions = Vec::with_capacity(assumed.len());
+        for &(row, col) in assumed {
+            if !target.cares(row, col) {
+                anyhow::bail!("Cell ({}, {}) is not specified by the target", row, col);
+            }
+            let index = target.grid.index(row, col);
+            fixed.care[index] = false;
+
+            let cell_var = self.variable_manager.cell_variable(col, row, final_time)?;
+            assumptions.push(if target.grid.get(row, col) { cell_var } else { -cell_var });
+        }
+
+        if self.transition.counts_neighbors() && !self.rule.is_totalistic() {
+            anyhow::bail!(
+                "The {:?} transition encoding counts neighbors, but rule {} depends on their positions",
+                self.transition,
+                self.rule
+            );
+        }
 
         // 1. Target state constraints (final time step must match target)
-        clauses.extend(self.generate_target_constraints(target_grid)?);
+        clauses.extend(self.generate_target_constraints(&fixed)?);
+
+        // 2. Cells required to be dead at earlier generations
+        for &(x, y, t) in &self.dead_cells {
+            clauses.push(Clause::unit(-self.variable_manager.cell_variable(x, y, t)?));
+        }
 
-        // 2. Game of Life transition constraints for each time step
+        // 3. Game of Life transition constraints for each time step
         for t in 0..self.time_steps - 1 {
             clauses.extend(self.generate_transition_constraints(t)?);
         }
 
-        // 3. Symmetry breaking constraints (if enabled)
+        // 4. Symmetry breaking constraints (if enabled)
         if self.symmetry_breaking {
-            clauses.extend(self.generate_symmetry_breaking_constraints()?);
+            clauses.extend(self.generate_symmetry_breaking_constraints(target)?);
+        }
+
+        // 5. Population bounds on the predecessor
+        if self.min_population.is_some() || self.max_population.is_some() {
+            clauses.extend(self.generate_population_bounds()?);
+        }
+
+        self.clause_count = clauses.len();
+        Ok((clauses, assumptions))
+    }
+
+    /// Count the live cells of the predecessor with a totalizer
+    ///
+    /// The count is exact up to `limit`. Cells that are required to be dead
+    /// are left out of the count. Call after `generate_all_constraints` and
+    /// add the returned clauses alongside its clauses.
+    pub fn generate_population_counter(&mut self, limit: usize) -> Result<(Vec<Clause>, InputCount)> {
+        let dead: HashSet<(usize, usize)> = self.dead_cells.iter()
+            .filter(|cell| cell.2 == 0)
+            .map(|&(x, y, _)| (x, y))
+            .collect();
+
+        let mut inputs = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if !dead.contains(&(x, y)) {
+                    inputs.push(self.variable_manager.cell_variable(x, y, 0)?);
+                }
+            }
+        }
+
+        let mut encoder = CardinalityEncoder::new(&mut self.variable_manager);
+        let count = encoder.totalizer_up_to(&inputs, limit)?;
+        Ok((encoder.into_clauses(), count))
+    }
+
+    /// Generate constraints keeping the predecessor population within the configured bounds
+    fn generate_population_bounds(&mut self) -> Result<Vec<Clause>> {
+        let min = self.min_population.unwrap_or(0);
+        // Counting one past the maximum is enough to rule it out
+        let limit = match self.max_population {
+            Some(max) => min.max(max + 1),
+            None => min,
+        };
+
+        let (mut clauses, count) = self.generate_population_counter(limit)?;
+        let mut bounds = vec![count.at_least(min).expect("totalizer counts are unary")];
+        if let Some(max) = self.max_population {
+            bounds.push(count.at_least(max + 1).expect("totalizer counts are unary").negate());
+        }
+
+        for bound in bounds {
+            match bound {
+                Signal::True => {}
+                Signal::False => anyhow::bail!(
+                    "Minimum population {} is more than the predecessor has room for", min
+                ),
+                Signal::Literal(literal) => clauses.push(Clause::unit(literal)),
+            }
         }
 
         Ok(clauses)
     }
 
-    /// Generate constraints that fix the final state to match the target
-    fn generate_target_constraints(&mut self, target_grid: &Grid) -> Result<Vec<Clause>> {
+    /// Generate constraints that fix the specified cells of the final state
+    fn generate_target_constraints(&mut self, target: &TargetPattern) -> Result<Vec<Clause>> {
+        let target_grid = &target.grid;
         let mut clauses = Vec::new();
         let final_time = self.time_steps - 1;
 
@@ -118,10 +296,13 @@ impl ConstraintGenerator {
 
         for y in 0..self.height {
             for x in 0..self.width {
+                if !target.cares(y, x) {
+                    continue;
+                }
+
                 let cell_var = self.variable_manager.cell_variable(x, y, final_time)?;
                 let target_alive = target_grid.get(y, x);
 
-
                 if target_alive {
                     // Cell must be alive
                     clauses.push(Clause::unit(cell_var));
@@ -155,13 +336,72 @@ impl ConstraintGenerator {
         let current_cell = self.variable_manager.cell_variable(x, y, t)?;
         let next_cell = self.variable_manager.cell_variable(x, y, t + 1)?;
 
-        clauses.extend(self.generate_direct_transition_constraints(x, y, t, current_cell, next_cell)?);
+        match self.transition {
+            TransitionEncoding::Direct => {
+                clauses.extend(self.generate_direct_transition_constraints(x, y, t, current_cell, next_cell)?);
+            }
+            TransitionEncoding::Minimized => {
+                clauses.extend(self.generate_minimized_transition_constraints(x, y, t, current_cell, next_cell)?);
+            }
+            TransitionEncoding::Totalizer | TransitionEncoding::SequentialCounter | TransitionEncoding::Adder => {
+                clauses.extend(self.generate_counted_transition_constraints(x, y, t, current_cell, next_cell)?);
+            }
+        }
 
         Ok(clauses)
     }
 
+    /// Generate transition constraints over a neighbor count held in auxiliary variables
+    ///
+    /// Only valid for totalistic rules: the next state is looked up from the
+    /// current state and the count, two clauses per possible count.
+    fn generate_counted_transition_constraints(
+        &mut self,
+        x: usize,
+        y: usize,
+        t: usize,
+        current_cell: i32,
+        next_cell: i32,
+    ) -> Result<Vec<Clause>> {
+        // Neighbors beyond a dead boundary never count
+        let neighbor_vars: Vec<i32> = self.get_neighbor_variables(x, y, t)?
+            .into_iter()
+            .flatten()
+            .collect();
+
+        let mut encoder = CardinalityEncoder::new(&mut self.variable_manager);
+        let count = match self.transition {
+            TransitionEncoding::Totalizer => encoder.totalizer(&neighbor_vars)?,
+            TransitionEncoding::SequentialCounter => encoder.sequential_counter(&neighbor_vars)?,
+            TransitionEncoding::Adder => encoder.adder(&neighbor_vars)?,
+            TransitionEncoding::Direct | TransitionEncoding::Minimized => {
+                unreachable!("{:?} encoding uses no neighbor count", self.transition)
+            }
+        };
+
+        for current_alive in [false, true] {
+            let current = if current_alive { Signal::Literal(current_cell) } else { Signal::Literal(-current_cell) };
+
+            for k in 0..=neighbor_vars.len() {
+                let next_alive = self.rule.should_be_alive(current_alive, k as u8);
+                let next = if next_alive { Signal::Literal(next_cell) } else { Signal::Literal(-next_cell) };
+
+                // current state and exactly k neighbors imply the next state
+                let mut clause = vec![current.negate()];
+                clause.extend(count.equals(k).into_iter().map(Signal::negate));
+                clause.push(next);
+                encoder.add_clause(&clause);
+            }
+        }
+
+        Ok(encoder.into_clauses())
+    }
+
 
     /// Generate transition constraints without auxiliary variables (direct encoding)
+    ///
+    /// One clause per current state and neighborhood configuration, so any
+    /// rule in `Rule`'s 256-configuration form (totalistic or Hensel) is exact.
     fn generate_direct_transition_constraints(
         &mut self,
         x: usize,
@@ -172,37 +412,32 @@ impl ConstraintGenerator {
     ) -> Result<Vec<Clause>> {
         let mut clauses = Vec::new();
 
-        // Get neighbor variables
+        // Get neighbor variables in configuration bit order
         let neighbor_vars = self.get_neighbor_variables(x, y, t)?;
 
-        // Generate constraints for each possible neighbor count
-        for k in 0..=8 {
-            // Generate all combinations of k neighbors being alive
-            let neighbor_combinations = self.generate_neighbor_combinations(&neighbor_vars, k);
-
-            for combination in neighbor_combinations {
-                if GameOfLifeRules::should_be_alive(true, k) {
-                    // If current cell is alive and exactly k neighbors are alive, next cell should be alive
-                    let mut clause = vec![-current_cell, next_cell];
-                    clause.extend(combination.iter().map(|&(var, alive)| if alive { -var } else { var }));
-                    clauses.push(Clause::new(clause));
-                } else {
-                    // If current cell is alive and exactly k neighbors are alive, next cell should be dead
-                    let mut clause = vec![-current_cell, -next_cell];
-                    clause.extend(combination.iter().map(|&(var, alive)| if alive { -var } else { var }));
-                    clauses.push(Clause::new(clause));
+        for configuration in 0..=u8::MAX {
+            for current_alive in [false, true] {
+                let mut inputs = vec![(current_cell, current_alive)];
+                let mut reachable = true;
+
+                for (bit, neighbor) in neighbor_vars.iter().enumerate() {
+                    let alive = configuration & (1 << bit) != 0;
+                    match neighbor {
+                        Some(var) => inputs.push((*var, alive)),
+                        // Cells beyond a dead boundary can't be alive
+                        None if alive => reachable = false,
+                        None => {}
+                    }
                 }
 
-                if GameOfLifeRules::should_be_alive(false, k) {
-                    // If current cell is dead and exactly k neighbors are alive, next cell should be alive
-                    let mut clause = vec![current_cell, next_cell];
-                    clause.extend(combination.iter().map(|&(var, alive)| if alive { -var } else { var }));
-                    clauses.push(Clause::new(clause));
-                } else {
-                    // If current cell is dead and exactly k neighbors are alive, next cell should be dead
-                    let mut clause = vec![current_cell, -next_cell];
-                    clause.extend(combination.iter().map(|&(var, alive)| if alive { -var } else { var }));
-                    clauses.push(Clause::new(clause));
+                if !reachable {
+                    continue;
+                }
+
+                let next_alive = self.rule.next_state(current_alive, configuration);
+                let next_literal = if next_alive { next_cell } else { -next_cell };
+                if let Some(clause) = Self::implication_clause(&inputs, next_literal) {
+                    clauses.push(clause);
                 }
             }
         }
@@ -210,26 +445,78 @@ impl ConstraintGenerator {
         Ok(clauses)
     }
 
+    /// Generate transition constraints from the rule's minimized truth table
+    ///
+    /// The prime-implicant CNF is shared by every cell; here its inputs are
+    /// bound to this cell's variables and simplified at dead boundaries.
+    fn generate_minimized_transition_constraints(
+        &mut self,
+        x: usize,
+        y: usize,
+        t: usize,
+        current_cell: i32,
+        next_cell: i32,
+    ) -> Result<Vec<Clause>> {
+        let cnf = Arc::clone(self.minimized.get_or_insert_with(|| minimized_transition(&self.rule)));
+        let neighbor_vars = self.get_neighbor_variables(x, y, t)?;
+        let mut clauses = Vec::with_capacity(cnf.clause_count());
+
+        let terms = cnf.alive_terms.iter().map(|term| (term, next_cell))
+            .chain(cnf.dead_terms.iter().map(|term| (term, -next_cell)));
+
+        'terms: for (term, next_literal) in terms {
+            let mut inputs = Vec::with_capacity(term.literal_count() as usize);
+            for (input, value) in term.literals() {
+                let var = if input == CURRENT_CELL_INPUT { Some(current_cell) } else { neighbor_vars[input] };
+                match var {
+                    Some(var) => inputs.push((var, value)),
+                    // Cells beyond a dead boundary can't be alive
+                    None if value => continue 'terms,
+                    None => {}
+                }
+            }
 
-    /// Get neighbor variables for a cell, handling boundary conditions
-    fn get_neighbor_variables(&mut self, x: usize, y: usize, t: usize) -> Result<Vec<i32>> {
-        let mut neighbors = Vec::new();
+            if let Some(clause) = Self::implication_clause(&inputs, next_literal) {
+                clauses.push(clause);
+            }
+        }
 
-        for dy in -1..=1 {
-            for dx in -1..=1 {
-                if dx == 0 && dy == 0 {
-                    continue; // Skip the cell itself
-                }
+        Ok(clauses)
+    }
 
-                let nx = x as isize + dx;
-                let ny = y as isize + dy;
+    /// Clause saying the given variable assignment implies `consequence`
+    ///
+    /// Returns `None` if the assignment gives a variable both values, which
+    /// happens when wrap or mirror boundaries map several neighbors to one cell.
+    fn implication_clause(assignment: &[(i32, bool)], consequence: i32) -> Option<Clause> {
+        let mut literals: Vec<i32> = Vec::with_capacity(assignment.len() + 1);
 
-                if let Some(neighbor_var) = self.get_neighbor_variable_with_boundary(nx, ny, t)? {
-                    neighbors.push(neighbor_var);
-                }
+        for &(var, value) in assignment {
+            let literal = if value { -var } else { var };
+            if literals.contains(&-literal) {
+                return None;
+            }
+            if !literals.contains(&literal) {
+                literals.push(literal);
             }
         }
 
+        literals.push(consequence);
+        Some(Clause::new(literals))
+    }
+
+    /// Get neighbor variables for a cell in `NEIGHBOR_OFFSETS` order, handling boundary conditions
+    ///
+    /// `None` marks a neighbor outside a dead boundary.
+    fn get_neighbor_variables(&mut self, x: usize, y: usize, t: usize) -> Result<[Option<i32>; 8]> {
+        let mut neighbors = [None; 8];
+
+        for (neighbor, (dy, dx)) in neighbors.iter_mut().zip(NEIGHBOR_OFFSETS) {
+            let nx = x as isize + dx;
+            let ny = y as isize + dy;
+            *neighbor = self.get_neighbor_variable_with_boundary(nx, ny, t)?;
+        }
+
         Ok(neighbors)
     }
 
@@ -274,408 +561,67 @@ impl ConstraintGenerator {
         }
     }
 
-    /// Generate all combinations of exactly k neighbors being alive
-    fn generate_neighbor_combinations(&self, neighbor_vars: &[i32], k: u8) -> Vec<Vec<(i32, bool)>> {
-        let n = neighbor_vars.len();
-        let k = k as usize;
-        let mut combinations = Vec::new();
-
-        if k > n {
-            return combinations; // Impossible to have more alive than total neighbors
-        }
-
-        // Generate all combinations of choosing k positions to be alive
-        let mut alive_indices = Vec::new();
-        self.generate_k_combinations(n, k, 0, &mut alive_indices, &mut combinations, neighbor_vars);
-
-        combinations
-    }
-
-    /// Generate combinations of exactly k alive neighbors using iterative approach
-    fn generate_k_combinations(
-        &self,
-        n: usize,
-        k: usize,
-        start: usize,
-        current_alive: &mut Vec<usize>,
-        result: &mut Vec<Vec<(i32, bool)>>,
-        neighbor_vars: &[i32],
-    ) {
-        if current_alive.len() == k {
-            // Create a combination with exactly k alive neighbors
-            let mut combination = Vec::new();
-            for (i, &var) in neighbor_vars.iter().enumerate() {
-                let is_alive = current_alive.contains(&i);
-                combination.push((var, is_alive));
-            }
-            result.push(combination);
-            return;
-        }
-
-        if start >= n || current_alive.len() + (n - start) < k {
-            return; // Not enough remaining positions to reach k
-        }
-
-        // Include current position as alive
-        current_alive.push(start);
-        self.generate_k_combinations(n, k, start + 1, current_alive, result, neighbor_vars);
-        current_alive.pop();
-
-        // Skip current position (keep it dead)
-        self.generate_k_combinations(n, k, start + 1, current_alive, result, neighbor_vars);
-    }
-
-    /// Generate symmetry breaking constraints for maximum speedup
-    fn generate_symmetry_breaking_constraints(&mut self) -> Result<Vec<Clause>> {
-        let mut clauses = Vec::new();
-        
-        for t in 0..self.time_steps {
-            let constraint_strength = self.calculate_constraint_strength(t);
-            
-            match constraint_strength {
-                ConstraintStrength::Full => {
-                    clauses.extend(self.generate_all_symmetry_constraints(t)?);
-                }
-                ConstraintStrength::Medium => {
-                    clauses.extend(self.generate_lexicographic_constraints(t)?);
-                    clauses.extend(self.generate_rotational_constraints(t)?);
-                }
-                ConstraintStrength::Light => {
-                    clauses.extend(self.generate_lexicographic_constraints(t)?);
-                }
-            }
-        }
-        
-        Ok(clauses)
-    }
-
-    /// Calculate constraint strength based on time step for optimal performance
-    fn calculate_constraint_strength(&self, t: usize) -> ConstraintStrength {
-        match t {
-            0 => ConstraintStrength::Full,      // Maximum early pruning
-            1..=2 => ConstraintStrength::Medium, // Balanced approach
-            _ => ConstraintStrength::Light,      // Minimal overhead
-        }
-    }
-
-    /// Generate all symmetry constraints for maximum breaking
-    fn generate_all_symmetry_constraints(&mut self, t: usize) -> Result<Vec<Clause>> {
-        let mut clauses = Vec::new();
-        
-        if self.should_apply_symmetry_type(SymmetryType::Lexicographic) {
-            clauses.extend(self.generate_lexicographic_constraints(t)?);
-        }
-        if self.should_apply_symmetry_type(SymmetryType::Rotational) {
-            clauses.extend(self.generate_rotational_constraints(t)?);
-        }
-        if self.should_apply_symmetry_type(SymmetryType::Reflectional) {
-            clauses.extend(self.generate_reflectional_constraints(t)?);
-        }
-        if self.should_apply_symmetry_type(SymmetryType::Translational) {
-            clauses.extend(self.generate_translational_constraints(t)?);
-        }
-        
-        Ok(clauses)
-    }
-
-    /// Check if a symmetry type should be applied based on grid size
-    fn should_apply_symmetry_type(&self, symmetry_type: SymmetryType) -> bool {
-        let grid_size = self.width * self.height;
-        
-        match (symmetry_type, grid_size) {
-            (SymmetryType::Lexicographic, _) => true,  // Always beneficial
-            (SymmetryType::Rotational, size) if size <= 100 => true,
-            (SymmetryType::Reflectional, size) if size <= 225 => true,
-            (SymmetryType::Translational, size) if size <= 64 => true,
-            _ => false,  // Skip for large grids to avoid constraint explosion
-        }
-    }
-
-    /// Generate lexicographic ordering constraints for maximum early pruning
-    fn generate_lexicographic_constraints(&mut self, t: usize) -> Result<Vec<Clause>> {
+    /// Generate lex-leader constraints for the symmetries that fix the target
+    ///
+    /// Each symmetry maps solutions to solutions, so requiring the symmetry
+    /// generation to be lexicographically no greater than its image keeps
+    /// exactly one representative of every orbit.
+    fn generate_symmetry_breaking_constraints(&mut self, target: &TargetPattern) -> Result<Vec<Clause>> {
         let mut clauses = Vec::new();
-        
-        // Only apply to initial time step for maximum early pruning with minimal risk
-        if t == 0 {
-            // Aggressive dominance-based symmetry breaking
-            clauses.extend(self.generate_minimal_rotation_breaking(t)?);
-        }
-        
-        Ok(clauses)
-    }
 
-    /// Generate dominance-based symmetry breaking constraints
-    /// Uses the most effective techniques to eliminate symmetric search branches
-    fn generate_minimal_rotation_breaking(&mut self, t: usize) -> Result<Vec<Clause>> {
-        let mut clauses = Vec::new();
-        
-        // Strategy: Use "first living cell" dominance constraint
-        // This is extremely effective for sparse patterns common in GoL reverse problems
-        
-        // For 180° rotation symmetry: compare first few positions with their rotated counterparts
-        let total_cells = self.width * self.height;
-        let constraint_positions = std::cmp::min(3, total_cells / 2);
-        
-        for i in 0..constraint_positions {
-            let y1 = i / self.width;
-            let x1 = i % self.width;
-            
-            // Calculate 180° rotated position
-            let x2 = self.width - 1 - x1;
-            let y2 = self.height - 1 - y1;
-            let pos2 = y2 * self.width + x2;
-            
-            // Skip if same position (center)
-            if i == pos2 {
-                continue;
-            }
-            
-            // Only add constraint if this is the lexicographically smaller position
-            if i < pos2 {
-                let var1 = self.variable_manager.cell_variable(x1, y1, t)?;
-                let var2 = self.variable_manager.cell_variable(x2, y2, t)?;
-                
-                // Dominance constraint: var2 → var1 (if rotated position is alive, original must be)
-                clauses.push(Clause::binary(-var2, var1));
-            }
-        }
-        
-        // Additional constraint: "first living cell" must be in canonical position
-        // This is very effective for sparse patterns
-        if self.width >= 3 && self.height >= 3 {
-            // If any cell in the bottom-right quadrant is alive,
-            // then at least one cell in the top-left quadrant must be alive
-            let mid_x = self.width / 2;
-            let mid_y = self.height / 2;
-            
-            // Sample key positions from bottom-right quadrant
-            let mut br_vars = Vec::new();
-            if mid_x < self.width - 1 && mid_y < self.height - 1 {
-                br_vars.push(self.variable_manager.cell_variable(self.width - 1, self.height - 1, t)?);
-                if mid_x + 1 < self.width - 1 {
-                    br_vars.push(self.variable_manager.cell_variable(self.width - 2, self.height - 1, t)?);
-                }
-                if mid_y + 1 < self.height - 1 {
-                    br_vars.push(self.variable_manager.cell_variable(self.width - 1, self.height - 2, t)?);
-                }
-            }
-            
-            // Sample key positions from top-left quadrant
-            let mut tl_vars = Vec::new();
-            tl_vars.push(self.variable_manager.cell_variable(0, 0, t)?);
-            if mid_x > 0 {
-                tl_vars.push(self.variable_manager.cell_variable(1, 0, t)?);
-            }
-            if mid_y > 0 {
-                tl_vars.push(self.variable_manager.cell_variable(0, 1, t)?);
-            }
-            
-            // Create constraints: if any bottom-right cell is alive, at least one top-left cell must be alive
-            for &br_var in &br_vars {
-                for &tl_var in &tl_vars {
-                    // br_var → tl_var
-                    clauses.push(Clause::binary(-br_var, tl_var));
-                }
-            }
-        }
-        
-        Ok(clauses)
-    }
+        self.symmetries = pattern_symmetries(target, &self.boundary_condition);
+        let cells = self.variable_manager.all_cell_variables_at_time(self.symmetry_generation)?;
 
-    /// Generate horizontal reflection lexicographic constraints
-    #[allow(dead_code)]
-    fn generate_horizontal_reflection_lex_constraints(&mut self, t: usize) -> Result<Vec<Clause>> {
-        let mut clauses = Vec::new();
-        
-        for y in 0..self.height {
-            for x in 0..self.width {
-                let orig_var = self.variable_manager.cell_variable(x, y, t)?;
-                let refl_var = self.variable_manager.cell_variable(x, self.height - 1 - y, t)?;
-                
-                // Constraint: orig_var >= refl_var
-                clauses.push(Clause::binary(-refl_var, orig_var));
-            }
-        }
-        
-        Ok(clauses)
-    }
+        for symmetry in self.symmetries.clone() {
+            let image: Vec<i32> = (0..self.height)
+                .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+                .map(|(x, y)| {
+                    let (tx, ty) = symmetry.apply(x, y, self.width, self.height);
+                    cells[ty * self.width + tx]
+                })
+                .collect();
 
-    /// Generate vertical reflection lexicographic constraints
-    #[allow(dead_code)]
-    fn generate_vertical_reflection_lex_constraints(&mut self, t: usize) -> Result<Vec<Clause>> {
-        let mut clauses = Vec::new();
-        
-        for y in 0..self.height {
-            for x in 0..self.width {
-                let orig_var = self.variable_manager.cell_variable(x, y, t)?;
-                let refl_var = self.variable_manager.cell_variable(self.width - 1 - x, y, t)?;
-                
-                // Constraint: orig_var >= refl_var
-                clauses.push(Clause::binary(-refl_var, orig_var));
-            }
+            clauses.extend(self.generate_lex_leq_constraints(&cells, &image)?);
         }
-        
-        Ok(clauses)
-    }
 
-    /// Generate rotational symmetry breaking constraints
-    fn generate_rotational_constraints(&mut self, t: usize) -> Result<Vec<Clause>> {
-        let mut clauses = Vec::new();
-        
-        if self.width == self.height {
-            // Square grid: break 90° and 270° rotations
-            clauses.extend(self.generate_90_rotation_constraints(t)?);
-            clauses.extend(self.generate_270_rotation_constraints(t)?);
-        }
-        
-        // Note: 180° rotation is handled by lexicographic constraints
-        
         Ok(clauses)
     }
 
-    /// Generate 90° rotation constraints for square grids
-    fn generate_90_rotation_constraints(&mut self, t: usize) -> Result<Vec<Clause>> {
+    /// Constrain `left <= right` lexicographically (false < true)
+    ///
+    /// `equal_k` auxiliary variables track whether the first k differing
+    /// positions agree; each is fully defined so models stay unique.
+    fn generate_lex_leq_constraints(&mut self, left: &[i32], right: &[i32]) -> Result<Vec<Clause>> {
         let mut clauses = Vec::new();
-        
-        // For square grids only
-        if self.width != self.height {
-            return Ok(clauses);
-        }
-        
-        let n = self.width;
-        
-        for y in 0..n {
-            for x in 0..n {
-                let orig_var = self.variable_manager.cell_variable(x, y, t)?;
-                // 90° clockwise rotation: (x,y) -> (y, n-1-x)
-                let rot_var = self.variable_manager.cell_variable(y, n - 1 - x, t)?;
-                
-                // Constraint: orig_var >= rot_var (lexicographic ordering)
-                clauses.push(Clause::binary(-rot_var, orig_var));
-            }
-        }
-        
-        Ok(clauses)
-    }
+        let pairs: Vec<(i32, i32)> = left.iter()
+            .zip(right)
+            .filter(|(a, b)| a != b)
+            .map(|(&a, &b)| (a, b))
+            .collect();
 
-    /// Generate 270° rotation constraints for square grids
-    fn generate_270_rotation_constraints(&mut self, t: usize) -> Result<Vec<Clause>> {
-        let mut clauses = Vec::new();
-        
-        // For square grids only
-        if self.width != self.height {
-            return Ok(clauses);
-        }
-        
-        let n = self.width;
-        
-        for y in 0..n {
-            for x in 0..n {
-                let orig_var = self.variable_manager.cell_variable(x, y, t)?;
-                // 270° clockwise rotation: (x,y) -> (n-1-y, x)
-                let rot_var = self.variable_manager.cell_variable(n - 1 - y, x, t)?;
-                
-                // Constraint: orig_var >= rot_var (lexicographic ordering)
-                clauses.push(Clause::binary(-rot_var, orig_var));
-            }
-        }
-        
-        Ok(clauses)
-    }
+        let mut prefix_equal: Option<i32> = None;
+        for (i, &(a, b)) in pairs.iter().enumerate() {
+            let guard: Vec<i32> = prefix_equal.map(|e| vec![-e]).unwrap_or_default();
 
-    /// Generate reflection symmetry breaking constraints
-    fn generate_reflectional_constraints(&mut self, t: usize) -> Result<Vec<Clause>> {
-        let mut clauses = Vec::new();
-        
-        // Diagonal reflections (for square grids)
-        if self.width == self.height {
-            clauses.extend(self.generate_diagonal_reflection_constraints(t)?);
-            clauses.extend(self.generate_anti_diagonal_reflection_constraints(t)?);
-        }
-        
-        // Note: Horizontal and vertical reflections are handled by lexicographic constraints
-        
-        Ok(clauses)
-    }
+            // Equal so far implies a <= b
+            clauses.push(Clause::new([guard.as_slice(), &[-a, b]].concat()));
 
-    /// Generate diagonal reflection constraints (main diagonal)
-    fn generate_diagonal_reflection_constraints(&mut self, t: usize) -> Result<Vec<Clause>> {
-        let mut clauses = Vec::new();
-        
-        // For square grids only
-        if self.width != self.height {
-            return Ok(clauses);
-        }
-        
-        let n = self.width;
-        
-        for y in 0..n {
-            for x in 0..n {
-                let orig_var = self.variable_manager.cell_variable(x, y, t)?;
-                // Diagonal reflection: (x,y) -> (y, x)
-                let refl_var = self.variable_manager.cell_variable(y, x, t)?;
-                
-                // Constraint: orig_var >= refl_var
-                clauses.push(Clause::binary(-refl_var, orig_var));
+            if i + 1 == pairs.len() {
+                break;
             }
-        }
-        
-        Ok(clauses)
-    }
 
-    /// Generate anti-diagonal reflection constraints
-    fn generate_anti_diagonal_reflection_constraints(&mut self, t: usize) -> Result<Vec<Clause>> {
-        let mut clauses = Vec::new();
-        
-        // For square grids only
-        if self.width != self.height {
-            return Ok(clauses);
-        }
-        
-        let n = self.width;
-        
-        for y in 0..n {
-            for x in 0..n {
-                let orig_var = self.variable_manager.cell_variable(x, y, t)?;
-                // Anti-diagonal reflection: (x,y) -> (n-1-y, n-1-x)
-                let refl_var = self.variable_manager.cell_variable(n - 1 - y, n - 1 - x, t)?;
-                
-                // Constraint: orig_var >= refl_var
-                clauses.push(Clause::binary(-refl_var, orig_var));
+            // equal <-> equal so far and a == b
+            let equal = self.variable_manager.new_auxiliary_variable()?;
+            clauses.push(Clause::new([guard.as_slice(), &[a, b, equal]].concat()));
+            clauses.push(Clause::new([guard.as_slice(), &[-a, -b, equal]].concat()));
+            clauses.push(Clause::new(vec![-equal, -a, b]));
+            clauses.push(Clause::new(vec![-equal, a, -b]));
+            if let Some(previous) = prefix_equal {
+                clauses.push(Clause::binary(-equal, previous));
             }
+            prefix_equal = Some(equal);
         }
-        
-        Ok(clauses)
-    }
-
-    /// Generate translational symmetry breaking constraints
-    fn generate_translational_constraints(&mut self, t: usize) -> Result<Vec<Clause>> {
-        let mut clauses = Vec::new();
-        
-        // Strategy 1: Corner anchoring (if any cell alive, specific corner must be alive)
-        clauses.extend(self.generate_corner_anchoring_constraints(t)?);
-        
-        Ok(clauses)
-    }
 
-    /// Generate corner anchoring constraints to eliminate translations
-    fn generate_corner_anchoring_constraints(&mut self, t: usize) -> Result<Vec<Clause>> {
-        let mut clauses = Vec::new();
-        
-        // If any cell is alive, then top-left corner must be alive
-        let corner_var = self.variable_manager.cell_variable(0, 0, t)?;
-        
-        for y in 0..self.height {
-            for x in 0..self.width {
-                if x == 0 && y == 0 { continue; }
-                
-                let cell_var = self.variable_manager.cell_variable(x, y, t)?;
-                // Constraint: cell_var -> corner_var
-                // In CNF: ¬cell_var ∨ corner_var
-                clauses.push(Clause::binary(-cell_var, corner_var));
-            }
-        }
-        
         Ok(clauses)
     }
 
@@ -686,11 +632,18 @@ impl ConstraintGenerator {
 
     /// Get constraint generation statistics
     pub fn statistics(&self) -> ConstraintStatistics {
+        let variable_stats = self.variable_manager.statistics();
+
         ConstraintStatistics {
             width: self.width,
             height: self.height,
             time_steps: self.time_steps,
             total_variables: self.variable_manager.variable_count(),
+            cell_variables: variable_stats.cell_variables,
+            auxiliary_variables: variable_stats.auxiliary_variables,
+            total_clauses: self.clause_count,
+            transition: self.transition,
+            symmetries_broken: self.symmetries.len(),
         }
     }
 }
@@ -702,6 +655,11 @@ pub struct ConstraintStatistics {
     pub height: usize,
     pub time_steps: usize,
     pub total_variables: usize,
+    pub cell_variables: usize,
+    pub auxiliary_variables: usize,
+    pub total_clauses: usize,
+    pub transition: TransitionEncoding,
+    pub symmetries_broken: usize,
 }
 
 impl std::fmt::Display for ConstraintStatistics {
@@ -709,7 +667,11 @@ impl std::fmt::Display for ConstraintStatistics {
         writeln!(f, "Constraint Generation Statistics:")?;
         writeln!(f, "  Grid size: {}x{}", self.width, self.height)?;
         writeln!(f, "  Time steps: {}", self.time_steps)?;
-        writeln!(f, "  Total variables: {}", self.total_variables)?;
+        writeln!(f, "  Transition encoding: {:?}", self.transition)?;
+        writeln!(f, "  Total variables: {} ({} cell, {} auxiliary)",
+                 self.total_variables, self.cell_variables, self.auxiliary_variables)?;
+        writeln!(f, "  Total clauses: {}", self.total_clauses)?;
+        writeln!(f, "  Symmetries broken: {}", self.symmetries_broken)?;
         Ok(())
     }
 }
@@ -759,11 +721,75 @@ mod tests {
         ];
         let target_grid = Grid::from_cells(cells, BoundaryCondition::Dead).unwrap();
 
-        let constraints = cg.generate_target_constraints(&target_grid).unwrap();
+        let constraints = cg.generate_target_constraints(&TargetPattern::from_grid(target_grid.clone())).unwrap();
         assert_eq!(constraints.len(), 4); // 2x2 grid = 4 cells
 
         // Check that constraints fix the target state
         assert!(constraints.iter().any(|c| c.literals == vec![cg.variable_manager.cell_variable(0, 0, 1).unwrap()]));
         assert!(constraints.iter().any(|c| c.literals == vec![-cg.variable_manager.cell_variable(1, 0, 1).unwrap()]));
+
+        // Don't-care cells get no unit clause
+        let pattern = TargetPattern::new(target_grid, vec![true, false, false, true]).unwrap();
+        let constraints = cg.generate_target_constraints(&pattern).unwrap();
+        assert_eq!(constraints.len(), 2);
+        let free_cell = cg.variable_manager.cell_variable(1, 0, 1).unwrap();
+        assert!(constraints.iter().all(|c| !c.literals.contains(&free_cell) && !c.literals.contains(&-free_cell)));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_assumed_target_cells() {
+        let mut cg = ConstraintGenerator::new(2, 1, 2, BoundaryCondition::Dead, false);
+        let target = TargetPattern::from_grid(Grid::from_cells(vec![vec![true, false]], BoundaryCondition::Dead).unwrap());
+
+        let all = cg.generate_all_constraints_for_pattern(&target).unwrap();
+        let (clauses, assumptions) = cg.generate_constraints_with_assumptions(&target, &[(0, 1), (0, 0)]).unwrap();
+
+        // The unit clauses turn into assumptions, in the order asked for
+        let alive = cg.variable_manager.cell_variable(0, 0, 1).unwrap();
+        let dead = cg.variable_manager.cell_variable(1, 0, 1).unwrap();
+        assert_eq!(assumptions, vec![-dead, alive]);
+        assert_eq!(clauses.len(), all.len() - 2);
+        assert!(!clauses.iter().any(|c| c.literals == vec![alive] || c.literals == vec![-dead]));
+
+        let pattern = TargetPattern::new(target.grid.clone(), vec![true, false]).unwrap();
+        assert!(cg.generate_constraints_with_assumptions(&pattern, &[(0, 1)]).is_err());
+    }
+
+    #[test]
+    fn test_transition_encoding_statistics() {
+        let target_grid = Grid::new(4, 4, BoundaryCondition::Wrap);
+
+        let mut direct = ConstraintGenerator::new(4, 4, 2, BoundaryCondition::Wrap, false);
+        direct.generate_all_constraints(&target_grid).unwrap();
+        let direct_stats = direct.statistics();
+        assert_eq!(direct_stats.auxiliary_variables, 0);
+        assert_eq!(direct_stats.cell_variables, 32);
+
+        for transition in [
+            TransitionEncoding::Totalizer,
+            TransitionEncoding::SequentialCounter,
+            TransitionEncoding::Adder,
+            TransitionEncoding::Minimized,
+        ] {
+            let mut cg = ConstraintGenerator::new(4, 4, 2, BoundaryCondition::Wrap, false)
+                .with_transition_encoding(transition);
+            let clauses = cg.generate_all_constraints(&target_grid).unwrap();
+            let stats = cg.statistics();
+
+            assert_eq!(stats.total_clauses, clauses.len());
+            assert_eq!(stats.cell_variables, 32);
+            assert_eq!(stats.auxiliary_variables > 0, transition.counts_neighbors(), "{:?}", transition);
+            assert!(stats.total_clauses < direct_stats.total_clauses, "{:?}", transition);
+        }
+    }
+
+    #[test]
+    fn test_counting_encoding_rejects_isotropic_rule() {
+        let target_grid = Grid::new(3, 3, BoundaryCondition::Dead);
+        let mut cg = ConstraintGenerator::new(3, 3, 2, BoundaryCondition::Dead, false)
+            .with_rule("B2-a/S12".parse().unwrap())
+            .with_transition_encoding(TransitionEncoding::Totalizer);
+
+        assert!(cg.generate_all_constraints(&target_grid).is_err());
+    }
+}
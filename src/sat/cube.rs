@@ -0,0 +1,429 @@
+//! Cube-and-conquer solving on a pool of CaDiCaL instances
+//!
+//! The formula is split on k cube variables into 2^k cubes, each assuming
+//! one assignment to them. Every cube is solved on a rayon pool by a CaDiCaL
+//! instance holding the whole formula. The cubes cover every assignment
+//! without overlapping, so the formula is satisfiable exactly when some cube
+//! is, and a search for one model stops at the first satisfiable cube.
+//! Enumeration runs in every cube at once and merges the models, dropping
+//! those already found in another cube.
+
+use super::backend::SatBackend;
+use super::cancellation::CancellationToken;
+use super::constraints::Clause;
+use super::solver::{SatSolver, SolverOptions, SolverResultType, SolverSolution, SolverStatistics};
+use crate::config::SolverBackend;
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Backend splitting the formula into cubes solved in parallel
+#[derive(Debug)]
+pub struct CubeSolver {
+    clauses: Vec<Clause>,
+    variable_count: usize,
+    options: SolverOptions,
+    split_hint: Vec<i32>,
+    model: HashMap<i32, bool>,
+    failed: HashSet<i32>,
+    cancellation: CancellationToken,
+    solve_time: Duration,
+    last_result: SolverResultType,
+}
+
+/// What solving one cube gave
+enum CubeOutcome {
+    Model(HashMap<i32, bool>),
+    /// The failed assumptions, cube literals left out
+    Refuted(Vec<i32>),
+    Interrupted,
+}
+
+/// Models found so far by an enumeration, keyed by their projection
+#[derive(Default)]
+struct Enumeration {
+    seen: HashSet<Vec<bool>>,
+    solutions: Vec<SolverSolution>,
+}
+
+impl CubeSolver {
+    /// Create a new cube-and-conquer backend
+    pub fn new() -> Self {
+        Self {
+            clauses: Vec::new(),
+            variable_count: 0,
+            options: SolverOptions::default(),
+            split_hint: Vec::new(),
+            model: HashMap::new(),
+            failed: HashSet::new(),
+            cancellation: CancellationToken::new(),
+            solve_time: Duration::ZERO,
+            last_result: SolverResultType::Error,
+        }
+    }
+
+    /// The variables to split on under `assumptions`
+    ///
+    /// Suggested variables come first, then those in the most clauses.
+    /// Variables fixed by a unit clause or an assumption would only give
+    /// empty cubes, so they are skipped.
+    pub fn cube_variables(&self, assumptions: &[i32]) -> Vec<i32> {
+        let fixed: HashSet<i32> = self.clauses.iter()
+            .filter(|clause| clause.is_unit())
+            .map(|clause| clause.literals[0])
+            .chain(assumptions.iter().copied())
+            .map(i32::abs)
+            .collect();
+        let usable = |var: i32| var <= self.variable_count as i32 && !fixed.contains(&var);
+
+        let mut chosen: Vec<i32> = Vec::new();
+        for var in self.split_hint.iter().map(|var| var.abs()) {
+            if chosen.len() < self.options.cube_variables && usable(var) && !chosen.contains(&var) {
+                chosen.push(var);
+            }
+        }
+
+        if chosen.len() < self.options.cube_variables {
+            let mut occurrences: HashMap<i32, usize> = HashMap::new();
+            for var in self.clauses.iter().flat_map(|clause| &clause.literals).map(|literal| literal.abs()) {
+                *occurrences.entry(var).or_default() += 1;
+            }
+            let mut candidates: Vec<(i32, usize)> = occurrences.into_iter()
+                .filter(|&(var, _)| usable(var) && !chosen.contains(&var))
+                .collect();
+            candidates.sort_by_key(|&(var, count)| (Reverse(count), var));
+            let missing = self.options.cube_variables - chosen.len();
+            chosen.extend(candidates.into_iter().take(missing).map(|(var, _)| var));
+        }
+
+        chosen
+    }
+
+    /// Thread pool sized by `num_threads`, or by the available parallelism
+    fn pool(&self) -> Result<rayon::ThreadPool> {
+        let mut builder = rayon::ThreadPoolBuilder::new();
+        if let Some(threads) = self.options.num_threads {
+            builder = builder.num_threads(threads);
+        }
+        builder.build().context("Failed to start the cube-and-conquer thread pool")
+    }
+
+    /// A CaDiCaL instance holding the whole formula, stopped by `token`
+    fn worker(&self, token: &CancellationToken) -> Result<SatSolver> {
+        let mut solver = SatSolver::new();
+        solver.configure(&self.options)?;
+        solver.set_cancellation_token(token.clone());
+        solver.add_clauses(&self.clauses)?;
+        Ok(solver)
+    }
+
+    /// Solve one cube, cancelling the others through `race` once it has a model
+    fn solve_cube(&self, assumptions: &[i32], cube: &[i32], race: &CancellationToken) -> Result<CubeOutcome> {
+        if race.is_cancelled() {
+            return Ok(CubeOutcome::Interrupted);
+        }
+
+        let mut solver = self.worker(race)?;
+        match solver.solve_with_assumptions(&[assumptions, cube].concat())? {
+            SolverResultType::Satisfiable => {
+                race.cancel();
+                Ok(CubeOutcome::Model(solver.extract_assignment()))
+            }
+            SolverResultType::Unsatisfiable => Ok(CubeOutcome::Refuted(
+                assumptions.iter().copied().filter(|&literal| solver.failed(literal)).collect(),
+            )),
+            _ => Ok(CubeOutcome::Interrupted),
+        }
+    }
+
+    /// Enumerate the models of one cube into `found`, returning whether the cube was interrupted
+    fn enumerate_cube(
+        &self,
+        cube: &[i32],
+        projection: &[i32],
+        max_solutions: usize,
+        found: &Mutex<Enumeration>,
+        race: &CancellationToken,
+        start_time: Instant,
+    ) -> Result<bool> {
+        if race.is_cancelled() {
+            return Ok(true);
+        }
+
+        let mut solver = self.worker(race)?;
+        loop {
+            match solver.solve_with_assumptions(cube)? {
+                SolverResultType::Satisfiable => {
+                    let assignment = solver.extract_assignment();
+                    let key: Vec<bool> = projection.iter()
+                        .map(|var| assignment.get(var).copied().unwrap_or(false))
+                        .collect();
+
+                    let mut found = found.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    if found.solutions.len() < max_solutions && found.seen.insert(key) {
+                        found.solutions.push(SolverSolution { assignment, solve_time: start_time.elapsed() });
+                        if found.solutions.len() == max_solutions {
+                            race.cancel();
+                        }
+                    }
+                    drop(found);
+
+                    // With nothing to project on, every model is the same solution
+                    if projection.is_empty() {
+                        return Ok(false);
+                    }
+                    solver.add_blocking_clause(projection)?;
+                }
+                SolverResultType::Unsatisfiable => return Ok(false),
+                _ => return Ok(true),
+            }
+        }
+    }
+}
+
+impl Default for CubeSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Every assignment to `variables`, as assumption literals
+fn cubes(variables: &[i32]) -> Vec<Vec<i32>> {
+    (0..1usize << variables.len())
+        .map(|bits| {
+            variables.iter()
+                .enumerate()
+                .map(|(i, &var)| if bits & (1 << i) != 0 { var } else { -var })
+                .collect()
+        })
+        .collect()
+}
+
+impl SatBackend for CubeSolver {
+    fn backend(&self) -> SolverBackend {
+        SolverBackend::CubeAndConquer
+    }
+
+    fn configure(&mut self, options: &SolverOptions) -> Result<()> {
+        self.options = options.clone();
+        Ok(())
+    }
+
+    fn add_clause(&mut self, clause: &Clause) -> Result<()> {
+        if clause.is_empty() {
+            anyhow::bail!("Cannot add empty clause (unsatisfiable)");
+        }
+
+        for &literal in &clause.literals {
+            self.variable_count = self.variable_count.max(literal.unsigned_abs() as usize);
+        }
+        self.clauses.push(clause.clone());
+        Ok(())
+    }
+
+    fn solve_with_assumptions(&mut self, assumptions: &[i32]) -> Result<SolverResultType> {
+        self.model.clear();
+        self.failed.clear();
+        let token = self.cancellation.or_timeout(self.options.timeout);
+        if token.is_cancelled() {
+            self.last_result = SolverResultType::Timeout;
+            return Ok(SolverResultType::Timeout);
+        }
+
+        let start_time = Instant::now();
+        let cubes = cubes(&self.cube_variables(assumptions));
+        let race = token.child();
+        let outcomes: Vec<Result<CubeOutcome>> = self.pool()?.install(|| {
+            cubes.par_iter()
+                .map(|cube| self.solve_cube(assumptions, cube, &race))
+                .collect()
+        });
+        self.solve_time += start_time.elapsed();
+
+        let mut model = None;
+        let mut interrupted = false;
+        for outcome in outcomes {
+            match outcome? {
+                CubeOutcome::Model(assignment) => {
+                    if model.is_none() {
+                        model = Some(assignment);
+                    }
+                }
+                CubeOutcome::Refuted(failed) => self.failed.extend(failed),
+                CubeOutcome::Interrupted => interrupted = true,
+            }
+        }
+
+        let result = match model {
+            Some(assignment) => {
+                self.model = assignment;
+                SolverResultType::Satisfiable
+            }
+            None if interrupted => SolverResultType::Timeout,
+            None => SolverResultType::Unsatisfiable,
+        };
+        self.last_result = result.clone();
+        Ok(result)
+    }
+
+    fn model_value(&self, var: i32) -> Option<bool> {
+        self.model.get(&var).copied()
+    }
+
+    fn failed(&self, literal: i32) -> bool {
+        self.failed.contains(&literal)
+    }
+
+    fn suggest_split(&mut self, variables: &[i32]) {
+        self.split_hint = variables.to_vec();
+    }
+
+    fn solve_multiple_projected(&mut self, max_solutions: usize, projection: &[i32]) -> Result<Vec<SolverSolution>> {
+        self.model.clear();
+        let token = self.cancellation.or_timeout(self.options.timeout);
+        if token.is_cancelled() || max_solutions == 0 {
+            self.last_result = SolverResultType::Timeout;
+            return Ok(Vec::new());
+        }
+
+        let start_time = Instant::now();
+        let cubes = cubes(&self.cube_variables(&[]));
+        let race = token.child();
+        let found = Mutex::new(Enumeration::default());
+        let interrupted: Vec<Result<bool>> = self.pool()?.install(|| {
+            cubes.par_iter()
+                .map(|cube| self.enumerate_cube(cube, projection, max_solutions, &found, &race, start_time))
+                .collect()
+        });
+        self.solve_time += start_time.elapsed();
+
+        let mut interrupted_cubes = 0;
+        for cube_interrupted in interrupted {
+            if cube_interrupted? {
+                interrupted_cubes += 1;
+            }
+        }
+
+        let mut solutions = found.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner()).solutions;
+        solutions.sort_by_key(|solution| solution.solve_time);
+        // End as `SatBackend::solve_multiple_projected` does: satisfiable when
+        // the limit or a lone unprojected model stopped the search, and
+        // unsatisfiable once every cube is exhausted
+        self.last_result = if solutions.len() >= max_solutions || (projection.is_empty() && !solutions.is_empty()) {
+            SolverResultType::Satisfiable
+        } else if interrupted_cubes > 0 {
+            SolverResultType::Timeout
+        } else {
+            SolverResultType::Unsatisfiable
+        };
+
+        // Keep the solutions blocked, as enumeration on a single solver would
+        if !projection.is_empty() {
+            for solution in &solutions {
+                self.model = solution.assignment.clone();
+                self.add_blocking_clause(projection)?;
+            }
+        }
+        if let Some(last) = solutions.last() {
+            self.model = last.assignment.clone();
+        }
+
+        Ok(solutions)
+    }
+
+    fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancellation = token;
+    }
+
+    fn statistics(&self) -> SolverStatistics {
+        SolverStatistics {
+            variable_count: self.variable_count,
+            clause_count: self.clauses.len(),
+            solve_time: self.solve_time,
+            result: self.last_result.clone(),
+        }
+    }
+
+    fn variable_count(&self) -> usize {
+        self.variable_count
+    }
+
+    fn clause_count(&self) -> usize {
+        self.clauses.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solver(cube_variables: usize) -> CubeSolver {
+        let mut solver = CubeSolver::new();
+        solver.configure(&SolverOptions { cube_variables, num_threads: Some(4), ..SolverOptions::default() }).unwrap();
+        solver
+    }
+
+    #[test]
+    fn test_single_model_and_refutation() {
+        let mut cube_solver = solver(2);
+        // x1 -> x2 -> x3, and x1 or x4
+        for literals in [vec![-1, 2], vec![-2, 3], vec![1, 4]] {
+            cube_solver.add_clause(&Clause::new(literals)).unwrap();
+        }
+        assert_eq!(cube_solver.cube_variables(&[]).len(), 2);
+
+        let solution = cube_solver.solve().unwrap().unwrap();
+        let value = |var: i32| solution.assignment[&var];
+        assert!(!value(1) || (value(2) && value(3)));
+        assert!(value(1) || value(4));
+
+        // Refuted in every cube: only the assumptions that matter fail
+        assert_eq!(cube_solver.solve_with_assumptions(&[1, -3, 4]).unwrap(), SolverResultType::Unsatisfiable);
+        assert!(cube_solver.failed(1) && cube_solver.failed(-3));
+        assert!(!cube_solver.failed(4));
+        assert_eq!(cube_solver.solve_with_assumptions(&[1]).unwrap(), SolverResultType::Satisfiable);
+        assert_eq!(cube_solver.model_value(3), Some(true));
+    }
+
+    #[test]
+    fn test_enumeration_merges_cubes() {
+        let mut cube_solver = solver(2);
+        cube_solver.add_clause(&Clause::new(vec![1, 2])).unwrap();
+        cube_solver.add_clause(&Clause::new(vec![3, 4])).unwrap();
+        assert_eq!(cube_solver.solve_multiple(100).unwrap().len(), 9);
+
+        // Splitting on x3 and x4 finds each projection onto x1, x2 in several cubes
+        let mut cube_solver = solver(2);
+        cube_solver.add_clause(&Clause::new(vec![1, 2])).unwrap();
+        cube_solver.add_clause(&Clause::new(vec![3, 4])).unwrap();
+        cube_solver.suggest_split(&[3, 4]);
+        assert_eq!(cube_solver.cube_variables(&[]), vec![3, 4]);
+        let solutions = cube_solver.solve_multiple_projected(100, &[1, 2]).unwrap();
+        assert_eq!(solutions.len(), 3);
+        assert_eq!(cube_solver.statistics().result, SolverResultType::Unsatisfiable);
+
+        // Blocked solutions stay blocked, and the limit stops the search early
+        assert!(cube_solver.solve_multiple_projected(100, &[1, 2]).unwrap().is_empty());
+        let mut cube_solver = solver(3);
+        cube_solver.add_clause(&Clause::new(vec![1, 2, 3, 4])).unwrap();
+        assert_eq!(cube_solver.solve_multiple(2).unwrap().len(), 2);
+        assert_eq!(cube_solver.statistics().result, SolverResultType::Satisfiable);
+    }
+
+    #[test]
+    fn test_cancellation() {
+        let mut cube_solver = solver(2);
+        cube_solver.add_clause(&Clause::new(vec![1, 2])).unwrap();
+        cube_solver.terminate();
+        assert_eq!(cube_solver.solve_with_assumptions(&[]).unwrap(), SolverResultType::Timeout);
+        assert!(cube_solver.solve_multiple(10).unwrap().is_empty());
+        assert_eq!(cube_solver.statistics().result, SolverResultType::Timeout);
+    }
+}
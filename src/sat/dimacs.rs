@@ -0,0 +1,277 @@
+//! DIMACS CNF output and parsing of solver models
+//!
+//! A `CellFormula` is the CNF for a reverse problem together with the
+//! variables of its cells. Written out, the cell variables become comment
+//! lines ahead of the header, so a solver run offline sees a plain CNF file,
+//! while its model can still be read back into grids:
+//!
+//! ```text
+//! c grid <width> <height> <time_steps>
+//! c cell <variable> <x> <y> <t>
+//! p cnf <variables> <clauses>
+//! ```
+
+use super::constraints::Clause;
+use super::solver::SolverResultType;
+use crate::config::BoundaryCondition;
+use crate::game_of_life::Grid;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Render clauses over variables `1..=variable_count` in DIMACS CNF format
+pub fn formula_to_dimacs(clauses: &[Clause], variable_count: usize) -> String {
+    let mut dimacs = String::new();
+    let _ = writeln!(dimacs, "p cnf {} {}", variable_count, clauses.len());
+    for clause in clauses {
+        for literal in &clause.literals {
+            let _ = write!(dimacs, "{} ", literal);
+        }
+        dimacs.push_str("0\n");
+    }
+    dimacs
+}
+
+/// Parse a DIMACS CNF formula into its clauses and declared variable count
+///
+/// Comment lines are skipped and clauses may span several lines.
+pub fn parse_dimacs(text: &str) -> Result<(Vec<Clause>, usize)> {
+    let mut header = None;
+    let mut clauses = Vec::new();
+    let mut literals = Vec::new();
+
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('c') {
+            continue;
+        }
+
+        if line.starts_with('p') {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 4 || fields[1] != "cnf" {
+                anyhow::bail!("Invalid DIMACS header on line {}: {}", line_number + 1, line);
+            }
+            let variables: usize = fields[2].parse()
+                .with_context(|| format!("Invalid variable count on line {}", line_number + 1))?;
+            let clause_count: usize = fields[3].parse()
+                .with_context(|| format!("Invalid clause count on line {}", line_number + 1))?;
+            header = Some((variables, clause_count));
+            continue;
+        }
+
+        let Some((variables, _)) = header else {
+            anyhow::bail!("Clause before the DIMACS header on line {}", line_number + 1);
+        };
+
+        for token in line.split_whitespace() {
+            let literal: i32 = token.parse()
+                .with_context(|| format!("Invalid literal '{}' on line {}", token, line_number + 1))?;
+            if literal == 0 {
+                clauses.push(Clause::new(std::mem::take(&mut literals)));
+            } else if literal.unsigned_abs() as usize > variables {
+                anyhow::bail!("Literal {} on line {} exceeds the declared {} variables",
+                             literal, line_number + 1, variables);
+            } else {
+                literals.push(literal);
+            }
+        }
+    }
+
+    let (variables, clause_count) = header.context("Missing DIMACS header")?;
+    if !literals.is_empty() {
+        anyhow::bail!("Last clause is not terminated by 0");
+    }
+    if clauses.len() != clause_count {
+        anyhow::bail!("DIMACS header declares {} clauses, but {} were found", clause_count, clauses.len());
+    }
+
+    Ok((clauses, variables))
+}
+
+/// A model written by a SAT solver in the competition output format
+#[derive(Debug, Clone)]
+pub struct DimacsModel {
+    /// The `s` line: satisfiable, unsatisfiable or unknown (reported as a timeout)
+    pub status: SolverResultType,
+    /// Variable values from the `v` lines
+    pub assignment: HashMap<i32, bool>,
+}
+
+/// Parse solver output with an `s` status line and `v` value lines
+///
+/// Other lines, such as comments and statistics, are ignored. A missing
+/// status line is an error, since it cannot be told apart from a crash.
+pub fn parse_model(text: &str) -> Result<DimacsModel> {
+    let mut status = None;
+    let mut assignment = HashMap::new();
+
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if let Some(result) = line.strip_prefix("s ") {
+            status = Some(match result.trim() {
+                "SATISFIABLE" => SolverResultType::Satisfiable,
+                "UNSATISFIABLE" => SolverResultType::Unsatisfiable,
+                "UNKNOWN" => SolverResultType::Timeout,
+                other => anyhow::bail!("Unknown solver status '{}' on line {}", other, line_number + 1),
+            });
+        } else if let Some(values) = line.strip_prefix("v ") {
+            for token in values.split_whitespace() {
+                let literal: i32 = token.parse()
+                    .with_context(|| format!("Invalid literal '{}' on line {}", token, line_number + 1))?;
+                if literal != 0 {
+                    assignment.insert(literal.abs(), literal > 0);
+                }
+            }
+        }
+    }
+
+    let status = status.context("Solver output has no 's' status line")?;
+    if status == SolverResultType::Satisfiable && assignment.is_empty() {
+        anyhow::bail!("Solver reported SATISFIABLE but gave no 'v' lines");
+    }
+
+    Ok(DimacsModel { status, assignment })
+}
+
+/// A reverse problem's CNF with the variable of every cell
+#[derive(Debug, Clone)]
+pub struct CellFormula {
+    /// Grid width
+    pub width: usize,
+    /// Grid height
+    pub height: usize,
+    /// Number of generations encoded, the predecessor included
+    pub time_steps: usize,
+    /// Number of variables in the formula, auxiliary ones included
+    pub variable_count: usize,
+    /// The clauses
+    pub clauses: Vec<Clause>,
+    /// Variable of the cell `(x, y, t)`
+    pub cells: HashMap<(usize, usize, usize), i32>,
+}
+
+impl CellFormula {
+    /// Render the formula in DIMACS CNF format, cell variables first as comments
+    pub fn to_dimacs(&self) -> String {
+        let mut dimacs = String::new();
+        let _ = writeln!(dimacs, "c grid {} {} {}", self.width, self.height, self.time_steps);
+
+        let mut cells: Vec<(&(usize, usize, usize), &i32)> = self.cells.iter().collect();
+        cells.sort_by_key(|&(_, id)| *id);
+        for ((x, y, t), id) in cells {
+            let _ = writeln!(dimacs, "c cell {} {} {} {}", id, x, y, t);
+        }
+
+        dimacs.push_str(&formula_to_dimacs(&self.clauses, self.variable_count));
+        dimacs
+    }
+
+    /// Parse a formula written by `to_dimacs`
+    pub fn from_dimacs(text: &str) -> Result<Self> {
+        let (clauses, variable_count) = parse_dimacs(text)?;
+        let mut grid = None;
+        let mut cells = HashMap::new();
+
+        for (line_number, line) in text.lines().enumerate() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let numbers = || -> Result<Vec<usize>> {
+                fields[2..].iter()
+                    .map(|field| field.parse::<usize>())
+                    .collect::<Result<_, _>>()
+                    .with_context(|| format!("Invalid '{}' comment on line {}", fields[1], line_number + 1))
+            };
+
+            match fields.as_slice() {
+                ["c", "grid", _, _, _] => {
+                    let numbers = numbers()?;
+                    grid = Some((numbers[0], numbers[1], numbers[2]));
+                }
+                ["c", "cell", _, _, _, _] => {
+                    let numbers = numbers()?;
+                    let id = i32::try_from(numbers[0])
+                        .with_context(|| format!("Invalid cell variable on line {}", line_number + 1))?;
+                    cells.insert((numbers[1], numbers[2], numbers[3]), id);
+                }
+                _ => {}
+            }
+        }
+
+        let (width, height, time_steps) = grid.context("Missing 'c grid' comment: the CNF was not exported by this tool")?;
+        for (&(x, y, t), &id) in &cells {
+            if x >= width || y >= height || t >= time_steps || id < 1 || id as usize > variable_count {
+                anyhow::bail!("Cell comment for ({}, {}, {}) with variable {} is out of range", x, y, t, id);
+            }
+        }
+
+        Ok(Self { width, height, time_steps, variable_count, clauses, cells })
+    }
+
+    /// Read the grid at generation `t` out of a solver's model
+    ///
+    /// Every cell of that generation must have a variable, and the model
+    /// must assign it.
+    pub fn grid_from_model(&self, assignment: &HashMap<i32, bool>, t: usize, boundary_condition: BoundaryCondition) -> Result<Grid> {
+        if t >= self.time_steps {
+            anyhow::bail!("Generation {} is not in the formula, which has {} time steps", t, self.time_steps);
+        }
+
+        let mut grid = Grid::new(self.width, self.height, boundary_condition);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let id = self.cells.get(&(x, y, t))
+                    .with_context(|| format!("The formula has no variable for cell ({}, {}, {})", x, y, t))?;
+                let alive = assignment.get(id)
+                    .with_context(|| format!("The model does not assign variable {} of cell ({}, {}, {})", id, x, y, t))?;
+                grid.set(y, x, *alive)?;
+            }
+        }
+
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formula_to_dimacs() {
+        let clauses = vec![Clause::new(vec![1, -2]), Clause::unit(3)];
+        assert_eq!(formula_to_dimacs(&clauses, 3), "p cnf 3 2\n1 -2 0\n3 0\n");
+
+        let (parsed, variables) = parse_dimacs("c comment\np cnf 3 2\n1 -2\n0 3 0\n").unwrap();
+        assert_eq!(variables, 3);
+        assert_eq!(parsed, clauses);
+        assert!(parse_dimacs("p cnf 2 1\n1 -3 0\n").is_err());
+        assert!(parse_dimacs("p cnf 2 2\n1 -2 0\n").is_err());
+    }
+
+    #[test]
+    fn test_cell_formula_round_trip() {
+        let formula = CellFormula {
+            width: 2,
+            height: 1,
+            time_steps: 1,
+            variable_count: 3,
+            clauses: vec![Clause::new(vec![1, 3]), Clause::binary(-2, -3)],
+            cells: HashMap::from([((0, 0, 0), 1), ((1, 0, 0), 2)]),
+        };
+        let dimacs = formula.to_dimacs();
+        assert!(dimacs.starts_with("c grid 2 1 1\nc cell 1 0 0 0\nc cell 2 1 0 0\np cnf 3 2\n"));
+
+        let parsed = CellFormula::from_dimacs(&dimacs).unwrap();
+        assert_eq!(parsed.cells, formula.cells);
+        assert_eq!(parsed.clauses, formula.clauses);
+
+        let model = parse_model("c solver output\ns SATISFIABLE\nv 1 -2\nv 3 0\n").unwrap();
+        assert_eq!(model.status, SolverResultType::Satisfiable);
+        let grid = parsed.grid_from_model(&model.assignment, 0, BoundaryCondition::Dead).unwrap();
+        assert_eq!(grid.cells, vec![true, false]);
+
+        let partial = parse_model("s SATISFIABLE\nv 1 0\n").unwrap();
+        assert!(parsed.grid_from_model(&partial.assignment, 0, BoundaryCondition::Dead).is_err());
+        assert_eq!(parse_model("s UNSATISFIABLE\n").unwrap().status, SolverResultType::Unsatisfiable);
+        assert!(parse_model("v 1 0\n").is_err());
+        assert!(CellFormula::from_dimacs("p cnf 1 0\n").is_err());
+    }
+}
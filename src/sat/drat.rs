@@ -0,0 +1,418 @@
+//! DRAT proofs of unsatisfiability and a forward proof checker
+//!
+//! A proof is a sequence of clause additions and deletions ending in the
+//! empty clause. Every added clause must be a reverse unit propagation (RUP)
+//! consequence of the clauses present at that point, or a resolution
+//! asymmetric tautology (RAT) on its first literal. Proofs read and write the
+//! textual DRAT format understood by external checkers such as drat-trim.
+
+use super::constraints::Clause;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fmt;
+
+/// One step of a DRAT proof
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofStep {
+    /// Add a lemma
+    Add(Vec<i32>),
+    /// Delete a clause
+    Delete(Vec<i32>),
+}
+
+/// A DRAT proof
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DratProof {
+    pub steps: Vec<ProofStep>,
+}
+
+impl DratProof {
+    /// Create an empty proof
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a lemma
+    pub fn add(&mut self, lemma: Vec<i32>) {
+        self.steps.push(ProofStep::Add(lemma));
+    }
+
+    /// Append a deletion
+    pub fn delete(&mut self, clause: Vec<i32>) {
+        self.steps.push(ProofStep::Delete(clause));
+    }
+
+    /// Number of added lemmas
+    pub fn lemma_count(&self) -> usize {
+        self.steps.iter().filter(|step| matches!(step, ProofStep::Add(_))).count()
+    }
+
+    /// Check whether the proof states the empty clause
+    pub fn derives_empty_clause(&self) -> bool {
+        self.steps.iter().any(|step| matches!(step, ProofStep::Add(lemma) if lemma.is_empty()))
+    }
+
+    /// Parse a proof in textual DRAT format
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut proof = Self::new();
+        let mut literals = Vec::new();
+        let mut deletion = false;
+
+        for (line_number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('c') {
+                continue;
+            }
+
+            for token in line.split_whitespace() {
+                if token == "d" && literals.is_empty() && !deletion {
+                    deletion = true;
+                    continue;
+                }
+                let literal: i32 = token.parse()
+                    .with_context(|| format!("Invalid literal '{}' on proof line {}", token, line_number + 1))?;
+                if literal != 0 {
+                    literals.push(literal);
+                    continue;
+                }
+
+                let clause = std::mem::take(&mut literals);
+                if std::mem::take(&mut deletion) {
+                    proof.delete(clause);
+                } else {
+                    proof.add(clause);
+                }
+            }
+        }
+
+        if !literals.is_empty() || deletion {
+            anyhow::bail!("Proof ends in the middle of a clause");
+        }
+        Ok(proof)
+    }
+}
+
+impl fmt::Display for DratProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for step in &self.steps {
+            let (prefix, literals) = match step {
+                ProofStep::Add(literals) => ("", literals),
+                ProofStep::Delete(literals) => ("d ", literals),
+            };
+            write!(f, "{}", prefix)?;
+            for literal in literals {
+                write!(f, "{} ", literal)?;
+            }
+            writeln!(f, "0")?;
+        }
+        Ok(())
+    }
+}
+
+/// Summary of a successfully checked proof
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofSummary {
+    pub formula_clauses: usize,
+    pub lemmas: usize,
+    pub rat_lemmas: usize,
+    pub deletions: usize,
+}
+
+impl fmt::Display for ProofSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} lemmas checked against {} clauses ({} by RAT, {} deletions)",
+               self.lemmas, self.formula_clauses, self.rat_lemmas, self.deletions)
+    }
+}
+
+/// Check that `proof` refutes `formula`
+pub fn verify_proof(formula: &[Clause], proof: &DratProof) -> Result<ProofSummary> {
+    let mut checker = ProofChecker::new();
+    for clause in formula {
+        checker.insert(&clause.literals);
+    }
+
+    let mut summary = ProofSummary {
+        formula_clauses: formula.len(),
+        lemmas: 0,
+        rat_lemmas: 0,
+        deletions: 0,
+    };
+
+    if checker.has_empty_clause {
+        return Ok(summary);
+    }
+
+    for (index, step) in proof.steps.iter().enumerate() {
+        match step {
+            ProofStep::Add(lemma) => {
+                summary.lemmas += 1;
+                if !checker.is_rup(lemma) {
+                    if checker.is_rat(lemma) {
+                        summary.rat_lemmas += 1;
+                    } else {
+                        anyhow::bail!("Proof step {} adds {:?}, which is neither RUP nor RAT", index + 1, lemma);
+                    }
+                }
+                checker.insert(lemma);
+                if checker.has_empty_clause {
+                    return Ok(summary);
+                }
+            }
+            ProofStep::Delete(clause) => {
+                summary.deletions += 1;
+                checker.remove(clause);
+            }
+        }
+    }
+
+    anyhow::bail!("Proof does not derive the empty clause")
+}
+
+/// Clause database with two watched literals per clause
+struct ProofChecker {
+    clauses: Vec<Vec<i32>>,
+    active: Vec<bool>,
+    /// Clause indices by their sorted literals, for deletions
+    index: HashMap<Vec<i32>, Vec<usize>>,
+    /// Clauses watching each literal, by `literal_index`
+    watches: Vec<Vec<usize>>,
+    units: Vec<usize>,
+    assignment: Vec<i8>,
+    trail: Vec<i32>,
+    has_empty_clause: bool,
+}
+
+impl ProofChecker {
+    fn new() -> Self {
+        Self {
+            clauses: Vec::new(),
+            active: Vec::new(),
+            index: HashMap::new(),
+            watches: Vec::new(),
+            units: Vec::new(),
+            assignment: Vec::new(),
+            trail: Vec::new(),
+            has_empty_clause: false,
+        }
+    }
+
+    fn normalize(literals: &[i32]) -> Option<Vec<i32>> {
+        let mut clause = literals.to_vec();
+        clause.sort_unstable();
+        clause.dedup();
+        // Tautologies are always satisfied and can be ignored
+        if clause.iter().any(|&literal| clause.binary_search(&-literal).is_ok()) {
+            return None;
+        }
+        Some(clause)
+    }
+
+    fn grow(&mut self, literals: &[i32]) {
+        let variables = literals.iter().map(|literal| literal.unsigned_abs() as usize).max().unwrap_or(0);
+        if variables >= self.assignment.len() {
+            self.assignment.resize(variables + 1, 0);
+            self.watches.resize(2 * (variables + 1), Vec::new());
+        }
+    }
+
+    fn insert(&mut self, literals: &[i32]) {
+        let Some(clause) = Self::normalize(literals) else { return };
+        self.grow(&clause);
+
+        let id = self.clauses.len();
+        match clause.len() {
+            0 => self.has_empty_clause = true,
+            1 => self.units.push(id),
+            _ => {
+                self.watches[literal_index(clause[0])].push(id);
+                self.watches[literal_index(clause[1])].push(id);
+            }
+        }
+        self.index.entry(clause.clone()).or_default().push(id);
+        self.clauses.push(clause);
+        self.active.push(true);
+    }
+
+    fn remove(&mut self, literals: &[i32]) {
+        let Some(clause) = Self::normalize(literals) else { return };
+        if let Some(id) = self.index.get_mut(&clause).and_then(|ids| ids.pop()) {
+            self.active[id] = false;
+        }
+    }
+
+    fn value(&self, literal: i32) -> i8 {
+        let value = self.assignment.get(literal.unsigned_abs() as usize).copied().unwrap_or(0);
+        if literal > 0 { value } else { -value }
+    }
+
+    /// Make `literal` true; returns false if it is already false
+    fn assign(&mut self, literal: i32) -> bool {
+        match self.value(literal) {
+            1 => true,
+            -1 => false,
+            _ => {
+                self.assignment[literal.unsigned_abs() as usize] = if literal > 0 { 1 } else { -1 };
+                self.trail.push(literal);
+                true
+            }
+        }
+    }
+
+    /// Unit propagation from the trail; returns true on conflict
+    fn propagate(&mut self) -> bool {
+        let mut head = 0;
+        while head < self.trail.len() {
+            let falsified = -self.trail[head];
+            head += 1;
+
+            let watching = std::mem::take(&mut self.watches[literal_index(falsified)]);
+            let mut kept = Vec::with_capacity(watching.len());
+            let mut conflict = false;
+
+            for (position, &id) in watching.iter().enumerate() {
+                if conflict {
+                    kept.extend_from_slice(&watching[position..]);
+                    break;
+                }
+                if !self.active[id] {
+                    continue;
+                }
+
+                let clause = &mut self.clauses[id];
+                if clause[0] == falsified {
+                    clause.swap(0, 1);
+                }
+                let other = clause[0];
+                if self.value(other) == 1 {
+                    kept.push(id);
+                    continue;
+                }
+
+                let clause = &self.clauses[id];
+                let replacement = (2..clause.len()).find(|&i| self.value(clause[i]) != -1);
+                if let Some(i) = replacement {
+                    let clause = &mut self.clauses[id];
+                    clause.swap(1, i);
+                    let watched = clause[1];
+                    self.watches[literal_index(watched)].push(id);
+                    continue;
+                }
+
+                kept.push(id);
+                if !self.assign(other) {
+                    conflict = true;
+                }
+            }
+
+            self.watches[literal_index(falsified)].extend(kept);
+            if conflict {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn undo(&mut self) {
+        for literal in self.trail.drain(..) {
+            self.assignment[literal.unsigned_abs() as usize] = 0;
+        }
+    }
+
+    /// Check whether falsifying `literals` leads to a conflict by unit propagation
+    fn is_rup(&mut self, literals: &[i32]) -> bool {
+        self.grow(literals);
+
+        let units: Vec<i32> = self.units.iter()
+            .filter(|&&id| self.active[id])
+            .map(|&id| self.clauses[id][0])
+            .collect();
+        let conflict = literals.iter().any(|&literal| !self.assign(-literal))
+            || units.into_iter().any(|unit| !self.assign(unit))
+            || self.propagate();
+
+        self.undo();
+        conflict
+    }
+
+    /// Check the RAT property on the first literal of `literals`
+    fn is_rat(&mut self, literals: &[i32]) -> bool {
+        let Some(&pivot) = literals.first() else { return false };
+
+        let candidates: Vec<Vec<i32>> = self.clauses.iter()
+            .zip(&self.active)
+            .filter(|(clause, &active)| active && clause.contains(&-pivot))
+            .map(|(clause, _)| clause.clone())
+            .collect();
+
+        candidates.iter().all(|clause| {
+            let resolvent: Vec<i32> = literals.iter()
+                .copied()
+                .chain(clause.iter().copied().filter(|&literal| literal != -pivot))
+                .collect();
+            Self::normalize(&resolvent).is_none_or(|resolvent| self.is_rup(&resolvent))
+        })
+    }
+}
+
+fn literal_index(literal: i32) -> usize {
+    2 * literal.unsigned_abs() as usize + usize::from(literal < 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formula(clauses: &[&[i32]]) -> Vec<Clause> {
+        clauses.iter().map(|literals| Clause::new(literals.to_vec())).collect()
+    }
+
+    /// All four clauses over two variables
+    fn four_clauses() -> Vec<Clause> {
+        formula(&[&[1, 2], &[1, -2], &[-1, 2], &[-1, -2]])
+    }
+
+    #[test]
+    fn test_accepts_rup_proof() {
+        let proof = DratProof::parse("1 0\nd 1 2 0\n0\n").unwrap();
+        let summary = verify_proof(&four_clauses(), &proof).unwrap();
+        assert_eq!(summary.lemmas, 2);
+        assert_eq!(summary.deletions, 1);
+        assert_eq!(summary.rat_lemmas, 0);
+    }
+
+    #[test]
+    fn test_rejects_invalid_proofs() {
+        // -1 does not follow from the first two clauses alone
+        let weak = formula(&[&[1, 2], &[1, -2]]);
+        assert!(verify_proof(&weak, &DratProof::parse("-1 0\n0\n").unwrap()).is_err());
+
+        // A proof must end in the empty clause
+        assert!(verify_proof(&four_clauses(), &DratProof::parse("1 0\n").unwrap()).is_err());
+
+        // The empty clause is not RUP while the formula is satisfiable
+        assert!(verify_proof(&weak, &DratProof::parse("0\n").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_accepts_rat_lemma() {
+        // (3) introduces a fresh variable: not RUP, but trivially RAT
+        let proof = DratProof::parse("3 0\n1 0\n0\n").unwrap();
+        let summary = verify_proof(&four_clauses(), &proof).unwrap();
+        assert_eq!(summary.lemmas, 3);
+        assert_eq!(summary.rat_lemmas, 1);
+    }
+
+    #[test]
+    fn test_round_trip_format() {
+        let mut proof = DratProof::new();
+        proof.add(vec![1, -2]);
+        proof.delete(vec![3]);
+        proof.add(vec![]);
+        let text = proof.to_string();
+        assert_eq!(text, "1 -2 0\nd 3 0\n0\n");
+        assert_eq!(DratProof::parse(&text).unwrap(), proof);
+        assert!(proof.derives_empty_clause());
+        assert_eq!(proof.lemma_count(), 2);
+    }
+}
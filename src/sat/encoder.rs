@@ -1,71 +1,166 @@
 //! SAT encoder for the reverse Game of Life problem
 
-use super::{ConstraintGenerator, SatSolver, SolverOptions, SolverSolution};
-use crate::config::Settings;
-use crate::game_of_life::{Grid, GameOfLifeRules};
+use super::{create_solver, BruteForceSearch, CancellationToken, CellFormula, CounterStatistics, MemberStatistics, Portfolio, ConstraintGenerator, DratProof, ModelCounter, SatBackend, Signal, SolverOptions, SolverResultType, SolverSolution};
+use super::constraints::Clause;
+use crate::config::{Objective, Settings, SolverBackend, TransitionEncoding};
+use crate::game_of_life::{Grid, GameOfLifeRules, TargetPattern};
 use anyhow::{Context, Result};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// Main SAT encoder for reverse Game of Life problems
 pub struct SatEncoder {
     settings: Settings,
     constraint_generator: ConstraintGenerator,
-    solver: SatSolver,
+    solver: Box<dyn SatBackend>,
+    cancellation: CancellationToken,
     grid_width: usize,
     grid_height: usize,
+    dead_cells: Vec<(usize, usize, usize)>,
+    population_optimum: Option<PopulationOptimum>,
+    /// Whether the last brute-force or portfolio search, which bypass `solver`, was interrupted
+    search_interrupted: bool,
+    portfolio_statistics: Vec<MemberStatistics>,
 }
 
 impl SatEncoder {
     /// Create a new SAT encoder with the given settings and target grid
-    pub fn new(settings: Settings, target_grid: &Grid) -> Self {
-        let constraint_generator = ConstraintGenerator::new(
-            target_grid.width,
-            target_grid.height,
-            settings.simulation.generations + 1, // +1 because we need initial state + generations
-            settings.simulation.boundary_condition.clone(),
-            settings.encoding.symmetry_breaking,
-        );
+    pub fn new(settings: Settings, target_grid: &Grid) -> Result<Self> {
+        let constraint_generator = Self::create_constraint_generator(&settings, target_grid.width, target_grid.height);
 
-        let mut solver = SatSolver::new();
-        
-        // Configure solver based on settings
-        let solver_options = SolverOptions {
-            num_threads: settings.solver.num_threads,
-            enable_preprocessing: settings.solver.enable_preprocessing,
-            verbosity: settings.solver.verbosity,
-            timeout: Some(Duration::from_secs(settings.solver.timeout_seconds)),
-            random_seed: None,
-        };
-        solver.configure(&solver_options);
+        let solver = Self::create_solver(&settings)?;
 
-        Self {
+        Ok(Self {
             settings,
             constraint_generator,
             solver,
+            cancellation: CancellationToken::new(),
             grid_width: target_grid.width,
             grid_height: target_grid.height,
+            dead_cells: Vec::new(),
+            population_optimum: None,
+            search_interrupted: false,
+            portfolio_statistics: Vec::new(),
+        })
+    }
+
+    /// Require the cells `(x, y, t)` to be dead in every solution
+    pub fn with_dead_cells(mut self, dead_cells: Vec<(usize, usize, usize)>) -> Self {
+        self.dead_cells = dead_cells;
+        self.constraint_generator =
+            Self::create_constraint_generator(&self.settings, self.grid_width, self.grid_height)
+                .with_dead_cells(self.dead_cells.clone());
+        self
+    }
+
+    /// Check `token` during every solve instead of a token of the encoder's own
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Create a constraint generator for the rule and encoding selected in the settings
+    fn create_constraint_generator(settings: &Settings, width: usize, height: usize) -> ConstraintGenerator {
+        let generator = ConstraintGenerator::new(
+            width,
+            height,
+            settings.simulation.generations + 1, // +1 because we need initial state + generations
+            settings.simulation.boundary_condition.clone(),
+            settings.encoding.symmetry_breaking,
+        )
+        .with_rule(settings.simulation.rule.clone())
+        .with_transition_encoding(settings.encoding.transition)
+        .with_symmetry_generation(settings.solver.projection_generation)
+        .with_population_bounds(settings.encoding.min_population, settings.encoding.max_population);
+
+        if settings.solver.optimize == Objective::None {
+            generator
+        } else {
+            generator.with_population_counter()
         }
     }
 
+    /// Create and configure the solver backend selected in the settings
+    fn create_solver(settings: &Settings) -> Result<Box<dyn SatBackend>> {
+        let mut solver = create_solver(&settings.solver.backend)
+            .with_context(|| format!("Failed to create {:?} solver", settings.solver.backend))?;
+
+        let solver_options = SolverOptions::from_config(&settings.solver);
+        solver.configure(&solver_options)
+            .context("Failed to configure SAT solver")?;
+
+        Ok(solver)
+    }
+
+    /// Get a handle that stops `solve` when cancelled, even from another thread
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    /// Check whether the last solve stopped early on timeout or cancellation
+    pub fn was_interrupted(&self) -> bool {
+        self.search_interrupted || self.solver.statistics().result == SolverResultType::Timeout
+    }
+
     /// Encode and solve the reverse Game of Life problem
+    ///
+    /// `solver.timeout_seconds` bounds the whole call, encoding included. When
+    /// it runs out, the solutions found so far are returned.
     pub fn solve(&mut self, target_grid: &Grid) -> Result<Vec<Grid>> {
+        self.solve_pattern(&TargetPattern::from_grid(target_grid.clone()))
+    }
+
+    /// Encode and solve for predecessors that match `target` on its specified cells
+    pub fn solve_pattern(&mut self, target: &TargetPattern) -> Result<Vec<Grid>> {
+        let timeout = Duration::from_secs(self.settings.solver.timeout_seconds);
+        self.solver.set_cancellation_token(self.cancellation.with_timeout(timeout));
+
+        match self.settings.solver.backend {
+            SolverBackend::BruteForce => return self.solve_brute_force(target, timeout),
+            SolverBackend::Portfolio => return self.solve_portfolio(target, timeout),
+            _ => {}
+        }
+
         // Generate all SAT constraints
         let clauses = self.constraint_generator
-            .generate_all_constraints(target_grid)
+            .generate_all_constraints_for_pattern(target)
             .context("Failed to generate SAT constraints")?;
 
-        println!("Generated {} clauses with {} variables", 
-                clauses.len(), 
-                self.constraint_generator.variable_manager().variable_count());
+        let constraint_stats = self.constraint_generator.statistics();
+        println!("Generated {} clauses with {} variables ({} auxiliary, {:?} transitions)",
+                clauses.len(),
+                constraint_stats.total_variables,
+                constraint_stats.auxiliary_variables,
+                constraint_stats.transition);
 
         // Add constraints to solver
         self.solver.add_clauses(&clauses)
             .context("Failed to add clauses to SAT solver")?;
+        let split = self.central_cells(self.settings.solver.cubes.generation)?;
+        self.solver.suggest_split(&split);
+
+        // Solutions only count as distinct when they differ on the projected generation
+        let projection_generation = self.settings.solver.projection_generation;
+        if projection_generation > self.settings.simulation.generations {
+            anyhow::bail!(
+                "Projection generation {} is beyond the last generation {}",
+                projection_generation,
+                self.settings.simulation.generations
+            );
+        }
+        let projection = self.constraint_generator
+            .variable_manager()
+            .all_cell_variables_at_time(projection_generation)?;
 
         // Solve for multiple solutions
-        let solutions = self.solver.solve_multiple(self.settings.solver.max_solutions)
-            .context("SAT solving failed")?;
+        let solutions = match self.settings.solver.optimize {
+            Objective::None => self.solver.solve_multiple_projected(self.settings.solver.max_solutions, &projection),
+            objective => self.solve_optimal(objective, &projection),
+        }
+        .context("SAT solving failed")?;
 
+        if self.was_interrupted() {
+            println!("Solver stopped early (timeout or cancellation), keeping solutions found so far");
+        }
         println!("Found {} solutions", solutions.len());
 
         // Convert SAT solutions to Game of Life grids
@@ -74,7 +169,7 @@ impl SatEncoder {
             match self.extract_grid_from_solution(solution, 0) {
                 Ok(grid) => {
                     // Validate the solution
-                    if self.validate_solution(&grid, target_grid)? {
+                    if self.validate_solution(&grid, target)? {
                         result_grids.push(grid);
                     } else {
                         eprintln!("Warning: Solution {} failed validation", i);
@@ -89,6 +184,293 @@ impl SatEncoder {
         Ok(result_grids)
     }
 
+    /// Variables of the cells of generation `t`, nearest to the grid's center first
+    ///
+    /// The center of a pattern is usually its busiest part, so fixing those
+    /// cells splits the search into cubes of similar difficulty.
+    fn central_cells(&mut self, t: usize) -> Result<Vec<i32>> {
+        let (width, height) = (self.grid_width as i64, self.grid_height as i64);
+        let mut cells: Vec<(usize, usize)> = (0..self.grid_height)
+            .flat_map(|y| (0..self.grid_width).map(move |x| (x, y)))
+            .collect();
+        // Twice the offset from the center keeps the distance an integer
+        cells.sort_by_key(|&(x, y)| (2 * x as i64 - (width - 1)).pow(2) + (2 * y as i64 - (height - 1)).pow(2));
+
+        let variable_manager = self.constraint_generator.variable_manager();
+        cells.into_iter()
+            .map(|(x, y)| variable_manager.cell_variable(x, y, t))
+            .collect()
+    }
+
+    /// Find predecessors by simulating every candidate grid instead of solving the CNF
+    ///
+    /// Symmetry breaking does not apply, so every predecessor is a candidate
+    /// solution. Population bounds and objectives are applied to the list.
+    fn solve_brute_force(&mut self, target: &TargetPattern, timeout: Duration) -> Result<Vec<Grid>> {
+        if self.settings.solver.projection_generation != 0 {
+            anyhow::bail!("The brute-force backend only enumerates distinct predecessors (projection generation 0)");
+        }
+
+        let search = BruteForceSearch::new(&self.settings, self.grid_width, self.grid_height)
+            .with_dead_cells(self.dead_cells.clone())
+            .with_cancellation(self.cancellation.with_timeout(timeout));
+        let result = search.predecessors(target)
+            .context("Brute-force search failed")?;
+        self.search_interrupted = result.interrupted;
+
+        let mut predecessors = result.predecessors;
+        self.population_optimum = None;
+        let best = match self.settings.solver.optimize {
+            Objective::None => None,
+            Objective::MinPopulation => predecessors.iter().map(Grid::living_count).min(),
+            Objective::MaxPopulation => predecessors.iter().map(Grid::living_count).max(),
+        };
+        if let Some(population) = best {
+            predecessors.retain(|grid| grid.living_count() == population);
+            self.population_optimum = Some(PopulationOptimum { population, proven: !result.interrupted });
+        }
+
+        if self.was_interrupted() {
+            println!("Brute force stopped early (timeout or cancellation), keeping predecessors found so far");
+        }
+        predecessors.truncate(self.settings.solver.max_solutions);
+        println!("Found {} solutions by brute force", predecessors.len());
+
+        Ok(predecessors)
+    }
+
+    /// Race the configurations of the portfolio and keep the first answer
+    fn solve_portfolio(&mut self, target: &TargetPattern, timeout: Duration) -> Result<Vec<Grid>> {
+        let portfolio = Portfolio::new(&self.settings)
+            .with_dead_cells(self.dead_cells.clone())
+            .with_cancellation(self.cancellation.with_timeout(timeout));
+        println!("Racing {} portfolio members", portfolio.members().len());
+
+        let result = portfolio.solve(target)
+            .context("Portfolio search failed")?;
+        for member in &result.members {
+            println!("  {}", member);
+        }
+        self.search_interrupted = result.interrupted;
+        self.population_optimum = result.population_optimum;
+        self.portfolio_statistics = result.members;
+
+        if self.was_interrupted() {
+            println!("No portfolio member finished (timeout or cancellation), keeping solutions found so far");
+        }
+        Ok(result.predecessors)
+    }
+
+    /// Find predecessors with the best population under `objective`
+    ///
+    /// After a first solution, a population counter is added and the bound
+    /// is tightened past each solution's population until the solver reports
+    /// none is left, which proves the last one optimal. Further solutions up
+    /// to `max_solutions` then share its population. If the search is
+    /// interrupted, the best solution found so far is returned unproven.
+    fn solve_optimal(&mut self, objective: Objective, projection: &[i32]) -> Result<Vec<SolverSolution>> {
+        let start_time = Instant::now();
+        self.population_optimum = None;
+
+        let mut best = match self.solver.solve_with_assumptions(&[])? {
+            SolverResultType::Satisfiable => self.current_solution(start_time),
+            SolverResultType::Unsatisfiable | SolverResultType::Timeout => return Ok(Vec::new()),
+            result => anyhow::bail!("Solver returned no answer ({:?})", result),
+        };
+        let mut population = self.population(&best)?;
+
+        // Smaller populations only need counting up to one past the first
+        let limit = match objective {
+            Objective::MaxPopulation => self.grid_width * self.grid_height,
+            _ => population + 1,
+        };
+        let (clauses, count) = self.constraint_generator.generate_population_counter(limit)?;
+        self.solver.add_clauses(&clauses)?;
+        let improvement = |population: usize| {
+            let signal = match objective {
+                Objective::MaxPopulation => count.at_least(population + 1),
+                _ => count.at_least(population).map(Signal::negate),
+            };
+            signal.expect("totalizer counts are unary")
+        };
+
+        let proven = loop {
+            let bound = match improvement(population) {
+                Signal::Literal(literal) => literal,
+                // No population can beat this one
+                _ => break true,
+            };
+            match self.solver.solve_with_assumptions(&[bound])? {
+                SolverResultType::Satisfiable => {
+                    best = self.current_solution(start_time);
+                    population = self.population(&best)?;
+                }
+                SolverResultType::Unsatisfiable => break true,
+                _ => break false,
+            }
+        };
+        self.population_optimum = Some(PopulationOptimum { population, proven });
+
+        let mut solutions = vec![best.clone()];
+        if !proven || self.settings.solver.max_solutions == 1 {
+            return Ok(solutions);
+        }
+
+        // Enumerate the rest of the optimal solutions: none does better, so
+        // ruling out worse populations leaves exactly the optimal one
+        let no_worse = match objective {
+            Objective::MaxPopulation => count.at_least(population),
+            _ => count.at_least(population + 1).map(Signal::negate),
+        };
+        if let Some(Signal::Literal(literal)) = no_worse {
+            self.solver.add_clause(&Clause::unit(literal))?;
+        }
+        let blocking = projection.iter()
+            .map(|&var| if best.assignment.get(&var).copied().unwrap_or(false) { -var } else { var })
+            .collect();
+        self.solver.add_clause(&Clause::new(blocking))?;
+        solutions.extend(self.solver.solve_multiple_projected(self.settings.solver.max_solutions - 1, projection)?);
+
+        Ok(solutions)
+    }
+
+    /// The solver's current model as a solution
+    fn current_solution(&self, start_time: Instant) -> SolverSolution {
+        SolverSolution {
+            assignment: self.solver.extract_assignment(),
+            solve_time: start_time.elapsed(),
+        }
+    }
+
+    /// Number of live cells in the predecessor of a solution
+    fn population(&mut self, solution: &SolverSolution) -> Result<usize> {
+        Ok(self.extract_grid_from_solution(solution, 0)?.living_count())
+    }
+
+    /// Population of the last optimized search, and whether it was proven optimal
+    pub fn population_optimum(&self) -> Option<PopulationOptimum> {
+        self.population_optimum
+    }
+
+    /// Look for a single predecessor, recording a DRAT proof in case there is none
+    ///
+    /// Proof recording has to start before any clause reaches the solver, so
+    /// this must be the first search on the encoder (or follow `reset`).
+    pub fn find_predecessor_with_proof(&mut self, target: &TargetPattern) -> Result<PredecessorSearch> {
+        let timeout = Duration::from_secs(self.settings.solver.timeout_seconds);
+        self.solver.set_cancellation_token(self.cancellation.with_timeout(timeout));
+        self.solver.enable_proof()
+            .context("The solver backend cannot certify that no predecessor exists")?;
+
+        let clauses = self.constraint_generator
+            .generate_all_constraints_for_pattern(target)
+            .context("Failed to generate SAT constraints")?;
+        self.solver.add_clauses(&clauses)
+            .context("Failed to add clauses to SAT solver")?;
+
+        match self.solver.solve_with_assumptions(&[])? {
+            SolverResultType::Satisfiable => {
+                let solution = SolverSolution {
+                    assignment: self.solver.extract_assignment(),
+                    solve_time: self.solver.statistics().solve_time,
+                };
+                Ok(PredecessorSearch::Found(self.extract_grid_from_solution(&solution, 0)?))
+            }
+            SolverResultType::Unsatisfiable => {
+                let mut proof = self.solver.take_proof()
+                    .context("The solver did not record a proof")?;
+                proof.add(Vec::new());
+                Ok(PredecessorSearch::Refuted {
+                    variable_count: self.constraint_generator.statistics().total_variables,
+                    formula: clauses,
+                    proof,
+                })
+            }
+            _ => Ok(PredecessorSearch::Interrupted),
+        }
+    }
+
+    /// Find a locally minimal set of target cells that no predecessor can produce
+    ///
+    /// The specified cells in `candidates`, given as `(row, col)`, are assumed
+    /// rather than fixed by unit clauses, and the solver's failed assumptions
+    /// are shrunk until dropping any one cell would let a predecessor exist.
+    /// The other specified cells stay fixed. Returns `None` when the target
+    /// has a predecessor.
+    pub fn find_unreachable_core(&mut self, target: &TargetPattern, candidates: &[(usize, usize)]) -> Result<Option<CellCore>> {
+        if self.settings.encoding.symmetry_breaking {
+            anyhow::bail!("Symmetry breaking assumes the whole target, so it must be disabled to explain one");
+        }
+
+        let timeout = Duration::from_secs(self.settings.solver.timeout_seconds);
+        self.solver.set_cancellation_token(self.cancellation.with_timeout(timeout));
+
+        let (clauses, assumptions) = self.constraint_generator
+            .generate_constraints_with_assumptions(target, candidates)
+            .context("Failed to generate SAT constraints")?;
+        self.solver.add_clauses(&clauses)
+            .context("Failed to add clauses to SAT solver")?;
+
+        let core = self.solver.unsat_core(&assumptions)
+            .context("SAT solving failed")?;
+
+        Ok(core.map(|core| {
+            let cells = candidates.iter()
+                .zip(&assumptions)
+                .filter(|(_, literal)| core.literals.contains(literal))
+                .map(|(&cell, _)| cell)
+                .collect();
+            CellCore { cells, minimal: core.minimal }
+        }))
+    }
+
+    /// Count the distinct predecessors that match `target`, without enumerating them
+    ///
+    /// Models are projected onto the predecessor's cells, so predecessors that
+    /// only differ in later generations or auxiliary variables count once.
+    /// The count is `None` when it is stopped by timeout or cancellation.
+    pub fn count_predecessors(&mut self, target: &TargetPattern) -> Result<(Option<u128>, CounterStatistics)> {
+        if self.settings.encoding.symmetry_breaking {
+            anyhow::bail!("Symmetry breaking keeps one predecessor per orbit, so it must be disabled to count them");
+        }
+
+        let timeout = Duration::from_secs(self.settings.solver.timeout_seconds);
+        let clauses = self.constraint_generator
+            .generate_all_constraints_for_pattern(target)
+            .context("Failed to generate SAT constraints")?;
+        let projection = self.constraint_generator
+            .variable_manager()
+            .all_cell_variables_at_time(0)?;
+
+        let mut counter = ModelCounter::new(&projection)
+            .with_cancellation(self.cancellation.with_timeout(timeout));
+        let count = counter.count(&clauses)
+            .context("Model counting failed")?;
+
+        Ok((count, counter.statistics().clone()))
+    }
+
+    /// Generate the CNF that `solve_pattern` would hand the solver, without solving it
+    ///
+    /// The formula carries the variable of every cell, so a model found by an
+    /// external solver can be read back into grids.
+    pub fn export_cnf(&mut self, target: &TargetPattern) -> Result<CellFormula> {
+        let clauses = self.constraint_generator
+            .generate_all_constraints_for_pattern(target)
+            .context("Failed to generate SAT constraints")?;
+        let variable_manager = self.constraint_generator.variable_manager();
+        let (width, height, time_steps) = variable_manager.dimensions();
+
+        Ok(CellFormula {
+            width,
+            height,
+            time_steps,
+            variable_count: variable_manager.variable_count(),
+            clauses,
+            cells: variable_manager.cell_variables().into_iter().collect(),
+        })
+    }
+
     /// Extract a Game of Life grid from a SAT solution at a specific time step
     fn extract_grid_from_solution(&mut self, solution: &SolverSolution, time_step: usize) -> Result<Grid> {
         let mut grid = Grid::new(
@@ -116,13 +498,14 @@ impl SatEncoder {
     }
 
     /// Validate that a predecessor grid correctly evolves to the target
-    fn validate_solution(&self, predecessor: &Grid, target: &Grid) -> Result<bool> {
-        let evolved = GameOfLifeRules::evolve_generations(
+    fn validate_solution(&self, predecessor: &Grid, target: &TargetPattern) -> Result<bool> {
+        let evolved = GameOfLifeRules::evolve_generations_with_rule(
             predecessor.clone(),
             self.settings.simulation.generations,
+            &self.settings.simulation.rule,
         );
 
-        Ok(GameOfLifeRules::grids_equal(&evolved, target))
+        Ok(target.matches(&evolved))
     }
 
     /// Get all intermediate states from a solution
@@ -147,21 +530,25 @@ impl SatEncoder {
             grid_height: self.grid_height,
             generations: self.settings.simulation.generations,
             total_variables: constraint_stats.total_variables,
+            cell_variables: constraint_stats.cell_variables,
+            auxiliary_variables: constraint_stats.auxiliary_variables,
             total_clauses: solver_stats.clause_count,
+            transition: constraint_stats.transition,
             boundary_condition: self.settings.simulation.boundary_condition.clone(),
+            backend: self.solver.backend(),
+            portfolio: self.portfolio_statistics.clone(),
         }
     }
 
     /// Reset the encoder for a new problem
-    pub fn reset(&mut self) {
-        self.solver.reset();
-        self.constraint_generator = ConstraintGenerator::new(
-            self.grid_width,
-            self.grid_height,
-            self.settings.simulation.generations + 1,
-            self.settings.simulation.boundary_condition.clone(),
-            self.settings.encoding.symmetry_breaking,
-        );
+    pub fn reset(&mut self) -> Result<()> {
+        self.solver = Self::create_solver(&self.settings)?;
+        self.search_interrupted = false;
+        self.portfolio_statistics.clear();
+        self.constraint_generator =
+            Self::create_constraint_generator(&self.settings, self.grid_width, self.grid_height)
+                .with_dead_cells(self.dead_cells.clone());
+        Ok(())
     }
 
     /// Check if the problem is likely to be solvable (heuristic check)
@@ -198,6 +585,38 @@ impl SatEncoder {
     }
 }
 
+/// Best predecessor population reached by an optimizing search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PopulationOptimum {
+    pub population: usize,
+    /// Whether the solver proved that no predecessor does better
+    pub proven: bool,
+}
+
+/// Specified target cells that no predecessor can produce together
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellCore {
+    /// `(row, col)` of the cells in the core
+    pub cells: Vec<(usize, usize)>,
+    /// Whether shrinking finished, rather than stopping on timeout or cancellation
+    pub minimal: bool,
+}
+
+/// Outcome of a search for a single predecessor
+#[derive(Debug, Clone)]
+pub enum PredecessorSearch {
+    /// A predecessor was found
+    Found(Grid),
+    /// No predecessor exists: `proof` refutes `formula`
+    Refuted {
+        formula: Vec<Clause>,
+        variable_count: usize,
+        proof: DratProof,
+    },
+    /// The search stopped on timeout or cancellation
+    Interrupted,
+}
+
 /// Statistics about the SAT encoding
 #[derive(Debug, Clone)]
 pub struct EncodingStatistics {
@@ -205,8 +624,14 @@ pub struct EncodingStatistics {
     pub grid_height: usize,
     pub generations: usize,
     pub total_variables: usize,
+    pub cell_variables: usize,
+    pub auxiliary_variables: usize,
     pub total_clauses: usize,
+    pub transition: TransitionEncoding,
     pub boundary_condition: crate::config::BoundaryCondition,
+    pub backend: SolverBackend,
+    /// How each member of the last portfolio race did
+    pub portfolio: Vec<MemberStatistics>,
 }
 
 /// Complexity estimate for the problem
@@ -233,9 +658,15 @@ impl std::fmt::Display for EncodingStatistics {
         writeln!(f, "SAT Encoding Statistics:")?;
         writeln!(f, "  Grid: {}x{}", self.grid_width, self.grid_height)?;
         writeln!(f, "  Generations: {}", self.generations)?;
-        writeln!(f, "  Total variables: {}", self.total_variables)?;
+        writeln!(f, "  Transition encoding: {:?}", self.transition)?;
+        writeln!(f, "  Total variables: {} ({} cell, {} auxiliary)",
+                 self.total_variables, self.cell_variables, self.auxiliary_variables)?;
         writeln!(f, "  Total clauses: {}", self.total_clauses)?;
         writeln!(f, "  Boundary condition: {:?}", self.boundary_condition)?;
+        writeln!(f, "  Solver backend: {:?}", self.backend)?;
+        for member in &self.portfolio {
+            writeln!(f, "    {}", member)?;
+        }
         Ok(())
     }
 }
@@ -266,7 +697,8 @@ impl std::fmt::Display for ComplexityEstimate {
 mod tests {
     use super::*;
     use crate::config::*;
-    use crate::game_of_life::Grid;
+    use crate::game_of_life::{Grid, Rule};
+    use crate::sat::{target_symmetries, BRUTE_FORCE_MAX_CELLS};
     use std::path::PathBuf;
 
     fn create_test_settings() -> Settings {
@@ -274,6 +706,9 @@ mod tests {
             simulation: SimulationConfig {
                 generations: 1,
                 boundary_condition: BoundaryCondition::Dead,
+                rule: Rule::conway(),
+                margin: 0,
+                free_margin: false,
             },
             solver: SolverConfig {
                 max_solutions: 5,
@@ -282,17 +717,27 @@ mod tests {
                 enable_preprocessing: false,
                 verbosity: 0,
                 backend: SolverBackend::Cadical,
+                random_seed: None,
+                projection_generation: 0,
+                optimize: Objective::None,
+                portfolio: Vec::new(),
+                cubes: CubeConfig::default(),
             },
             input: InputConfig {
                 target_state_file: PathBuf::from("test.txt"),
+                image: ImageImportConfig::default(),
             },
             output: OutputConfig {
                 format: OutputFormat::Text,
                 save_intermediate: false,
                 output_directory: PathBuf::from("output"),
+                image: ImageExportConfig::default(),
             },
             encoding: EncodingConfig {
                 symmetry_breaking: false,
+                transition: TransitionEncoding::Direct,
+                min_population: None,
+                max_population: None,
             },
         }
     }
@@ -306,12 +751,440 @@ mod tests {
             vec![false, true, false],
         ];
         let target_grid = Grid::from_cells(cells, BoundaryCondition::Dead).unwrap();
-        let encoder = SatEncoder::new(settings, &target_grid);
+        let encoder = SatEncoder::new(settings, &target_grid).unwrap();
         
         let stats = encoder.statistics();
         assert_eq!(stats.grid_width, 3);
         assert_eq!(stats.grid_height, 3);
         assert_eq!(stats.generations, 1);
+        assert_eq!(stats.backend, SolverBackend::Cadical);
+    }
+
+    #[test]
+    fn test_encoder_uses_configured_backend() {
+        let mut settings = create_test_settings();
+        settings.solver.backend = SolverBackend::Parkissat;
+        settings.solver.num_threads = Some(2);
+        settings.solver.random_seed = Some(7);
+
+        let target_grid = Grid::new(3, 3, BoundaryCondition::Dead);
+        let mut encoder = SatEncoder::new(settings, &target_grid).unwrap();
+        assert_eq!(encoder.statistics().backend, SolverBackend::Parkissat);
+
+        // Empty target: the empty grid is always a predecessor
+        let solutions = encoder.solve(&target_grid).unwrap();
+        assert!(!solutions.is_empty());
+    }
+
+    #[test]
+    fn test_solutions_have_distinct_predecessors() {
+        let mut settings = create_test_settings();
+        settings.simulation.generations = 2;
+        settings.solver.max_solutions = 20;
+
+        // Intermediate generations vary freely, so only projection keeps predecessors unique
+        let target_grid = Grid::new(3, 3, BoundaryCondition::Dead);
+        let mut encoder = SatEncoder::new(settings, &target_grid).unwrap();
+        let solutions = encoder.solve(&target_grid).unwrap();
+        assert_eq!(solutions.len(), 20);
+
+        for (i, a) in solutions.iter().enumerate() {
+            for b in &solutions[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_projection_generation_out_of_range() {
+        let mut settings = create_test_settings();
+        settings.solver.projection_generation = 2;
+
+        let target_grid = Grid::new(3, 3, BoundaryCondition::Dead);
+        let mut encoder = SatEncoder::new(settings, &target_grid).unwrap();
+        assert!(encoder.solve(&target_grid).is_err());
+    }
+
+    #[test]
+    fn test_solve_under_highlife() {
+        let mut settings = create_test_settings();
+        let highlife: Rule = "B36/S23".parse().unwrap();
+        settings.simulation.rule = highlife.clone();
+
+        let seed = Grid::from_cells(vec![
+            vec![true, true, true, false],
+            vec![false, false, false, false],
+            vec![true, true, true, false],
+            vec![false, false, false, false],
+        ], BoundaryCondition::Dead).unwrap();
+        let target_grid = GameOfLifeRules::evolve_with_rule(&seed, &highlife);
+
+        let mut encoder = SatEncoder::new(settings, &target_grid).unwrap();
+        let solutions = encoder.solve(&target_grid).unwrap();
+        assert!(!solutions.is_empty());
+
+        for solution in &solutions {
+            let evolved = GameOfLifeRules::evolve_with_rule(solution, &highlife);
+            assert!(GameOfLifeRules::grids_equal(&evolved, &target_grid));
+        }
+    }
+
+    /// Every 3x3 predecessor of `target_grid`, found by forward simulation
+    fn brute_force_predecessors(target_grid: &Grid, rule: &Rule) -> Vec<Vec<bool>> {
+        brute_force_pattern_predecessors(&TargetPattern::from_grid(target_grid.clone()), rule)
+    }
+
+    /// Every 3x3 grid that evolves to match `target` on its specified cells
+    fn brute_force_pattern_predecessors(target: &TargetPattern, rule: &Rule) -> Vec<Vec<bool>> {
+        let mut predecessors: Vec<Vec<bool>> = (0..512u32)
+            .map(|bits| {
+                let mut grid = Grid::new(3, 3, target.grid.boundary_condition.clone());
+                grid.cells = (0..9).map(|i| bits & (1 << i) != 0).collect();
+                grid
+            })
+            .filter(|grid| target.matches(&GameOfLifeRules::evolve_with_rule(grid, rule)))
+            .map(|grid| grid.cells)
+            .collect();
+        predecessors.sort();
+        predecessors
+    }
+
+    /// Every predecessor the encoder finds for `target_grid`, sorted
+    fn solve_all(settings: Settings, target_grid: &Grid) -> Vec<Vec<bool>> {
+        solve_all_pattern(settings, &TargetPattern::from_grid(target_grid.clone()))
+    }
+
+    /// Every predecessor the encoder finds for `target`, sorted
+    fn solve_all_pattern(mut settings: Settings, target: &TargetPattern) -> Vec<Vec<bool>> {
+        settings.simulation.boundary_condition = target.grid.boundary_condition.clone();
+        settings.solver.max_solutions = 512;
+
+        let mut encoder = SatEncoder::new(settings, &target.grid).unwrap();
+        let mut found: Vec<Vec<bool>> = encoder.solve_pattern(target).unwrap()
+            .into_iter()
+            .map(|grid| grid.cells)
+            .collect();
+        found.sort();
+        found
+    }
+
+    #[test]
+    fn test_isotropic_rule_matches_brute_force() {
+        let rule: Rule = "B2-a/S12".parse().unwrap();
+        let target_cells = vec![
+            vec![true, false, false],
+            vec![false, true, true],
+            vec![false, false, false],
+        ];
+
+        for boundary in [BoundaryCondition::Dead, BoundaryCondition::Wrap, BoundaryCondition::Mirror] {
+            let target_grid = Grid::from_cells(target_cells.clone(), boundary.clone()).unwrap();
+            let expected = brute_force_predecessors(&target_grid, &rule);
+
+            for transition in [TransitionEncoding::Direct, TransitionEncoding::Minimized] {
+                let mut settings = create_test_settings();
+                settings.simulation.rule = rule.clone();
+                settings.encoding.transition = transition;
+
+                assert_eq!(solve_all(settings, &target_grid), expected, "{:?} with {:?} boundary", transition, boundary);
+            }
+        }
+    }
+
+    #[test]
+    fn test_transition_encodings_match_brute_force() {
+        let target_cells = vec![
+            vec![false, true, false],
+            vec![false, true, true],
+            vec![false, false, false],
+        ];
+
+        for boundary in [BoundaryCondition::Dead, BoundaryCondition::Wrap, BoundaryCondition::Mirror] {
+            let target_grid = Grid::from_cells(target_cells.clone(), boundary.clone()).unwrap();
+            let expected = brute_force_predecessors(&target_grid, &Rule::conway());
+
+            for transition in [
+                TransitionEncoding::Direct,
+                TransitionEncoding::Totalizer,
+                TransitionEncoding::SequentialCounter,
+                TransitionEncoding::Adder,
+                TransitionEncoding::Minimized,
+            ] {
+                let mut settings = create_test_settings();
+                settings.encoding.transition = transition;
+
+                assert_eq!(solve_all(settings, &target_grid), expected, "{:?} with {:?} boundary", transition, boundary);
+            }
+        }
+    }
+
+    /// Compare every backend against the brute-force oracle on the example
+    /// targets of `min_cells` to `max_cells` cells, returning how many were compared
+    fn compare_backends_on_examples(min_cells: usize, max_cells: usize) -> usize {
+        let directory = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("input/target_states");
+        let mut compared = 0;
+
+        for entry in std::fs::read_dir(directory).unwrap() {
+            let path = entry.unwrap().path();
+            let target = crate::game_of_life::load_pattern_from_file(&path, BoundaryCondition::Dead).unwrap();
+            if !(min_cells..=max_cells).contains(&(target.grid.width * target.grid.height)) {
+                continue;
+            }
+
+            let mut settings = create_test_settings();
+            settings.solver.max_solutions = 1 << 16;
+            settings.solver.timeout_seconds = 3600;
+            let mut predecessor_sets = Vec::new();
+            for backend in [
+                SolverBackend::Cadical,
+                SolverBackend::Parkissat,
+                SolverBackend::BruteForce,
+                SolverBackend::Portfolio,
+                SolverBackend::CubeAndConquer,
+            ] {
+                settings.solver.backend = backend.clone();
+                let mut encoder = SatEncoder::new(settings.clone(), &target.grid).unwrap();
+                let mut found: Vec<Vec<bool>> = encoder.solve_pattern(&target).unwrap()
+                    .into_iter()
+                    .map(|grid| grid.cells)
+                    .collect();
+                found.sort();
+                assert!(!encoder.was_interrupted(), "{:?} on {}", backend, path.display());
+                if backend == SolverBackend::Portfolio {
+                    let members = encoder.statistics().portfolio;
+                    assert_eq!(members.iter().filter(|member| member.won).count(), 1);
+                }
+                predecessor_sets.push((backend, found));
+            }
+
+            let (_, expected) = &predecessor_sets[2];
+            for (backend, found) in &predecessor_sets {
+                assert_eq!(found, expected, "{:?} on {}", backend, path.display());
+            }
+            compared += 1;
+        }
+
+        compared
+    }
+
+    /// Largest example the default test run hands the oracle
+    const QUICK_ORACLE_CELLS: usize = 16;
+
+    #[test]
+    fn test_backends_agree_on_example_targets() {
+        // Blinker, block, empty and single cell
+        let compared = compare_backends_on_examples(0, QUICK_ORACLE_CELLS);
+        assert!(compared >= 4, "only {} example targets compared", compared);
+    }
+
+    /// The 24-cell beacon and 25-cell glider, run with `cargo test -- --ignored`
+    #[test]
+    #[ignore = "the brute-force oracle takes minutes on these in a debug build"]
+    fn test_backends_agree_on_large_example_targets() {
+        let compared = compare_backends_on_examples(QUICK_ORACLE_CELLS + 1, BRUTE_FORCE_MAX_CELLS);
+        assert!(compared >= 2, "only {} example targets compared", compared);
+    }
+
+    #[test]
+    fn test_brute_force_backend_applies_objective_and_limit() {
+        let target = Grid::from_cells(vec![vec![false, true, false]; 3], BoundaryCondition::Dead).unwrap();
+        let expected = brute_force_predecessors(&target, &Rule::conway());
+        let fewest = expected.iter().map(|cells| cells.iter().filter(|&&alive| alive).count()).min().unwrap();
+
+        let mut settings = create_test_settings();
+        settings.solver.backend = SolverBackend::BruteForce;
+        settings.solver.optimize = Objective::MinPopulation;
+        settings.solver.max_solutions = 1;
+        let mut encoder = SatEncoder::new(settings, &target).unwrap();
+        let found = encoder.solve(&target).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].living_count(), fewest);
+        assert_eq!(encoder.population_optimum(), Some(PopulationOptimum { population: fewest, proven: true }));
+        assert!(!encoder.was_interrupted());
+    }
+
+    #[test]
+    fn test_symmetry_breaking_keeps_one_solution_per_orbit() {
+        let blinker = vec![
+            vec![false, true, false],
+            vec![false, true, false],
+            vec![false, true, false],
+        ];
+        let empty = vec![vec![false; 3]; 3];
+        let full = vec![vec![true; 3]; 3];
+        let mut pruned_any = false;
+
+        for (cells, boundary) in [
+            (blinker.clone(), BoundaryCondition::Dead),
+            (blinker, BoundaryCondition::Mirror),
+            (empty, BoundaryCondition::Dead),
+            (full, BoundaryCondition::Wrap),
+        ] {
+            let target_grid = Grid::from_cells(cells, boundary.clone()).unwrap();
+            let symmetries = target_symmetries(&target_grid, &boundary);
+            assert!(!symmetries.is_empty());
+
+            let all = solve_all(create_test_settings(), &target_grid);
+            let mut settings = create_test_settings();
+            settings.encoding.symmetry_breaking = true;
+            let representatives = solve_all(settings, &target_grid);
+
+            // Closing the representatives under the symmetries recovers every predecessor
+            let mut closure: Vec<Vec<bool>> = representatives.iter()
+                .flat_map(|cells| {
+                    let grid = Grid { width: 3, height: 3, cells: cells.clone(), boundary_condition: boundary.clone() };
+                    std::iter::once(cells.clone())
+                        .chain(symmetries.iter().map(move |symmetry| symmetry.transform(&grid).cells))
+                })
+                .collect();
+            closure.sort();
+            closure.dedup();
+            assert_eq!(closure, all, "{:?} boundary", boundary);
+
+            // and no two representatives are images of each other
+            for (i, cells) in representatives.iter().enumerate() {
+                let grid = Grid { width: 3, height: 3, cells: cells.clone(), boundary_condition: boundary.clone() };
+                for symmetry in &symmetries {
+                    let image = symmetry.transform(&grid).cells;
+                    assert!(image == *cells || !representatives[i + 1..].contains(&image));
+                }
+            }
+            pruned_any |= representatives.len() < all.len();
+        }
+        assert!(pruned_any);
+    }
+
+    #[test]
+    fn test_dont_care_cells_are_unconstrained() {
+        // A vertical blinker whose side columns may be anything
+        let pattern = crate::game_of_life::io::parse_pattern_from_string("?1?\n?1?\n?1?\n", BoundaryCondition::Dead).unwrap();
+        let pinned = Grid::from_cells(vec![vec![false, true, false]; 3], BoundaryCondition::Dead).unwrap();
+
+        let expected = brute_force_pattern_predecessors(&pattern, &Rule::conway());
+        let found = solve_all_pattern(create_test_settings(), &pattern);
+        assert_eq!(found, expected);
+
+        // Every predecessor of the fully specified blinker is still a solution, and there are more
+        let pinned_predecessors = brute_force_predecessors(&pinned, &Rule::conway());
+        assert!(pinned_predecessors.iter().all(|cells| found.contains(cells)));
+        assert!(found.len() > pinned_predecessors.len());
+
+        // Symmetry breaking only uses symmetries that also fix the don't-care cells
+        let mut settings = create_test_settings();
+        settings.encoding.symmetry_breaking = true;
+        let representatives = solve_all_pattern(settings, &pattern);
+        assert!(!representatives.is_empty() && representatives.len() < found.len());
+    }
+
+    #[test]
+    fn test_population_bounds_match_brute_force() {
+        let target = Grid::from_cells(vec![vec![false, true, false]; 3], BoundaryCondition::Dead).unwrap();
+        let expected = brute_force_predecessors(&target, &Rule::conway());
+        let population = |cells: &Vec<bool>| cells.iter().filter(|&&alive| alive).count();
+
+        for (min, max) in [(Some(4), None), (None, Some(4)), (Some(4), Some(5)), (Some(9), None)] {
+            let mut settings = create_test_settings();
+            settings.encoding.min_population = min;
+            settings.encoding.max_population = max;
+
+            let bounded: Vec<Vec<bool>> = expected.iter()
+                .filter(|cells| min.is_none_or(|min| population(cells) >= min) && max.is_none_or(|max| population(cells) <= max))
+                .cloned()
+                .collect();
+            assert_eq!(solve_all(settings, &target), bounded, "bounds {:?}..{:?}", min, max);
+        }
+    }
+
+    #[test]
+    fn test_population_optimization() {
+        let target = Grid::from_cells(vec![vec![false, true, false]; 3], BoundaryCondition::Dead).unwrap();
+        let expected = brute_force_predecessors(&target, &Rule::conway());
+        let population = |cells: &Vec<bool>| cells.iter().filter(|&&alive| alive).count();
+
+        for (objective, best) in [
+            (Objective::MinPopulation, expected.iter().map(population).min().unwrap()),
+            (Objective::MaxPopulation, expected.iter().map(population).max().unwrap()),
+        ] {
+            let mut settings = create_test_settings();
+            settings.solver.optimize = objective;
+            let optimal: Vec<Vec<bool>> = expected.iter().filter(|cells| population(cells) == best).cloned().collect();
+
+            // Every optimal predecessor is reported, and nothing else
+            assert_eq!(solve_all(settings.clone(), &target), optimal, "{:?}", objective);
+
+            settings.solver.max_solutions = 1;
+            let mut encoder = SatEncoder::new(settings, &target).unwrap();
+            let found = encoder.solve(&target).unwrap();
+            assert_eq!(found.len(), 1);
+            assert_eq!(found[0].living_count(), best);
+            assert_eq!(encoder.population_optimum(), Some(PopulationOptimum { population: best, proven: true }));
+        }
+    }
+
+    #[test]
+    fn test_unreachable_core() {
+        // Either end of a 3x1 row has a single neighbor, so it can never be alive
+        let target = TargetPattern::from_grid(Grid::from_cells(vec![vec![true; 3]], BoundaryCondition::Dead).unwrap());
+        let cells = [(0, 0), (0, 1), (0, 2)];
+        let mut encoder = SatEncoder::new(create_test_settings(), &target.grid).unwrap();
+        let core = encoder.find_unreachable_core(&target, &cells).unwrap().unwrap();
+        assert!(core.minimal);
+        assert!(core.cells == vec![(0, 0)] || core.cells == vec![(0, 2)], "unexpected core {:?}", core);
+
+        // The middle cell alone is reachable
+        let middle = TargetPattern::new(target.grid.clone(), vec![false, true, false]).unwrap();
+        let mut encoder = SatEncoder::new(create_test_settings(), &target.grid).unwrap();
+        assert_eq!(encoder.find_unreachable_core(&middle, &[(0, 1)]).unwrap(), None);
+
+        let mut settings = create_test_settings();
+        settings.encoding.symmetry_breaking = true;
+        let mut encoder = SatEncoder::new(settings, &target.grid).unwrap();
+        assert!(encoder.find_unreachable_core(&target, &cells).is_err());
+    }
+
+    #[test]
+    fn test_count_predecessors_matches_brute_force() {
+        let target = Grid::from_cells(vec![vec![false, true, false]; 3], BoundaryCondition::Dead).unwrap();
+        let expected = brute_force_predecessors(&target, &Rule::conway()).len() as u128;
+
+        // Counts are projected onto the predecessor, whatever the encoding adds
+        for transition in [TransitionEncoding::Direct, TransitionEncoding::Totalizer, TransitionEncoding::Minimized] {
+            let mut settings = create_test_settings();
+            settings.encoding.transition = transition;
+            let mut encoder = SatEncoder::new(settings, &target).unwrap();
+            let (count, stats) = encoder.count_predecessors(&TargetPattern::from_grid(target.clone())).unwrap();
+            assert_eq!(count, Some(expected), "{:?}", transition);
+            assert!(stats.clauses > 0);
+        }
+
+        let mut settings = create_test_settings();
+        settings.encoding.symmetry_breaking = true;
+        let mut encoder = SatEncoder::new(settings, &target).unwrap();
+        assert!(encoder.count_predecessors(&TargetPattern::from_grid(target)).is_err());
+    }
+
+    #[test]
+    fn test_timeout_returns_without_error() {
+        let mut settings = create_test_settings();
+        settings.solver.timeout_seconds = 0;
+
+        let target_grid = Grid::new(3, 3, BoundaryCondition::Dead);
+        let mut encoder = SatEncoder::new(settings, &target_grid).unwrap();
+        let solutions = encoder.solve(&target_grid).unwrap();
+
+        assert!(solutions.is_empty());
+        assert!(encoder.was_interrupted());
+    }
+
+    #[test]
+    fn test_cancelled_encoder_stops() {
+        let settings = create_test_settings();
+        let target_grid = Grid::new(3, 3, BoundaryCondition::Dead);
+        let mut encoder = SatEncoder::new(settings, &target_grid).unwrap();
+
+        encoder.cancellation_token().cancel();
+        assert!(encoder.solve(&target_grid).unwrap().is_empty());
+        assert!(encoder.was_interrupted());
     }
 
     #[test]
@@ -323,7 +1196,7 @@ mod tests {
             vec![false, true, false],
         ];
         let target_grid = Grid::from_cells(cells, BoundaryCondition::Dead).unwrap();
-        let encoder = SatEncoder::new(settings, &target_grid);
+        let encoder = SatEncoder::new(settings, &target_grid).unwrap();
         
         let estimate = encoder.estimate_complexity(&target_grid);
         assert_eq!(estimate.grid_size, 9);
@@ -340,7 +1213,7 @@ mod tests {
             vec![false, true, false],
         ];
         let target_grid = Grid::from_cells(cells, BoundaryCondition::Dead).unwrap();
-        let mut encoder = SatEncoder::new(settings, &target_grid);
+        let mut encoder = SatEncoder::new(settings, &target_grid).unwrap();
         
         // Create a mock solution
         let mut assignment = std::collections::HashMap::new();
@@ -0,0 +1,305 @@
+//! External SAT solver process speaking DIMACS over stdio
+//!
+//! Every solve call writes the clauses, plus one unit clause per assumption,
+//! as a fresh DIMACS CNF and runs the solver binary on it, then reads its
+//! answer in the SAT competition output format. Nothing is kept between
+//! runs, so blocking-clause enumeration re-runs the solver with the blocking
+//! clauses added. The process is killed once the cancellation token fires.
+
+use super::backend::SatBackend;
+use super::cancellation::CancellationToken;
+use super::constraints::Clause;
+use super::dimacs::{formula_to_dimacs, parse_model};
+use super::solver::{SolverOptions, SolverResultType, SolverStatistics};
+use crate::config::SolverBackend;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Placeholder in the arguments replaced by the path of a temporary CNF file
+pub const CNF_PATH_PLACEHOLDER: &str = "{cnf}";
+
+/// How often a running solver is checked for exit or cancellation
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Backend running a SAT solver binary such as kissat or cryptominisat
+pub struct ExternalSolver {
+    command: String,
+    args: Vec<String>,
+    clauses: Vec<Clause>,
+    variable_count: usize,
+    model: HashMap<i32, bool>,
+    timeout: Option<Duration>,
+    cancellation: CancellationToken,
+    solve_time: Duration,
+    last_result: SolverResultType,
+}
+
+/// Temporary CNF file, removed when dropped
+struct TempCnf {
+    path: PathBuf,
+}
+
+impl TempCnf {
+    fn create(dimacs: &str) -> Result<Self> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "game_of_life_reverse_{}_{}.cnf",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::write(&path, dimacs)
+            .with_context(|| format!("Failed to write CNF to {}", path.display()))?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for TempCnf {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+impl ExternalSolver {
+    /// Run `command` with `args` on each solve
+    pub fn new(command: impl Into<String>, args: Vec<String>) -> Self {
+        Self {
+            command: command.into(),
+            args,
+            clauses: Vec::new(),
+            variable_count: 0,
+            model: HashMap::new(),
+            timeout: None,
+            cancellation: CancellationToken::new(),
+            solve_time: Duration::ZERO,
+            last_result: SolverResultType::Error,
+        }
+    }
+
+    /// Set solving timeout
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    /// Run the solver on `dimacs`, or return `None` if it was killed on cancellation
+    fn run(&self, dimacs: String, token: &CancellationToken) -> Result<Option<SolverOutput>> {
+        let temp_cnf = if self.args.iter().any(|arg| arg.contains(CNF_PATH_PLACEHOLDER)) {
+            Some(TempCnf::create(&dimacs)?)
+        } else {
+            None
+        };
+        let args = self.args.iter().map(|arg| match &temp_cnf {
+            Some(cnf) => arg.replace(CNF_PATH_PLACEHOLDER, &cnf.path.to_string_lossy()),
+            None => arg.clone(),
+        });
+
+        let mut child = Command::new(&self.command)
+            .args(args)
+            .stdin(if temp_cnf.is_some() { Stdio::null() } else { Stdio::piped() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("Failed to start external solver '{}'", self.command))?;
+
+        // Feed and drain the pipes on their own threads, so a full pipe cannot
+        // block the solver. A solver may stop reading early, so write errors
+        // are ignored.
+        if let Some(mut stdin) = child.stdin.take() {
+            thread::spawn(move || {
+                let _ = stdin.write_all(dimacs.as_bytes());
+            });
+        }
+        let mut stdout = child.stdout.take().expect("stdout is piped");
+        let reader = thread::spawn(move || {
+            let mut output = String::new();
+            stdout.read_to_string(&mut output).map(|_| output)
+        });
+
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if token.is_cancelled() {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Ok(None);
+            }
+            thread::sleep(POLL_INTERVAL);
+        };
+
+        let output = reader.join()
+            .map_err(|_| anyhow::anyhow!("Reading the output of '{}' panicked", self.command))?
+            .with_context(|| format!("Failed to read the output of '{}'", self.command))?;
+
+        Ok(Some(SolverOutput { status, output }))
+    }
+}
+
+/// What a finished solver process left behind
+struct SolverOutput {
+    status: std::process::ExitStatus,
+    output: String,
+}
+
+impl SatBackend for ExternalSolver {
+    fn backend(&self) -> SolverBackend {
+        SolverBackend::External {
+            command: self.command.clone(),
+            args: self.args.clone(),
+        }
+    }
+
+    fn configure(&mut self, options: &SolverOptions) -> Result<()> {
+        // Threads, seeds and verbosity are up to the solver's own arguments
+        if let Some(timeout) = options.timeout {
+            self.set_timeout(timeout);
+        }
+        Ok(())
+    }
+
+    fn add_clause(&mut self, clause: &Clause) -> Result<()> {
+        if clause.is_empty() {
+            anyhow::bail!("Cannot add empty clause (unsatisfiable)");
+        }
+
+        for &literal in &clause.literals {
+            self.variable_count = self.variable_count.max(literal.unsigned_abs() as usize);
+        }
+        self.clauses.push(clause.clone());
+        Ok(())
+    }
+
+    fn solve_with_assumptions(&mut self, assumptions: &[i32]) -> Result<SolverResultType> {
+        self.model.clear();
+        let token = self.cancellation.or_timeout(self.timeout);
+        if token.is_cancelled() {
+            self.last_result = SolverResultType::Timeout;
+            return Ok(SolverResultType::Timeout);
+        }
+
+        // Assumptions become unit clauses of this run only
+        let units: Vec<Clause> = assumptions.iter().map(|&literal| Clause::unit(literal)).collect();
+        let variable_count = assumptions.iter()
+            .map(|literal| literal.unsigned_abs() as usize)
+            .fold(self.variable_count, usize::max);
+        let dimacs = formula_to_dimacs(&[self.clauses.as_slice(), units.as_slice()].concat(), variable_count);
+
+        let start_time = Instant::now();
+        let finished = self.run(dimacs, &token);
+        self.solve_time += start_time.elapsed();
+        self.last_result = SolverResultType::Error;
+
+        let Some(finished) = finished? else {
+            self.last_result = SolverResultType::Timeout;
+            return Ok(SolverResultType::Timeout);
+        };
+        let model = parse_model(&finished.output).with_context(|| {
+            format!("External solver '{}' exited with {} without an answer", self.command, finished.status)
+        })?;
+
+        self.model = model.assignment;
+        self.last_result = model.status.clone();
+        Ok(model.status)
+    }
+
+    fn model_value(&self, var: i32) -> Option<bool> {
+        self.model.get(&var).copied()
+    }
+
+    fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancellation = token;
+    }
+
+    fn statistics(&self) -> SolverStatistics {
+        SolverStatistics {
+            variable_count: self.variable_count,
+            clause_count: self.clauses.len(),
+            solve_time: self.solve_time,
+            result: self.last_result.clone(),
+        }
+    }
+
+    fn variable_count(&self) -> usize {
+        self.variable_count
+    }
+
+    fn clause_count(&self) -> usize {
+        self.clauses.len()
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// Stub solver running a shell script, with `$0` set to `script_arg` if given
+    fn stub(script: &str, script_arg: Option<&str>) -> ExternalSolver {
+        let mut args = vec!["-c".to_string(), script.to_string()];
+        args.extend(script_arg.map(str::to_string));
+        ExternalSolver::new("sh", args)
+    }
+
+    #[test]
+    fn test_reads_stub_answers() {
+        let mut solver = stub("cat > /dev/null; echo 'c stub'; echo 's SATISFIABLE'; echo 'v 1 -2'; echo 'v 0'", None);
+        solver.add_clause(&Clause::new(vec![1, 2])).unwrap();
+        let solution = solver.solve().unwrap().unwrap();
+        assert_eq!(solution.assignment.get(&1), Some(&true));
+        assert_eq!(solution.assignment.get(&2), Some(&false));
+
+        let mut solver = stub("echo 's UNSATISFIABLE'", None);
+        solver.add_clause(&Clause::new(vec![1])).unwrap();
+        assert!(solver.solve().unwrap().is_none());
+
+        // No status line is an error, and so is a missing binary
+        assert!(stub("echo crashed", None).solve_with_assumptions(&[]).is_err());
+        assert!(ExternalSolver::new("/nonexistent/solver", Vec::new()).solve_with_assumptions(&[]).is_err());
+    }
+
+    #[test]
+    fn test_enumeration_reruns_with_blocking_clauses() {
+        // Answers x1 = 1, x2 = 0 until the clause blocking that model shows up
+        let script = "if grep -q '^-1 2 0$'; then echo 's UNSATISFIABLE'; \
+                      else echo 's SATISFIABLE'; echo 'v 1 -2 0'; fi";
+        let mut solver = stub(script, None);
+        solver.add_clause(&Clause::new(vec![1, 2])).unwrap();
+
+        let solutions = solver.solve_multiple(5).unwrap();
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solver.clause_count(), 2);
+        assert_eq!(solver.statistics().result, SolverResultType::Unsatisfiable);
+    }
+
+    #[test]
+    fn test_assumptions_reach_a_temporary_file() {
+        let script = "if grep -q '^-3 0$' \"$0\"; then echo 's UNSATISFIABLE'; \
+                      else echo 's SATISFIABLE'; echo 'v 1 2 3 0'; fi";
+        let mut solver = stub(script, Some(CNF_PATH_PLACEHOLDER));
+        solver.add_clause(&Clause::new(vec![1, 2])).unwrap();
+
+        assert_eq!(solver.solve_with_assumptions(&[-3]).unwrap(), SolverResultType::Unsatisfiable);
+        assert_eq!(solver.solve_with_assumptions(&[3]).unwrap(), SolverResultType::Satisfiable);
+        assert_eq!(solver.model_value(3), Some(true));
+    }
+
+    #[test]
+    fn test_timeout_kills_the_process() {
+        let mut solver = stub("sleep 30", None);
+        solver.add_clause(&Clause::new(vec![1])).unwrap();
+        solver.set_cancellation_token(CancellationToken::new().with_timeout(Duration::from_millis(100)));
+
+        let start_time = Instant::now();
+        assert_eq!(solver.solve_with_assumptions(&[]).unwrap(), SolverResultType::Timeout);
+        assert!(start_time.elapsed() < Duration::from_secs(10));
+        assert_eq!(solver.model_value(1), None);
+    }
+}
@@ -2,14 +2,38 @@
 
 pub mod variables;
 pub mod constraints;
+pub mod cardinality;
+pub mod truth_table;
+pub mod symmetry;
+pub mod drat;
+pub mod dimacs;
+pub mod model_counter;
 pub mod encoder;
+pub mod backend;
+pub mod cancellation;
 pub mod solver;
 pub mod parkissat_solver;
+pub mod brute_force;
+pub mod external_solver;
+pub mod portfolio;
+pub mod cube;
 pub mod solver_factory;
 
 pub use variables::VariableManager;
 pub use constraints::ConstraintGenerator;
+pub use cardinality::{CardinalityEncoder, InputCount, Signal};
+pub use truth_table::{minimized_transition, Implicant, TransitionCnf};
+pub use dimacs::{formula_to_dimacs, parse_dimacs, parse_model, CellFormula, DimacsModel};
+pub use drat::{verify_proof, DratProof, ProofStep, ProofSummary};
+pub use symmetry::{pattern_symmetries, target_symmetries, Dihedral, GridSymmetry};
+pub use model_counter::{CounterStatistics, ModelCounter};
 pub use encoder::SatEncoder;
-pub use solver::{SatSolver, SolverOptions, SolverSolution, SolverStatistics, SolverResultType};
+pub use backend::SatBackend;
+pub use cancellation::CancellationToken;
+pub use solver::{SatSolver, SolverOptions, SolverSolution, SolverStatistics, SolverResultType, UnsatCore};
 pub use parkissat_solver::ParkissatSatSolver;
-pub use solver_factory::UnifiedSatSolver;
\ No newline at end of file
+pub use brute_force::{BruteForceResult, BruteForceSearch, BruteForceSolver, BRUTE_FORCE_MAX_CELLS};
+pub use external_solver::{ExternalSolver, CNF_PATH_PLACEHOLDER};
+pub use portfolio::{MemberStatistics, Portfolio, PortfolioResult, PortfolioSolver};
+pub use cube::CubeSolver;
+pub use solver_factory::{create_solver, register_backend, is_backend_registered, BackendConstructor};
\ No newline at end of file
@@ -0,0 +1,329 @@
+//! Projected model counting with component caching
+//!
+//! Counts the assignments to a set of projection variables that extend to a
+//! model of a CNF formula, without enumerating them. The search is a DPLL
+//! with unit propagation that branches on projection variables while any are
+//! left. After each step the clauses split into variable-disjoint components
+//! whose counts multiply, and every component's count is cached, so a
+//! residual formula reached through different branches is counted once. A
+//! component without projection variables counts one if it is satisfiable.
+
+use super::constraints::Clause;
+use super::CancellationToken;
+use anyhow::Result;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+/// Cached components kept before the cache is cleared, to bound memory
+const CACHE_LIMIT: usize = 1 << 20;
+
+type Formula = Vec<Vec<i32>>;
+
+/// Why a count stopped before finishing
+enum Stop {
+    Interrupted,
+    Overflow,
+}
+
+/// Counts the models of a formula projected onto a set of variables
+pub struct ModelCounter {
+    projection: HashSet<i32>,
+    cancellation: CancellationToken,
+    cache: HashMap<Formula, u128>,
+    statistics: CounterStatistics,
+}
+
+/// Statistics about the last count
+#[derive(Debug, Clone, Default)]
+pub struct CounterStatistics {
+    /// Clauses in the counted formula
+    pub clauses: usize,
+    /// Branches taken on a variable
+    pub decisions: usize,
+    /// Components counted from scratch
+    pub components: usize,
+    /// Components whose count came from the cache
+    pub cache_hits: usize,
+}
+
+impl ModelCounter {
+    /// Create a counter projecting onto the given variables
+    pub fn new(projection: &[i32]) -> Self {
+        Self {
+            projection: projection.iter().map(|var| var.abs()).collect(),
+            cancellation: CancellationToken::new(),
+            cache: HashMap::new(),
+            statistics: CounterStatistics::default(),
+        }
+    }
+
+    /// Stop counting once `token` is cancelled or past its deadline
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Count the assignments to the projection variables that extend to a model of `clauses`
+    ///
+    /// Projection variables the clauses never mention are free and double
+    /// the count. Returns `None` if counting was cancelled, and fails if the
+    /// count does not fit in a `u128`.
+    pub fn count(&mut self, clauses: &[Clause]) -> Result<Option<u128>> {
+        self.cache.clear();
+        self.statistics = CounterStatistics { clauses: clauses.len(), ..Default::default() };
+
+        let formula: Formula = clauses.iter().map(|clause| clause.literals.clone()).collect();
+        match self.count_formula(formula, self.projection.len()) {
+            Ok(count) => Ok(Some(count)),
+            Err(Stop::Interrupted) => Ok(None),
+            Err(Stop::Overflow) => anyhow::bail!("The model count does not fit in 128 bits"),
+        }
+    }
+
+    /// Statistics about the last count
+    pub fn statistics(&self) -> &CounterStatistics {
+        &self.statistics
+    }
+
+    /// Count over `free` projection variables, including those `formula` no longer mentions
+    fn count_formula(&mut self, formula: Formula, free: usize) -> Result<u128, Stop> {
+        let Some((formula, forced)) = propagate(formula) else {
+            return Ok(0);
+        };
+
+        // Projection variables that dropped out without being forced can take either value
+        let forced = forced.iter().filter(|literal| self.projection.contains(&literal.abs())).count();
+        let unconstrained = free - forced - self.projected_variables(&formula).len();
+
+        let mut counts = Vec::new();
+        for component in components(formula) {
+            let count = self.count_component(component)?;
+            if count == 0 {
+                return Ok(0);
+            }
+            counts.push(count);
+        }
+
+        counts.into_iter().try_fold(power_of_two(unconstrained)?, checked_mul)
+    }
+
+    /// Count a connected component, branching on its most frequent variable
+    fn count_component(&mut self, mut component: Formula) -> Result<u128, Stop> {
+        if self.cancellation.is_cancelled() {
+            return Err(Stop::Interrupted);
+        }
+
+        // Equal components must look equal to the cache
+        for clause in &mut component {
+            clause.sort_unstable();
+        }
+        component.sort_unstable();
+        if let Some(&count) = self.cache.get(&component) {
+            self.statistics.cache_hits += 1;
+            return Ok(count);
+        }
+        self.statistics.components += 1;
+
+        // Without projection variables left, only satisfiability matters
+        let projected = self.projected_variables(&component);
+        let var = most_frequent_variable(&component, |var| projected.is_empty() || projected.contains(&var));
+
+        let mut count = 0u128;
+        for literal in [var, -var] {
+            self.statistics.decisions += 1;
+            if let Some(rest) = assign(&component, &HashSet::from([literal])) {
+                let branch = self.count_formula(rest, projected.len().saturating_sub(1))?;
+                count = count.checked_add(branch).ok_or(Stop::Overflow)?;
+            }
+            if projected.is_empty() && count > 0 {
+                break;
+            }
+        }
+
+        if self.cache.len() >= CACHE_LIMIT {
+            self.cache.clear();
+        }
+        self.cache.insert(component, count);
+        Ok(count)
+    }
+
+    /// Projection variables mentioned by `formula`
+    fn projected_variables(&self, formula: &[Vec<i32>]) -> HashSet<i32> {
+        formula.iter()
+            .flatten()
+            .map(|literal| literal.abs())
+            .filter(|var| self.projection.contains(var))
+            .collect()
+    }
+}
+
+/// Make every literal in `literals` true
+///
+/// Satisfied clauses are dropped and falsified literals removed from the
+/// rest. Returns `None` if a clause loses all its literals.
+fn assign(formula: &[Vec<i32>], literals: &HashSet<i32>) -> Option<Formula> {
+    let mut result = Vec::with_capacity(formula.len());
+
+    for clause in formula {
+        if clause.iter().any(|literal| literals.contains(literal)) {
+            continue;
+        }
+        let rest: Vec<i32> = clause.iter()
+            .copied()
+            .filter(|literal| !literals.contains(&-literal))
+            .collect();
+        if rest.is_empty() {
+            return None;
+        }
+        result.push(rest);
+    }
+
+    Some(result)
+}
+
+/// Assign unit clauses until none is left
+///
+/// Returns the simplified formula and the literals that were forced, or
+/// `None` on a conflict.
+fn propagate(mut formula: Formula) -> Option<(Formula, Vec<i32>)> {
+    if formula.iter().any(Vec::is_empty) {
+        return None;
+    }
+
+    let mut forced = Vec::new();
+    loop {
+        let units: HashSet<i32> = formula.iter()
+            .filter(|clause| clause.len() == 1)
+            .map(|clause| clause[0])
+            .collect();
+        if units.is_empty() {
+            return Some((formula, forced));
+        }
+        if units.iter().any(|literal| units.contains(&-literal)) {
+            return None;
+        }
+
+        formula = assign(&formula, &units)?;
+        forced.extend(units);
+    }
+}
+
+/// Split a formula into groups of clauses that share no variables
+fn components(formula: Formula) -> Vec<Formula> {
+    // Union-find over the variables, joining those that share a clause
+    let mut parent: HashMap<i32, i32> = HashMap::new();
+    let find = |parent: &mut HashMap<i32, i32>, var: i32| {
+        let mut root = var;
+        while let Some(&next) = parent.get(&root) {
+            if next == root {
+                break;
+            }
+            root = next;
+        }
+        parent.insert(var, root);
+        root
+    };
+
+    for clause in &formula {
+        let first = find(&mut parent, clause[0].abs());
+        for literal in &clause[1..] {
+            let root = find(&mut parent, literal.abs());
+            if root != first {
+                parent.insert(root, first);
+            }
+        }
+    }
+
+    let mut groups: HashMap<i32, Formula> = HashMap::new();
+    for clause in formula {
+        let root = find(&mut parent, clause[0].abs());
+        groups.entry(root).or_default().push(clause);
+    }
+    groups.into_values().collect()
+}
+
+/// The variable passing `eligible` that occurs in the most clauses, lowest first on ties
+fn most_frequent_variable(formula: &[Vec<i32>], eligible: impl Fn(i32) -> bool) -> i32 {
+    let mut occurrences: HashMap<i32, usize> = HashMap::new();
+    for var in formula.iter().flatten().map(|literal| literal.abs()).filter(|&var| eligible(var)) {
+        *occurrences.entry(var).or_default() += 1;
+    }
+
+    occurrences.into_iter()
+        .max_by_key(|&(var, count)| (count, Reverse(var)))
+        .map(|(var, _)| var)
+        .expect("components mention at least one eligible variable")
+}
+
+fn power_of_two(exponent: usize) -> Result<u128, Stop> {
+    u32::try_from(exponent).ok()
+        .and_then(|exponent| 1u128.checked_shl(exponent))
+        .ok_or(Stop::Overflow)
+}
+
+fn checked_mul(a: u128, b: u128) -> Result<u128, Stop> {
+    a.checked_mul(b).ok_or(Stop::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(clauses: &[&[i32]], projection: &[i32]) -> Option<u128> {
+        let clauses: Vec<Clause> = clauses.iter().map(|literals| Clause::new(literals.to_vec())).collect();
+        ModelCounter::new(projection).count(&clauses).unwrap()
+    }
+
+    /// Count projected models by trying every assignment to variables `1..=variable_count`
+    fn brute_force(clauses: &[&[i32]], projection: &[i32], variable_count: usize) -> u128 {
+        let mut projected_models = HashSet::new();
+        for bits in 0..1u32 << variable_count {
+            let value = |literal: i32| ((bits >> (literal.abs() - 1)) & 1 == 1) == (literal > 0);
+            if clauses.iter().all(|clause| clause.iter().any(|&literal| value(literal))) {
+                let key: Vec<bool> = projection.iter().map(|&var| value(var)).collect();
+                projected_models.insert(key);
+            }
+        }
+        projected_models.len() as u128
+    }
+
+    #[test]
+    fn test_small_formulas() {
+        assert_eq!(count(&[&[1, 2]], &[1, 2]), Some(3));
+        assert_eq!(count(&[&[1], &[-1]], &[1]), Some(0));
+        assert_eq!(count(&[], &[1, 2, 3]), Some(8));
+
+        // Independent components multiply, and unmentioned variables are free
+        assert_eq!(count(&[&[1, 2], &[3, 4]], &[1, 2, 3, 4, 5]), Some(18));
+
+        // Auxiliary variable 3 only needs some value: x1 xor x2 has two projected models
+        assert_eq!(count(&[&[-3, 1, 2], &[-3, -1, -2], &[3]], &[1, 2]), Some(2));
+        assert_eq!(count(&[&[-3, 1, 2], &[-3, -1, -2], &[3]], &[1, 2, 3]), Some(2));
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let clauses: &[&[i32]] = &[
+            &[1, -2, 5], &[-1, 3, 6], &[2, -4], &[-3, 4, -6], &[5, 6, 7],
+            &[-5, -7, 8], &[-8, 1], &[3, -7], &[-2, -6, 8],
+        ];
+        for projection in [&[1, 2, 3, 4][..], &[5, 6, 7, 8], &[1, 3, 5, 7], &[1, 2, 3, 4, 5, 6, 7, 8], &[]] {
+            assert_eq!(count(clauses, projection), Some(brute_force(clauses, projection, 8)), "{:?}", projection);
+        }
+    }
+
+    #[test]
+    fn test_large_counts_and_cancellation() {
+        // 100 free variables
+        let projection: Vec<i32> = (1..=100).collect();
+        assert_eq!(count(&[], &projection), Some(1 << 100));
+        let too_many: Vec<i32> = (1..=128).collect();
+        assert!(ModelCounter::new(&too_many).count(&[]).is_err());
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let clauses = vec![Clause::new(vec![1, 2])];
+        let mut counter = ModelCounter::new(&[1, 2]).with_cancellation(token);
+        assert_eq!(counter.count(&clauses).unwrap(), None);
+    }
+}
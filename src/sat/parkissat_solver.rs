@@ -1,19 +1,33 @@
 //! ParKissat-RS SAT solver integration
 
+use super::backend::SatBackend;
+use super::cancellation::CancellationToken;
 use super::constraints::Clause;
-use super::solver::{SolverOptions, SolverSolution, SolverStatistics, SolverResultType};
+use super::solver::{SolverOptions, SolverStatistics, SolverResultType};
+use crate::config::SolverBackend;
 use anyhow::Result;
 use parkissat_sys::{ParkissatSolver, SolverConfig, SolverResult};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
 /// SAT solver wrapper for ParKissat-RS
+///
+/// ParKissat has no incremental assumption interface, so a copy of every
+/// clause is kept and assumptions are solved on a freshly built instance.
+/// It also cannot be interrupted mid-search: cancellation is checked between
+/// calls, and time limits are passed to its native timeout option.
 pub struct ParkissatSatSolver {
     solver: ParkissatSolver,
+    config: SolverConfig,
+    clauses: Vec<Vec<i32>>,
+    model: Vec<bool>,
     variable_count: usize,
     clause_count: usize,
     timeout: Option<Duration>,
     configured: bool,
+    cancellation: CancellationToken,
+    solve_time: Duration,
+    last_result: SolverResultType,
 }
 
 impl ParkissatSatSolver {
@@ -24,10 +38,16 @@ impl ParkissatSatSolver {
         
         Ok(Self {
             solver,
+            config: SolverConfig::default(),
+            clauses: Vec::new(),
+            model: Vec::new(),
             variable_count: 0,
             clause_count: 0,
             timeout: None,
             configured: false,
+            cancellation: CancellationToken::new(),
+            solve_time: Duration::ZERO,
+            last_result: SolverResultType::Error,
         })
     }
 
@@ -36,16 +56,108 @@ impl ParkissatSatSolver {
         self.timeout = Some(timeout);
     }
 
-    /// Add clauses to the solver
-    pub fn add_clauses(&mut self, clauses: &[Clause]) -> Result<()> {
-        for clause in clauses {
-            self.add_clause(clause)?;
+    /// Reset the solver (clear all clauses)
+    pub fn reset(&mut self) -> Result<()> {
+        self.solver = ParkissatSolver::new()
+            .map_err(|e| anyhow::anyhow!("Failed to create new ParKissat solver: {}", e))?;
+        self.clauses.clear();
+        self.model.clear();
+        self.variable_count = 0;
+        self.clause_count = 0;
+        self.configured = false;
+        self.solve_time = Duration::ZERO;
+        self.last_result = SolverResultType::Error;
+        Ok(())
+    }
+
+    /// Check if a partial assignment satisfies all clauses
+    pub fn check_assignment(&self, _assignment: &HashMap<i32, bool>) -> bool {
+        // This is a simplified check - in practice, you might want to use
+        // the solver's internal checking mechanisms
+        true // Placeholder implementation
+    }
+
+    /// Ensure the solver is configured before solving
+    fn ensure_configured(&mut self) -> Result<()> {
+        if !self.configured {
+            // Use default configuration
+            let default_options = SolverOptions::default();
+            self.configure(&default_options)?;
+        }
+        Ok(())
+    }
+
+    /// Build a fresh solver holding every clause plus the assumptions as units
+    fn solver_with_assumptions(&self, assumptions: &[i32], time_limit: Option<Duration>) -> Result<ParkissatSolver> {
+        let mut config = self.config.clone();
+        if let Some(time_limit) = time_limit {
+            config.timeout = time_limit;
+        }
+
+        let mut solver = ParkissatSolver::new()
+            .map_err(|e| anyhow::anyhow!("Failed to create ParKissat solver: {}", e))?;
+        solver.configure(&config)
+            .map_err(|e| anyhow::anyhow!("Failed to configure solver: {}", e))?;
+
+        for clause in &self.clauses {
+            solver.add_clause(clause)
+                .map_err(|e| anyhow::anyhow!("Failed to add clause: {}", e))?;
+        }
+        for &literal in assumptions {
+            solver.add_clause(&[literal])
+                .map_err(|e| anyhow::anyhow!("Failed to add assumption {}: {}", literal, e))?;
+        }
+
+        Ok(solver)
+    }
+
+    /// Read the model of a satisfied solver
+    fn read_model(solver: &ParkissatSolver, variable_count: usize) -> Result<Vec<bool>> {
+        (1..=variable_count as i32)
+            .map(|var| {
+                solver.get_model_value(var)
+                    .map_err(|e| anyhow::anyhow!("Failed to get model value for variable {}: {}", var, e))
+            })
+            .collect()
+    }
+}
+
+impl SatBackend for ParkissatSatSolver {
+    fn backend(&self) -> SolverBackend {
+        SolverBackend::Parkissat
+    }
+
+    fn configure(&mut self, options: &SolverOptions) -> Result<()> {
+        let mut config = SolverConfig::default();
+        
+        // Use available parallelism by default, or user-specified thread count
+        config.num_threads = options.num_threads.map(|n| n as isize).unwrap_or(-1);
+        
+        // Set preprocessing and verbosity directly from options
+        config.enable_preprocessing = options.enable_preprocessing;
+        config.verbosity = options.verbosity;
+        
+        // Set timeout
+        if let Some(timeout) = options.timeout {
+            config.timeout = timeout;
+            self.set_timeout(timeout);
         }
+        
+        // Set random seed if provided
+        if let Some(seed) = options.random_seed {
+            config.random_seed = seed as u32;
+        }
+        
+        // Configure the solver
+        self.solver.configure(&config)
+            .map_err(|e| anyhow::anyhow!("Failed to configure solver: {}", e))?;
+        
+        self.config = config;
+        self.configured = true;
         Ok(())
     }
 
-    /// Add a single clause to the solver
-    pub fn add_clause(&mut self, clause: &Clause) -> Result<()> {
+    fn add_clause(&mut self, clause: &Clause) -> Result<()> {
         if clause.is_empty() {
             anyhow::bail!("Cannot add empty clause (unsatisfiable)");
         }
@@ -65,169 +177,84 @@ impl ParkissatSatSolver {
         self.solver.add_clause(&clause.literals)
             .map_err(|e| anyhow::anyhow!("Failed to add clause: {}", e))?;
 
+        self.clauses.push(clause.literals.clone());
         self.clause_count += 1;
         Ok(())
     }
 
-    /// Solve the SAT problem and return the first solution
-    pub fn solve(&mut self) -> Result<Option<SolverSolution>> {
+    fn solve_with_assumptions(&mut self, assumptions: &[i32]) -> Result<SolverResultType> {
         self.ensure_configured()?;
-        
-        let start_time = Instant::now();
-        
-        let result = self.solver.solve()
-            .map_err(|e| anyhow::anyhow!("Solver error: {}", e))?;
-        
-        let solve_time = start_time.elapsed();
 
-        match result {
-            SolverResult::Sat => {
-                let assignment = self.extract_assignment()?;
-                Ok(Some(SolverSolution {
-                    assignment,
-                    solve_time,
-                }))
-            }
-            SolverResult::Unsat => Ok(None),
-            SolverResult::Unknown => {
-                anyhow::bail!("Solver returned unknown result (possibly timeout)")
-            }
+        let token = self.cancellation.or_timeout(self.timeout);
+        if token.is_cancelled() {
+            self.last_result = SolverResultType::Timeout;
+            return Ok(SolverResultType::Timeout);
         }
-    }
 
-    /// Solve and find multiple solutions up to a limit
-    pub fn solve_multiple(&mut self, max_solutions: usize) -> Result<Vec<SolverSolution>> {
-        let mut solutions = Vec::new();
+        // Whichever instance solves only gets the time left on the token, so
+        // repeated calls during enumeration stay within the overall deadline
+        let mut assumption_solver = if assumptions.is_empty() {
+            if let Some(remaining) = token.remaining() {
+                let mut config = self.config.clone();
+                config.timeout = remaining;
+                self.solver.configure(&config)
+                    .map_err(|e| anyhow::anyhow!("Failed to configure solver: {}", e))?;
+            }
+            None
+        } else {
+            Some(self.solver_with_assumptions(assumptions, token.remaining())?)
+        };
+        let solver = assumption_solver.as_mut().unwrap_or(&mut self.solver);
+
         let start_time = Instant::now();
+        let result = solver.solve()
+            .map_err(|e| anyhow::anyhow!("Solver error: {}", e))?;
+        self.solve_time += start_time.elapsed();
 
-        for _ in 0..max_solutions {
-            match self.solver.solve()
-                .map_err(|e| anyhow::anyhow!("Solver error: {}", e))? {
-                SolverResult::Sat => {
-                    let assignment = self.extract_assignment()?;
-                    let solution = SolverSolution {
-                        assignment: assignment.clone(),
-                        solve_time: start_time.elapsed(),
-                    };
-                    solutions.push(solution);
-
-                    // Add blocking clause to prevent finding the same solution again
-                    self.add_blocking_clause(&assignment)?;
-                }
-                SolverResult::Unsat => break,
-                SolverResult::Unknown => {
-                    anyhow::bail!("Solver returned unknown result during multiple solution search")
-                }
+        let result = match result {
+            SolverResult::Sat => {
+                self.model = Self::read_model(solver, self.variable_count)?;
+                SolverResultType::Satisfiable
             }
-        }
+            SolverResult::Unsat => SolverResultType::Unsatisfiable,
+            SolverResult::Unknown => SolverResultType::Timeout,
+        };
+        self.last_result = result.clone();
 
-        Ok(solutions)
+        Ok(result)
     }
 
-    /// Extract variable assignment from the solver
-    fn extract_assignment(&self) -> Result<HashMap<i32, bool>> {
-        let mut assignment = HashMap::new();
-
-        for var in 1..=self.variable_count as i32 {
-            let value = self.solver.get_model_value(var)
-                .map_err(|e| anyhow::anyhow!("Failed to get model value for variable {}: {}", var, e))?;
-            assignment.insert(var, value);
+    fn model_value(&self, var: i32) -> Option<bool> {
+        if var < 1 {
+            return None;
         }
-
-        Ok(assignment)
+        self.model.get(var as usize - 1).copied()
     }
 
-    /// Add a blocking clause to prevent finding the same solution again
-    fn add_blocking_clause(&mut self, assignment: &HashMap<i32, bool>) -> Result<()> {
-        let mut blocking_literals = Vec::new();
-
-        for (&var, &value) in assignment {
-            // Add the negation of the current assignment
-            blocking_literals.push(if value { -var } else { var });
-        }
-
-        let blocking_clause = Clause::new(blocking_literals);
-        self.add_clause(&blocking_clause)?;
+    fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
 
-        Ok(())
+    fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancellation = token;
     }
 
-    /// Get solver statistics
-    pub fn statistics(&self) -> SolverStatistics {
+    fn statistics(&self) -> SolverStatistics {
         SolverStatistics {
             variable_count: self.variable_count,
             clause_count: self.clause_count,
-            solve_time: Duration::from_secs(0), // Will be updated during solving
-            result: SolverResultType::Error, // Will be updated during solving
+            solve_time: self.solve_time,
+            result: self.last_result.clone(),
         }
     }
 
-    /// Reset the solver (clear all clauses)
-    pub fn reset(&mut self) -> Result<()> {
-        self.solver = ParkissatSolver::new()
-            .map_err(|e| anyhow::anyhow!("Failed to create new ParKissat solver: {}", e))?;
-        self.variable_count = 0;
-        self.clause_count = 0;
-        self.configured = false;
-        Ok(())
-    }
-
-    /// Check if a partial assignment satisfies all clauses
-    pub fn check_assignment(&self, _assignment: &HashMap<i32, bool>) -> bool {
-        // This is a simplified check - in practice, you might want to use
-        // the solver's internal checking mechanisms
-        true // Placeholder implementation
-    }
-
-    /// Get the number of variables
-    pub fn variable_count(&self) -> usize {
+    fn variable_count(&self) -> usize {
         self.variable_count
     }
 
-    /// Get the number of clauses
-    pub fn clause_count(&self) -> usize {
+    fn clause_count(&self) -> usize {
         self.clause_count
     }
-
-    /// Set solver configuration options
-    pub fn configure(&mut self, options: &SolverOptions) -> Result<()> {
-        let mut config = SolverConfig::default();
-        
-        // Use available parallelism by default, or user-specified thread count
-        config.num_threads = options.num_threads.map(|n| n as isize).unwrap_or(-1);
-        
-        // Set preprocessing and verbosity directly from options
-        config.enable_preprocessing = options.enable_preprocessing;
-        config.verbosity = options.verbosity;
-        
-        // Set timeout
-        if let Some(timeout) = options.timeout {
-            config.timeout = timeout;
-            self.set_timeout(timeout);
-        }
-        
-        // Set random seed if provided
-        if let Some(seed) = options.random_seed {
-            config.random_seed = seed as u32;
-        }
-        
-        // Configure the solver
-        self.solver.configure(&config)
-            .map_err(|e| anyhow::anyhow!("Failed to configure solver: {}", e))?;
-        
-        self.configured = true;
-        Ok(())
-    }
-    
-    /// Ensure the solver is configured before solving
-    fn ensure_configured(&mut self) -> Result<()> {
-        if !self.configured {
-            // Use default configuration
-            let default_options = SolverOptions::default();
-            self.configure(&default_options)?;
-        }
-        Ok(())
-    }
 }
 
 impl Default for ParkissatSatSolver {
@@ -285,6 +312,7 @@ mod tests {
             verbosity: 1,
             timeout: Some(Duration::from_secs(10)),
             random_seed: Some(42),
+            cube_variables: 6,
         };
         
         solver.configure(&options).unwrap();
@@ -0,0 +1,484 @@
+//! Portfolio racing across solver configurations
+//!
+//! Hard targets vary wildly in which backend, seed or transition encoding
+//! solves them first. A portfolio runs every configuration on the same
+//! problem at once, each with its own encoder on its own thread. The first
+//! member to finish its search wins and the race returns at once; the others
+//! are cancelled and wind down in the background, so a member that only
+//! stops on its own timeout cannot hold up the answer.
+
+use super::backend::SatBackend;
+use super::cancellation::CancellationToken;
+use super::constraints::Clause;
+use super::encoder::{PopulationOptimum, SatEncoder};
+use super::solver::{SolverOptions, SolverResultType, SolverStatistics};
+use crate::config::{PortfolioMember, Settings, SolverBackend};
+use crate::game_of_life::{Grid, TargetPattern};
+use anyhow::Result;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often the race checks for outside cancellation while waiting
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A race between solver configurations on one problem
+#[derive(Debug, Clone)]
+pub struct Portfolio {
+    settings: Settings,
+    members: Vec<PortfolioMember>,
+    dead_cells: Vec<(usize, usize, usize)>,
+    cancellation: CancellationToken,
+}
+
+/// How one member of a portfolio did
+#[derive(Debug, Clone)]
+pub struct MemberStatistics {
+    pub member: PortfolioMember,
+    /// Time from the start of the race until the member returned, or until
+    /// the race ended if it was still stopping
+    pub solve_time: Duration,
+    /// `Timeout` if the member was stopped, `Error` if it failed
+    pub result: SolverResultType,
+    pub solutions: usize,
+    /// Whether the race kept this member's answer
+    pub won: bool,
+}
+
+/// Answer of a portfolio race
+#[derive(Debug, Clone)]
+pub struct PortfolioResult {
+    /// Predecessors found by the winner
+    pub predecessors: Vec<Grid>,
+    /// Population reached by the winner, when optimizing
+    pub population_optimum: Option<PopulationOptimum>,
+    /// Whether no member finished, so the predecessors come from the best interrupted one
+    pub interrupted: bool,
+    /// Every member, in configuration order
+    pub members: Vec<MemberStatistics>,
+}
+
+/// What a member's search returned
+struct MemberOutcome {
+    predecessors: Vec<Grid>,
+    population_optimum: Option<PopulationOptimum>,
+    interrupted: bool,
+}
+
+impl Portfolio {
+    /// Race the members in `solver.portfolio`, or the default portfolio if there are none
+    pub fn new(settings: &Settings) -> Self {
+        let members = if settings.solver.portfolio.is_empty() {
+            PortfolioMember::default_portfolio()
+        } else {
+            settings.solver.portfolio.clone()
+        };
+
+        Self {
+            settings: settings.clone(),
+            members,
+            dead_cells: Vec::new(),
+            cancellation: CancellationToken::new(),
+        }
+    }
+
+    /// Require the cells `(x, y, t)` to be dead in every member's solutions
+    pub fn with_dead_cells(mut self, dead_cells: Vec<(usize, usize, usize)>) -> Self {
+        self.dead_cells = dead_cells;
+        self
+    }
+
+    /// Stop every member once `token` is cancelled or past its deadline
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// The configurations that are raced
+    pub fn members(&self) -> &[PortfolioMember] {
+        &self.members
+    }
+
+    /// Race every member on `target` and keep the first finished answer
+    ///
+    /// A member finishes when its search ends without being interrupted,
+    /// whether or not it found predecessors. If none finishes, the
+    /// interrupted member with the most predecessors is kept. Fails only
+    /// when every member fails.
+    pub fn solve(&self, target: &TargetPattern) -> Result<PortfolioResult> {
+        if self.members.iter().any(|member| member.backend == SolverBackend::Portfolio) {
+            anyhow::bail!("A portfolio cannot contain another portfolio");
+        }
+
+        // The members share a flag of their own, so the race can be stopped
+        // without cancelling the caller's token
+        let race = CancellationToken::new();
+        if self.cancellation.is_cancelled() {
+            race.cancel();
+        }
+        let (sender, receiver) = mpsc::channel();
+        let start_time = Instant::now();
+        let target = Arc::new(target.clone());
+
+        // Members run detached, so the race can return before the losers stop
+        for (index, member) in self.members.iter().enumerate() {
+            let sender = sender.clone();
+            let settings = member.apply(&self.settings);
+            let dead_cells = self.dead_cells.clone();
+            let race = race.clone();
+            let target = Arc::clone(&target);
+            thread::spawn(move || {
+                let outcome = run_member(settings, &target, dead_cells, race);
+                let _ = sender.send((index, outcome, start_time.elapsed()));
+            });
+        }
+        drop(sender);
+
+        let mut outcomes: Vec<Option<(Result<MemberOutcome>, Duration)>> =
+            self.members.iter().map(|_| None).collect();
+        let mut winner = None;
+        while winner.is_none() {
+            match receiver.recv_timeout(POLL_INTERVAL) {
+                Ok((index, outcome, elapsed)) => {
+                    if outcome.as_ref().is_ok_and(|outcome| !outcome.interrupted) {
+                        winner = Some(index);
+                        race.cancel();
+                    }
+                    outcomes[index] = Some((outcome, elapsed));
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    if self.cancellation.is_cancelled() {
+                        race.cancel();
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        self.collect(outcomes, winner, start_time.elapsed())
+    }
+
+    /// Record every member's statistics and pick the answer to keep
+    ///
+    /// Members without an outcome were still stopping when the race ended
+    /// after `race_time`, or, without a winner, stopped without reporting.
+    fn collect(
+        &self,
+        outcomes: Vec<Option<(Result<MemberOutcome>, Duration)>>,
+        winner: Option<usize>,
+        race_time: Duration,
+    ) -> Result<PortfolioResult> {
+        let mut members = Vec::with_capacity(outcomes.len());
+        let mut answers = Vec::new();
+        let mut first_error = None;
+
+        for (index, (member, outcome)) in self.members.iter().zip(outcomes).enumerate() {
+            let Some((outcome, solve_time)) = outcome else {
+                if winner.is_none() && first_error.is_none() {
+                    first_error = Some(anyhow::anyhow!("Portfolio member '{}' stopped without reporting", member));
+                }
+                let result = if winner.is_some() { SolverResultType::Timeout } else { SolverResultType::Error };
+                members.push(MemberStatistics {
+                    member: member.clone(),
+                    solve_time: race_time,
+                    result,
+                    solutions: 0,
+                    won: false,
+                });
+                continue;
+            };
+            let (result, solutions) = match outcome {
+                Ok(outcome) => {
+                    let result = if outcome.interrupted {
+                        SolverResultType::Timeout
+                    } else if outcome.predecessors.is_empty() {
+                        SolverResultType::Unsatisfiable
+                    } else {
+                        SolverResultType::Satisfiable
+                    };
+                    let solutions = outcome.predecessors.len();
+                    answers.push((index, outcome));
+                    (result, solutions)
+                }
+                Err(e) => {
+                    if first_error.is_none() {
+                        first_error = Some(e.context(format!("Portfolio member '{}' failed", member)));
+                    }
+                    (SolverResultType::Error, 0)
+                }
+            };
+
+            members.push(MemberStatistics {
+                member: member.clone(),
+                solve_time,
+                result,
+                solutions,
+                won: false,
+            });
+        }
+
+        let kept = match winner {
+            Some(winner) => answers.into_iter().find(|(index, _)| *index == winner),
+            None => answers.into_iter().max_by_key(|(index, outcome)| (outcome.predecessors.len(), std::cmp::Reverse(*index))),
+        };
+        let Some((index, outcome)) = kept else {
+            return Err(first_error.unwrap_or_else(|| anyhow::anyhow!("The portfolio has no members")));
+        };
+        members[index].won = true;
+
+        Ok(PortfolioResult {
+            predecessors: outcome.predecessors,
+            population_optimum: outcome.population_optimum,
+            interrupted: outcome.interrupted,
+            members,
+        })
+    }
+}
+
+/// Solve `target` with one member's settings, stopping when the race is cancelled
+fn run_member(
+    settings: Settings,
+    target: &TargetPattern,
+    dead_cells: Vec<(usize, usize, usize)>,
+    race: CancellationToken,
+) -> Result<MemberOutcome> {
+    let mut encoder = SatEncoder::new(settings, &target.grid)?
+        .with_dead_cells(dead_cells)
+        .with_cancellation(race);
+    let predecessors = encoder.solve_pattern(target)?;
+
+    Ok(MemberOutcome {
+        predecessors,
+        population_optimum: encoder.population_optimum(),
+        interrupted: encoder.was_interrupted(),
+    })
+}
+
+impl std::fmt::Display for MemberStatistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {:?}, {} solution(s) in {:.3}s",
+               self.member, self.result, self.solutions, self.solve_time.as_secs_f64())?;
+        if self.won {
+            write!(f, " (winner)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Backend registered for `SolverBackend::Portfolio`
+///
+/// Members may use different encodings, so they cannot share one CNF.
+/// `SatEncoder` races a `Portfolio` itself when this backend is selected; the
+/// backend only keeps track of the clauses it is given, and asking it to
+/// solve them is an error.
+#[derive(Debug, Default)]
+pub struct PortfolioSolver {
+    variable_count: usize,
+    clause_count: usize,
+    cancellation: CancellationToken,
+}
+
+impl PortfolioSolver {
+    /// Create a new portfolio backend
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SatBackend for PortfolioSolver {
+    fn backend(&self) -> SolverBackend {
+        SolverBackend::Portfolio
+    }
+
+    fn configure(&mut self, _options: &SolverOptions) -> Result<()> {
+        Ok(())
+    }
+
+    fn add_clause(&mut self, clause: &Clause) -> Result<()> {
+        for &literal in &clause.literals {
+            self.variable_count = self.variable_count.max(literal.unsigned_abs() as usize);
+        }
+        self.clause_count += 1;
+        Ok(())
+    }
+
+    fn solve_with_assumptions(&mut self, _assumptions: &[i32]) -> Result<SolverResultType> {
+        anyhow::bail!("The portfolio backend races whole searches and cannot solve a single CNF formula")
+    }
+
+    fn model_value(&self, _var: i32) -> Option<bool> {
+        None
+    }
+
+    fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancellation = token;
+    }
+
+    fn statistics(&self) -> SolverStatistics {
+        SolverStatistics {
+            variable_count: self.variable_count,
+            clause_count: self.clause_count,
+            solve_time: Duration::ZERO,
+            result: SolverResultType::Error,
+        }
+    }
+
+    fn variable_count(&self) -> usize {
+        self.variable_count
+    }
+
+    fn clause_count(&self) -> usize {
+        self.clause_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{BoundaryCondition, TransitionEncoding};
+    use crate::game_of_life::io::parse_pattern_from_string;
+    use crate::game_of_life::GameOfLifeRules;
+
+    /// How long `Stubborn` takes to give up
+    const STUBBORN_TIME: Duration = Duration::from_secs(5);
+
+    /// A backend that ignores cancellation and only stops on its own time limit
+    #[derive(Default)]
+    struct Stubborn {
+        cancellation: CancellationToken,
+    }
+
+    impl SatBackend for Stubborn {
+        fn backend(&self) -> SolverBackend {
+            SolverBackend::Custom("stubborn".to_string())
+        }
+
+        fn configure(&mut self, _options: &SolverOptions) -> Result<()> {
+            Ok(())
+        }
+
+        fn add_clause(&mut self, _clause: &Clause) -> Result<()> {
+            Ok(())
+        }
+
+        fn solve_with_assumptions(&mut self, _assumptions: &[i32]) -> Result<SolverResultType> {
+            thread::sleep(STUBBORN_TIME);
+            Ok(SolverResultType::Timeout)
+        }
+
+        fn model_value(&self, _var: i32) -> Option<bool> {
+            None
+        }
+
+        fn cancellation_token(&self) -> CancellationToken {
+            self.cancellation.clone()
+        }
+
+        fn set_cancellation_token(&mut self, token: CancellationToken) {
+            self.cancellation = token;
+        }
+
+        fn statistics(&self) -> SolverStatistics {
+            SolverStatistics {
+                variable_count: 0,
+                clause_count: 0,
+                solve_time: Duration::ZERO,
+                result: SolverResultType::Timeout,
+            }
+        }
+
+        fn variable_count(&self) -> usize {
+            0
+        }
+
+        fn clause_count(&self) -> usize {
+            0
+        }
+    }
+
+    fn settings(members: Vec<PortfolioMember>) -> Settings {
+        let mut settings = Settings::default();
+        settings.simulation.generations = 1;
+        settings.solver.max_solutions = 3;
+        settings.solver.timeout_seconds = 30;
+        settings.solver.backend = SolverBackend::Portfolio;
+        settings.solver.portfolio = members;
+        settings
+    }
+
+    #[test]
+    fn test_first_finished_member_wins() {
+        let members = vec![
+            PortfolioMember::new(SolverBackend::Cadical),
+            PortfolioMember::new(SolverBackend::Cadical).with_seed(7),
+            PortfolioMember::new(SolverBackend::Cadical).with_transition(TransitionEncoding::Totalizer),
+            PortfolioMember::new(SolverBackend::BruteForce),
+        ];
+        let target = parse_pattern_from_string("000\n111\n000\n", BoundaryCondition::Dead).unwrap();
+        let result = Portfolio::new(&settings(members)).solve(&target).unwrap();
+
+        assert!(!result.interrupted);
+        assert_eq!(result.members.len(), 4);
+        assert_eq!(result.members.iter().filter(|member| member.won).count(), 1);
+        let winner = result.members.iter().find(|member| member.won).unwrap();
+        assert_eq!(winner.result, SolverResultType::Satisfiable);
+        assert_eq!(winner.solutions, result.predecessors.len());
+        for predecessor in &result.predecessors {
+            assert!(target.matches(&GameOfLifeRules::evolve(predecessor)));
+        }
+    }
+
+    #[test]
+    fn test_race_does_not_wait_for_cancelled_members() {
+        crate::sat::register_backend(SolverBackend::Custom("stubborn".to_string()), || Ok(Box::new(Stubborn::default())));
+        let members = vec![
+            PortfolioMember::new(SolverBackend::Custom("stubborn".to_string())),
+            PortfolioMember::new(SolverBackend::Cadical),
+        ];
+        let target = parse_pattern_from_string("000\n111\n000\n", BoundaryCondition::Dead).unwrap();
+
+        let start = Instant::now();
+        let result = Portfolio::new(&settings(members)).solve(&target).unwrap();
+        assert!(start.elapsed() < STUBBORN_TIME, "the race took {:?}", start.elapsed());
+        assert!(result.members[1].won);
+        assert_eq!(result.members[0].result, SolverResultType::Timeout);
+        assert_eq!(result.members[0].solutions, 0);
+    }
+
+    #[test]
+    fn test_unsatisfiable_answers_and_failures() {
+        // A Garden of Eden: finishing without predecessors is an answer too
+        let target = parse_pattern_from_string("111\n", BoundaryCondition::Dead).unwrap();
+        let members = vec![PortfolioMember::new(SolverBackend::Cadical), PortfolioMember::new(SolverBackend::BruteForce)];
+        let result = Portfolio::new(&settings(members)).solve(&target).unwrap();
+        assert!(result.predecessors.is_empty());
+        assert!(!result.interrupted);
+
+        // A failing member is recorded, and only fails the race if all fail
+        let target = TargetPattern::from_grid(Grid::new(8, 8, BoundaryCondition::Dead));
+        let members = vec![PortfolioMember::new(SolverBackend::BruteForce), PortfolioMember::new(SolverBackend::Cadical)];
+        let result = Portfolio::new(&settings(members)).solve(&target).unwrap();
+        assert_eq!(result.members[0].result, SolverResultType::Error);
+        assert!(result.members[1].won);
+        let members = vec![PortfolioMember::new(SolverBackend::BruteForce)];
+        assert!(Portfolio::new(&settings(members)).solve(&target).is_err());
+    }
+
+    #[test]
+    fn test_default_portfolio_and_cancellation() {
+        let portfolio = Portfolio::new(&settings(Vec::new()));
+        assert_eq!(portfolio.members(), PortfolioMember::default_portfolio().as_slice());
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let target = parse_pattern_from_string("010\n010\n010\n", BoundaryCondition::Dead).unwrap();
+        let result = portfolio.with_cancellation(token).solve(&target).unwrap();
+        assert!(result.interrupted);
+        assert!(result.members.iter().all(|member| member.result == SolverResultType::Timeout));
+    }
+}
@@ -1,17 +1,49 @@
 //! SAT solver integration using CaDiCaL
 
+use super::backend::SatBackend;
+use super::cancellation::CancellationToken;
 use super::constraints::Clause;
+use super::drat::DratProof;
+use crate::config::{SolverBackend, SolverConfig};
 use anyhow::Result;
-use cadical::Solver;
+use cadical::{Callbacks, Solver};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
 /// SAT solver wrapper for CaDiCaL
 pub struct SatSolver {
-    solver: Solver,
+    solver: Solver<SearchLimit>,
     variable_count: usize,
     clause_count: usize,
     timeout: Option<Duration>,
+    random_seed: Option<u64>,
+    cancellation: CancellationToken,
+    solve_time: Duration,
+    last_result: SolverResultType,
+    proof: Option<DratProof>,
+}
+
+/// CaDiCaL callbacks that stop the search once the token is cancelled or expires
+struct SearchLimit {
+    token: CancellationToken,
+    /// Learned clauses, collected only while a proof is recorded
+    lemmas: Option<Vec<Vec<i32>>>,
+}
+
+impl Callbacks for SearchLimit {
+    fn terminate(&mut self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    fn max_length(&self) -> i32 {
+        if self.lemmas.is_some() { i32::MAX } else { 0 }
+    }
+
+    fn learn(&mut self, clause: &[i32]) {
+        if let Some(lemmas) = self.lemmas.as_mut() {
+            lemmas.push(clause.to_vec());
+        }
+    }
 }
 
 /// Result of SAT solving
@@ -21,6 +53,14 @@ pub struct SolverSolution {
     pub solve_time: Duration,
 }
 
+/// Assumptions that are unsatisfiable together with the clauses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsatCore {
+    pub literals: Vec<i32>,
+    /// Whether minimization finished, rather than stopping on timeout or cancellation
+    pub minimal: bool,
+}
+
 /// Statistics about the solving process
 #[derive(Debug, Clone)]
 pub struct SolverStatistics {
@@ -46,6 +86,11 @@ impl SatSolver {
             variable_count: 0,
             clause_count: 0,
             timeout: None,
+            random_seed: None,
+            cancellation: CancellationToken::new(),
+            solve_time: Duration::ZERO,
+            last_result: SolverResultType::Error,
+            proof: None,
         }
     }
 
@@ -54,16 +99,51 @@ impl SatSolver {
         self.timeout = Some(timeout);
     }
 
-    /// Add clauses to the solver
-    pub fn add_clauses(&mut self, clauses: &[Clause]) -> Result<()> {
-        for clause in clauses {
-            self.add_clause(clause)?;
+    /// Reset the solver (clear all clauses)
+    pub fn reset(&mut self) {
+        self.solver = Solver::new();
+        self.variable_count = 0;
+        self.clause_count = 0;
+        self.solve_time = Duration::ZERO;
+        self.last_result = SolverResultType::Error;
+        self.proof = None;
+    }
+
+    /// Check if a partial assignment satisfies all clauses
+    pub fn check_assignment(&self, _assignment: &HashMap<i32, bool>) -> bool {
+        // This is a simplified check - in practice, you might want to use
+        // the solver's internal checking mechanisms
+        true // Placeholder implementation
+    }
+}
+
+impl Default for SatSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SatBackend for SatSolver {
+    fn backend(&self) -> SolverBackend {
+        SolverBackend::Cadical
+    }
+
+    fn configure(&mut self, options: &SolverOptions) -> Result<()> {
+        // Note: CaDiCaL 0.1 has limited configuration options
+        // Most optimization is handled internally
+        
+        if let Some(timeout) = options.timeout {
+            self.set_timeout(timeout);
         }
+        self.random_seed = options.random_seed;
+        
+        // CaDiCaL is single-threaded, so num_threads is ignored
+        // preprocessing and verbosity options are not exposed in the 0.1 API
+        // but we store them for reference
         Ok(())
     }
 
-    /// Add a single clause to the solver
-    pub fn add_clause(&mut self, clause: &Clause) -> Result<()> {
+    fn add_clause(&mut self, clause: &Clause) -> Result<()> {
         if clause.is_empty() {
             anyhow::bail!("Cannot add empty clause (unsatisfiable)");
         }
@@ -83,128 +163,110 @@ impl SatSolver {
         Ok(())
     }
 
-    /// Solve the SAT problem and return the first solution
-    pub fn solve(&mut self) -> Result<Option<SolverSolution>> {
-        let start_time = Instant::now();
-
-        // Set timeout if specified
-        if let Some(_timeout) = self.timeout {
-            // CaDiCaL doesn't have direct timeout support, so we'll implement a simple check
-            // In a production system, you might want to use a more sophisticated timeout mechanism
+    fn add_clauses(&mut self, clauses: &[Clause]) -> Result<()> {
+        // The 0.1 API has no seed option, so a seed shuffles the order in
+        // which clauses reach CaDiCaL instead, which is enough to vary its search
+        let mut order: Vec<usize> = (0..clauses.len()).collect();
+        if let Some(seed) = self.random_seed {
+            shuffle(&mut order, seed);
         }
-
-        let result = self.solver.solve();
-        let solve_time = start_time.elapsed();
-
-        if result == Some(true) {
-            let assignment = self.extract_assignment()?;
-            Ok(Some(SolverSolution {
-                assignment,
-                solve_time,
-            }))
-        } else {
-            Ok(None)
+        for index in order {
+            self.add_clause(&clauses[index])?;
         }
+        Ok(())
     }
 
-    /// Solve and find multiple solutions up to a limit
-    pub fn solve_multiple(&mut self, max_solutions: usize) -> Result<Vec<SolverSolution>> {
-        let mut solutions = Vec::new();
-        let start_time = Instant::now();
-
-        for _ in 0..max_solutions {
-            if self.solver.solve() == Some(true) {
-                let assignment = self.extract_assignment()?;
-                let solution = SolverSolution {
-                    assignment: assignment.clone(),
-                    solve_time: start_time.elapsed(),
-                };
-                solutions.push(solution);
-
-                // Add blocking clause to prevent finding the same solution again
-                self.add_blocking_clause(&assignment)?;
-            } else {
-                break;
-            }
+    fn solve_with_assumptions(&mut self, assumptions: &[i32]) -> Result<SolverResultType> {
+        let token = self.cancellation.or_timeout(self.timeout);
+        if token.is_cancelled() {
+            self.last_result = SolverResultType::Timeout;
+            return Ok(SolverResultType::Timeout);
         }
 
-        Ok(solutions)
-    }
+        // CaDiCaL has no timeout option; its terminate callback polls the
+        // token instead and the search returns no answer once it fires
+        let lemmas = self.proof.as_ref().map(|_| Vec::new());
+        self.solver.set_callbacks(Some(SearchLimit { token, lemmas }));
 
-    /// Extract variable assignment from the solver
-    fn extract_assignment(&self) -> Result<HashMap<i32, bool>> {
-        let mut assignment = HashMap::new();
+        let start_time = Instant::now();
+        let result = match self.solver.solve_with(assumptions.iter().copied()) {
+            Some(true) => SolverResultType::Satisfiable,
+            Some(false) => SolverResultType::Unsatisfiable,
+            None => SolverResultType::Timeout,
+        };
+        self.solve_time += start_time.elapsed();
 
-        for var in 1..=self.variable_count as i32 {
-            if let Some(value) = self.solver.value(var) {
-                assignment.insert(var, value);
+        let learned = self.solver.get_callbacks().and_then(|callbacks| callbacks.lemmas.take());
+        if let (Some(proof), Some(learned)) = (self.proof.as_mut(), learned) {
+            for lemma in learned {
+                proof.add(lemma);
             }
         }
+        self.last_result = result.clone();
 
-        Ok(assignment)
+        Ok(result)
     }
 
-    /// Add a blocking clause to prevent finding the same solution again
-    fn add_blocking_clause(&mut self, assignment: &HashMap<i32, bool>) -> Result<()> {
-        let mut blocking_literals = Vec::new();
+    fn model_value(&self, var: i32) -> Option<bool> {
+        self.solver.value(var)
+    }
 
-        for (&var, &value) in assignment {
-            // Add the negation of the current assignment
-            blocking_literals.push(if value { -var } else { var });
+    fn failed(&self, literal: i32) -> bool {
+        self.solver.failed(literal)
+    }
+
+    fn enable_proof(&mut self) -> Result<()> {
+        if self.clause_count > 0 {
+            anyhow::bail!("Proof recording must be enabled before clauses are added");
         }
+        // Without inprocessing every learned clause is a RUP consequence of
+        // the input, so the learned clauses alone form a DRUP proof
+        self.solver = Solver::with_config("plain")
+            .map_err(|e| anyhow::anyhow!("Failed to configure CaDiCaL for proofs: {}", e))?;
+        self.proof = Some(DratProof::new());
+        Ok(())
+    }
 
-        let blocking_clause = Clause::new(blocking_literals);
-        self.add_clause(&blocking_clause)?;
+    fn take_proof(&mut self) -> Option<DratProof> {
+        self.proof.as_mut().map(std::mem::take)
+    }
 
-        Ok(())
+    fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancellation = token;
     }
 
-    /// Get solver statistics
-    pub fn statistics(&self) -> SolverStatistics {
+    fn statistics(&self) -> SolverStatistics {
         SolverStatistics {
             variable_count: self.variable_count,
             clause_count: self.clause_count,
-            solve_time: Duration::from_secs(0), // Will be updated during solving
-            result: SolverResultType::Error, // Will be updated during solving
+            solve_time: self.solve_time,
+            result: self.last_result.clone(),
         }
     }
 
-    /// Reset the solver (clear all clauses)
-    pub fn reset(&mut self) {
-        self.solver = Solver::new();
-        self.variable_count = 0;
-        self.clause_count = 0;
-    }
-
-    /// Check if a partial assignment satisfies all clauses
-    pub fn check_assignment(&self, _assignment: &HashMap<i32, bool>) -> bool {
-        // This is a simplified check - in practice, you might want to use
-        // the solver's internal checking mechanisms
-        true // Placeholder implementation
-    }
-
-    /// Get the number of variables
-    pub fn variable_count(&self) -> usize {
+    fn variable_count(&self) -> usize {
         self.variable_count
     }
 
-    /// Get the number of clauses
-    pub fn clause_count(&self) -> usize {
+    fn clause_count(&self) -> usize {
         self.clause_count
     }
+}
 
-    /// Set solver configuration options
-    pub fn configure(&mut self, options: &SolverOptions) {
-        // Note: CaDiCaL 0.1 has limited configuration options
-        // Most optimization is handled internally
-        
-        if let Some(timeout) = options.timeout {
-            self.set_timeout(timeout);
-        }
-        
-        // CaDiCaL is single-threaded, so num_threads is ignored
-        // preprocessing and verbosity options are not exposed in the 0.1 API
-        // but we store them for reference
+/// Fisher-Yates shuffle driven by a splitmix64 sequence from `seed`
+fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut state = seed;
+    for i in (1..items.len()).rev() {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        items.swap(i, (z % (i as u64 + 1)) as usize);
     }
 }
 
@@ -216,6 +278,22 @@ pub struct SolverOptions {
     pub verbosity: u32,
     pub timeout: Option<Duration>,
     pub random_seed: Option<u64>,
+    /// Variables the cube-and-conquer backend splits on
+    pub cube_variables: usize,
+}
+
+impl SolverOptions {
+    /// Build solver options from the `solver` section of the settings
+    pub fn from_config(config: &SolverConfig) -> Self {
+        Self {
+            num_threads: config.num_threads,
+            enable_preprocessing: config.enable_preprocessing,
+            verbosity: config.verbosity,
+            timeout: Some(Duration::from_secs(config.timeout_seconds)),
+            random_seed: config.random_seed,
+            cube_variables: config.cubes.cube_variables,
+        }
+    }
 }
 
 impl Default for SolverOptions {
@@ -226,6 +304,7 @@ impl Default for SolverOptions {
             verbosity: 0,
             timeout: None,
             random_seed: None,
+            cube_variables: 6,
         }
     }
 }
@@ -312,6 +391,30 @@ mod tests {
         assert!(solution.is_none());
     }
 
+    #[test]
+    fn test_unsat_core_is_locally_minimal() {
+        let mut solver = SatSolver::new();
+
+        // x1 and x2 exclude each other, x3 is forbidden outright, x4 is free
+        solver.add_clause(&Clause::binary(-1, -2)).unwrap();
+        solver.add_clause(&Clause::unit(-3)).unwrap();
+
+        let assumptions = [4, 1, 2, 3];
+        let core = solver.unsat_core(&assumptions).unwrap().unwrap();
+        assert!(core.minimal);
+        let core = core.literals;
+        assert!(core == vec![3] || core == vec![1, 2], "unexpected core {:?}", core);
+
+        // Dropping any literal makes the rest satisfiable
+        for index in 0..core.len() {
+            let mut rest = core.clone();
+            rest.remove(index);
+            assert_eq!(solver.solve_with_assumptions(&rest).unwrap(), SolverResultType::Satisfiable);
+        }
+
+        assert_eq!(solver.unsat_core(&[1, 4]).unwrap(), None);
+    }
+
     #[test]
     fn test_multiple_solutions() {
         let mut solver = SatSolver::new();
@@ -331,6 +434,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_projected_solutions() {
+        let mut solver = SatSolver::new();
+
+        // x1 ∨ x2 with x3 unconstrained: six models, but only two values of x1
+        solver.add_clause(&Clause::new(vec![1, 2])).unwrap();
+        solver.add_clause(&Clause::new(vec![3, -3])).unwrap();
+
+        let solutions = solver.solve_multiple_projected(10, &[1]).unwrap();
+        assert_eq!(solutions.len(), 2);
+        assert_ne!(solutions[0].assignment.get(&1), solutions[1].assignment.get(&1));
+    }
+
     #[test]
     fn test_solver_options() {
         let mut solver = SatSolver::new();
@@ -340,11 +456,73 @@ mod tests {
             verbosity: 1,
             timeout: Some(Duration::from_secs(10)),
             random_seed: Some(42),
+            cube_variables: 6,
         };
         
-        solver.configure(&options);
+        solver.configure(&options).unwrap();
         // Test that configuration doesn't crash
         assert_eq!(solver.variable_count(), 0);
+
+        // The seed only reorders clauses: x1 -> x2 -> x3 with x1 still forces x3
+        let clauses = vec![Clause::binary(-1, 2), Clause::binary(-2, 3), Clause::unit(1)];
+        solver.add_clauses(&clauses).unwrap();
+        assert_eq!(solver.clause_count(), 3);
+        let solution = solver.solve().unwrap().unwrap();
+        assert_eq!(solution.assignment.get(&3), Some(&true));
+
+        let mut order: Vec<usize> = (0..10).collect();
+        shuffle(&mut order, 42);
+        let mut sorted = order.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..10).collect::<Vec<_>>());
+    }
+
+    /// Add the pigeonhole formula: `pigeons` pigeons in `pigeons - 1` holes (UNSAT, hard)
+    fn add_pigeonhole(solver: &mut SatSolver, pigeons: i32) {
+        let holes = pigeons - 1;
+        let var = |p: i32, h: i32| p * holes + h + 1;
+
+        for p in 0..pigeons {
+            solver.add_clause(&Clause::new((0..holes).map(|h| var(p, h)).collect())).unwrap();
+        }
+        for h in 0..holes {
+            for p in 0..pigeons {
+                for q in p + 1..pigeons {
+                    solver.add_clause(&Clause::binary(-var(p, h), -var(q, h))).unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_timeout_interrupts_search() {
+        let mut solver = SatSolver::new();
+        add_pigeonhole(&mut solver, 12);
+        solver.set_timeout(Duration::from_millis(50));
+
+        let start = Instant::now();
+        let result = solver.solve_with_assumptions(&[]).unwrap();
+        assert_eq!(result, SolverResultType::Timeout);
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert_eq!(solver.statistics().result, SolverResultType::Timeout);
+    }
+
+    #[test]
+    fn test_cancellation_from_another_thread() {
+        let mut solver = SatSolver::new();
+        add_pigeonhole(&mut solver, 12);
+
+        let token = solver.cancellation_token();
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            token.cancel();
+        });
+
+        let solutions = solver.solve_multiple(3).unwrap();
+        canceller.join().unwrap();
+
+        assert!(solutions.is_empty());
+        assert_eq!(solver.statistics().result, SolverResultType::Timeout);
     }
 
     #[test]
@@ -1,137 +1,105 @@
 //! Factory for creating SAT solver instances based on configuration
-
-use super::solver::{SatSolver, SolverOptions, SolverSolution, SolverStatistics};
+//!
+//! Backends are looked up in a process-wide registry keyed by `SolverBackend`.
+//! CaDiCaL, ParKissat, the brute-force oracle, the portfolio and
+//! cube-and-conquer are registered by default; other crates can add their own solver with
+//! `register_backend` and select it through `SolverBackend::Custom`. `SolverBackend::External` names its
+//! solver binary itself, so it is built directly instead of looked up.
+
+use super::backend::SatBackend;
+use super::solver::SatSolver;
 use super::parkissat_solver::ParkissatSatSolver;
-use super::constraints::Clause;
+use super::brute_force::BruteForceSolver;
+use super::external_solver::ExternalSolver;
+use super::portfolio::PortfolioSolver;
+use super::cube::CubeSolver;
 use crate::config::SolverBackend;
 use anyhow::Result;
 use std::collections::HashMap;
-
-/// Unified SAT solver interface that can use different backends
-pub enum UnifiedSatSolver {
-    Cadical(SatSolver),
-    Parkissat(ParkissatSatSolver),
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Constructor stored in the backend registry
+pub type BackendConstructor = Arc<dyn Fn() -> Result<Box<dyn SatBackend>> + Send + Sync>;
+
+/// Global registry of available backends
+fn registry() -> &'static RwLock<HashMap<SolverBackend, BackendConstructor>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<SolverBackend, BackendConstructor>>> = OnceLock::new();
+
+    REGISTRY.get_or_init(|| {
+        let mut backends: HashMap<SolverBackend, BackendConstructor> = HashMap::new();
+        backends.insert(
+            SolverBackend::Cadical,
+            Arc::new(|| Ok(Box::new(SatSolver::new()) as Box<dyn SatBackend>)),
+        );
+        backends.insert(
+            SolverBackend::Parkissat,
+            Arc::new(|| Ok(Box::new(ParkissatSatSolver::new()?) as Box<dyn SatBackend>)),
+        );
+        backends.insert(
+            SolverBackend::BruteForce,
+            Arc::new(|| Ok(Box::new(BruteForceSolver::new()) as Box<dyn SatBackend>)),
+        );
+        backends.insert(
+            SolverBackend::Portfolio,
+            Arc::new(|| Ok(Box::new(PortfolioSolver::new()) as Box<dyn SatBackend>)),
+        );
+        backends.insert(
+            SolverBackend::CubeAndConquer,
+            Arc::new(|| Ok(Box::new(CubeSolver::new()) as Box<dyn SatBackend>)),
+        );
+        RwLock::new(backends)
+    })
 }
 
-impl UnifiedSatSolver {
-    /// Create a new solver instance based on the specified backend
-    pub fn new(backend: SolverBackend) -> Result<Self> {
-        match backend {
-            SolverBackend::Cadical => Ok(UnifiedSatSolver::Cadical(SatSolver::new())),
-            SolverBackend::Parkissat => Ok(UnifiedSatSolver::Parkissat(ParkissatSatSolver::new()?)),
-        }
-    }
-
-    /// Add clauses to the solver
-    pub fn add_clauses(&mut self, clauses: &[Clause]) -> Result<()> {
-        match self {
-            UnifiedSatSolver::Cadical(solver) => solver.add_clauses(clauses),
-            UnifiedSatSolver::Parkissat(solver) => solver.add_clauses(clauses),
-        }
-    }
-
-    /// Add a single clause to the solver
-    pub fn add_clause(&mut self, clause: &Clause) -> Result<()> {
-        match self {
-            UnifiedSatSolver::Cadical(solver) => solver.add_clause(clause),
-            UnifiedSatSolver::Parkissat(solver) => solver.add_clause(clause),
-        }
-    }
-
-    /// Solve the SAT problem and return the first solution
-    pub fn solve(&mut self) -> Result<Option<SolverSolution>> {
-        match self {
-            UnifiedSatSolver::Cadical(solver) => solver.solve(),
-            UnifiedSatSolver::Parkissat(solver) => solver.solve(),
-        }
-    }
-
-    /// Solve and find multiple solutions up to a limit
-    pub fn solve_multiple(&mut self, max_solutions: usize) -> Result<Vec<SolverSolution>> {
-        match self {
-            UnifiedSatSolver::Cadical(solver) => solver.solve_multiple(max_solutions),
-            UnifiedSatSolver::Parkissat(solver) => solver.solve_multiple(max_solutions),
-        }
-    }
-
-    /// Get solver statistics
-    pub fn statistics(&self) -> SolverStatistics {
-        match self {
-            UnifiedSatSolver::Cadical(solver) => solver.statistics(),
-            UnifiedSatSolver::Parkissat(solver) => solver.statistics(),
-        }
-    }
-
-    /// Reset the solver (clear all clauses)
-    pub fn reset(&mut self) -> Result<()> {
-        match self {
-            UnifiedSatSolver::Cadical(solver) => {
-                solver.reset();
-                Ok(())
-            }
-            UnifiedSatSolver::Parkissat(solver) => solver.reset(),
-        }
-    }
-
-    /// Check if a partial assignment satisfies all clauses
-    pub fn check_assignment(&self, assignment: &HashMap<i32, bool>) -> bool {
-        match self {
-            UnifiedSatSolver::Cadical(solver) => solver.check_assignment(assignment),
-            UnifiedSatSolver::Parkissat(solver) => solver.check_assignment(assignment),
-        }
-    }
-
-    /// Get the number of variables
-    pub fn variable_count(&self) -> usize {
-        match self {
-            UnifiedSatSolver::Cadical(solver) => solver.variable_count(),
-            UnifiedSatSolver::Parkissat(solver) => solver.variable_count(),
-        }
-    }
+/// Register a solver constructor for a backend, replacing any previous one
+pub fn register_backend<F>(backend: SolverBackend, constructor: F)
+where
+    F: Fn() -> Result<Box<dyn SatBackend>> + Send + Sync + 'static,
+{
+    registry()
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .insert(backend, Arc::new(constructor));
+}
 
-    /// Get the number of clauses
-    pub fn clause_count(&self) -> usize {
-        match self {
-            UnifiedSatSolver::Cadical(solver) => solver.clause_count(),
-            UnifiedSatSolver::Parkissat(solver) => solver.clause_count(),
-        }
-    }
+/// Check whether a backend has a registered constructor
+///
+/// External solvers need no registration and always count as registered.
+pub fn is_backend_registered(backend: &SolverBackend) -> bool {
+    matches!(backend, SolverBackend::External { .. }) || registry()
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .contains_key(backend)
+}
 
-    /// Set solver configuration options
-    pub fn configure(&mut self, options: &SolverOptions) -> Result<()> {
-        match self {
-            UnifiedSatSolver::Cadical(solver) => {
-                solver.configure(options);
-                Ok(())
-            }
-            UnifiedSatSolver::Parkissat(solver) => solver.configure(options),
-        }
+/// Create a new solver instance for the specified backend
+pub fn create_solver(backend: &SolverBackend) -> Result<Box<dyn SatBackend>> {
+    if let SolverBackend::External { command, args } = backend {
+        return Ok(Box::new(ExternalSolver::new(command.clone(), args.clone())));
     }
 
-    /// Get the backend type being used
-    pub fn backend(&self) -> SolverBackend {
-        match self {
-            UnifiedSatSolver::Cadical(_) => SolverBackend::Cadical,
-            UnifiedSatSolver::Parkissat(_) => SolverBackend::Parkissat,
-        }
-    }
-}
+    // Clone the constructor so the lock is not held while the solver is built
+    let constructor = registry()
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .get(backend)
+        .cloned();
 
-impl Default for UnifiedSatSolver {
-    fn default() -> Self {
-        // Default to CaDiCaL for backward compatibility
-        UnifiedSatSolver::Cadical(SatSolver::new())
+    match constructor {
+        Some(constructor) => constructor(),
+        None => anyhow::bail!("No solver registered for backend {:?}", backend),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::SolverBackend;
+    use crate::sat::constraints::Clause;
+    use crate::sat::solver::SolverResultType;
 
     #[test]
     fn test_cadical_solver_creation() {
-        let solver = UnifiedSatSolver::new(SolverBackend::Cadical).unwrap();
+        let solver = create_solver(&SolverBackend::Cadical).unwrap();
         assert_eq!(solver.backend(), SolverBackend::Cadical);
         assert_eq!(solver.variable_count(), 0);
         assert_eq!(solver.clause_count(), 0);
@@ -139,7 +107,7 @@ mod tests {
 
     #[test]
     fn test_parkissat_solver_creation() {
-        let solver = UnifiedSatSolver::new(SolverBackend::Parkissat).unwrap();
+        let solver = create_solver(&SolverBackend::Parkissat).unwrap();
         assert_eq!(solver.backend(), SolverBackend::Parkissat);
         assert_eq!(solver.variable_count(), 0);
         assert_eq!(solver.clause_count(), 0);
@@ -147,30 +115,30 @@ mod tests {
 
     #[test]
     fn test_simple_satisfiable_cadical() {
-        let mut solver = UnifiedSatSolver::new(SolverBackend::Cadical).unwrap();
-        
+        let mut solver = create_solver(&SolverBackend::Cadical).unwrap();
+
         // Add clause: x1
         let clause = Clause::new(vec![1]);
         solver.add_clause(&clause).unwrap();
-        
+
         let result = solver.solve().unwrap();
         assert!(result.is_some());
-        
+
         let solution = result.unwrap();
         assert_eq!(solution.assignment.get(&1), Some(&true));
     }
 
     #[test]
     fn test_simple_satisfiable_parkissat() {
-        let mut solver = UnifiedSatSolver::new(SolverBackend::Parkissat).unwrap();
-        
+        let mut solver = create_solver(&SolverBackend::Parkissat).unwrap();
+
         // Add clause: x1
         let clause = Clause::new(vec![1]);
         solver.add_clause(&clause).unwrap();
-        
+
         let result = solver.solve().unwrap();
         assert!(result.is_some());
-        
+
         let solution = result.unwrap();
         // For clause x1, any assignment to x1 is valid (true or false)
         // Just verify that variable 1 has some assignment
@@ -179,25 +147,79 @@ mod tests {
 
     #[test]
     fn test_unsatisfiable_cadical() {
-        let mut solver = UnifiedSatSolver::new(SolverBackend::Cadical).unwrap();
-        
+        let mut solver = create_solver(&SolverBackend::Cadical).unwrap();
+
         // Add contradictory clauses: x1 and ¬x1
         solver.add_clause(&Clause::new(vec![1])).unwrap();
         solver.add_clause(&Clause::new(vec![-1])).unwrap();
-        
+
         let result = solver.solve().unwrap();
         assert!(result.is_none());
     }
 
     #[test]
     fn test_unsatisfiable_parkissat() {
-        let mut solver = UnifiedSatSolver::new(SolverBackend::Parkissat).unwrap();
-        
+        let mut solver = create_solver(&SolverBackend::Parkissat).unwrap();
+
         // Add contradictory clauses: x1 and ¬x1
         solver.add_clause(&Clause::new(vec![1])).unwrap();
         solver.add_clause(&Clause::new(vec![-1])).unwrap();
-        
+
         let result = solver.solve().unwrap();
         assert!(result.is_none());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_assumptions() {
+        for backend in [SolverBackend::Cadical, SolverBackend::Parkissat] {
+            let mut solver = create_solver(&backend).unwrap();
+
+            // x1 ∨ x2, solved under the assumption ¬x1
+            solver.add_clause(&Clause::new(vec![1, 2])).unwrap();
+
+            let result = solver.solve_with_assumptions(&[-1]).unwrap();
+            assert_eq!(result, SolverResultType::Satisfiable);
+            assert_eq!(solver.model_value(2), Some(true));
+
+            let result = solver.solve_with_assumptions(&[-1, -2]).unwrap();
+            assert_eq!(result, SolverResultType::Unsatisfiable);
+
+            // Assumptions do not persist between calls
+            assert!(solver.solve().unwrap().is_some());
+        }
+    }
+
+    #[test]
+    fn test_terminate_is_sticky() {
+        for backend in [SolverBackend::Cadical, SolverBackend::Parkissat] {
+            let mut solver = create_solver(&backend).unwrap();
+            solver.add_clause(&Clause::new(vec![1, 2])).unwrap();
+
+            solver.terminate();
+            let result = solver.solve_with_assumptions(&[]).unwrap();
+            assert_eq!(result, SolverResultType::Timeout);
+        }
+    }
+
+    #[test]
+    fn test_custom_backend_registration() {
+        let backend = SolverBackend::Custom("test_cadical".to_string());
+        assert!(!is_backend_registered(&backend));
+        assert!(create_solver(&backend).is_err());
+
+        register_backend(backend.clone(), || Ok(Box::new(SatSolver::new())));
+        assert!(is_backend_registered(&backend));
+
+        let mut solver = create_solver(&backend).unwrap();
+        solver.add_clause(&Clause::new(vec![1])).unwrap();
+        assert!(solver.solve().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_external_solver_creation() {
+        let backend = SolverBackend::External { command: "kissat".to_string(), args: vec!["-q".to_string()] };
+        assert!(is_backend_registered(&backend));
+        let solver = create_solver(&backend).unwrap();
+        assert_eq!(solver.backend(), backend);
+    }
+}
@@ -0,0 +1,195 @@
+//! Grid symmetries that can be broken without losing predecessors
+//!
+//! Breaking a transformation is only sound when it maps every solution to
+//! another solution. That holds when it commutes with evolution and fixes the
+//! target. Rotations and reflections of the grid commute with every rule we
+//! support (Hensel rules are isotropic by construction) under all boundary
+//! conditions; quarter turns and diagonal flips need a square grid.
+//! Translations commute only on a torus.
+
+use crate::config::BoundaryCondition;
+use crate::game_of_life::{Grid, TargetPattern};
+
+/// A rotation or reflection of the grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dihedral {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipHorizontal,
+    FlipVertical,
+    Transpose,
+    AntiTranspose,
+}
+
+impl Dihedral {
+    pub const ALL: [Dihedral; 8] = [
+        Dihedral::Identity,
+        Dihedral::Rotate90,
+        Dihedral::Rotate180,
+        Dihedral::Rotate270,
+        Dihedral::FlipHorizontal,
+        Dihedral::FlipVertical,
+        Dihedral::Transpose,
+        Dihedral::AntiTranspose,
+    ];
+
+    /// Whether the transformation maps the grid onto itself rather than its transpose
+    fn preserves_shape(&self, width: usize, height: usize) -> bool {
+        match self {
+            Dihedral::Identity | Dihedral::Rotate180 | Dihedral::FlipHorizontal | Dihedral::FlipVertical => true,
+            _ => width == height,
+        }
+    }
+}
+
+/// A rotation or reflection followed by a (toroidal) shift
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GridSymmetry {
+    pub dihedral: Dihedral,
+    /// Shift `(dx, dy)` applied after the rotation or reflection
+    pub shift: (usize, usize),
+}
+
+impl GridSymmetry {
+    /// Map cell `(x, y)` of a `width` x `height` grid to its image
+    pub fn apply(&self, x: usize, y: usize, width: usize, height: usize) -> (usize, usize) {
+        let (w, h) = (width - 1, height - 1);
+        let (tx, ty) = match self.dihedral {
+            Dihedral::Identity => (x, y),
+            Dihedral::Rotate90 => (h - y, x),
+            Dihedral::Rotate180 => (w - x, h - y),
+            Dihedral::Rotate270 => (y, w - x),
+            Dihedral::FlipHorizontal => (w - x, y),
+            Dihedral::FlipVertical => (x, h - y),
+            Dihedral::Transpose => (y, x),
+            Dihedral::AntiTranspose => (h - y, w - x),
+        };
+        ((tx + self.shift.0) % width, (ty + self.shift.1) % height)
+    }
+
+    /// Check whether this is the identity transformation
+    pub fn is_identity(&self) -> bool {
+        self.dihedral == Dihedral::Identity && self.shift == (0, 0)
+    }
+
+    /// Image of a whole grid
+    pub fn transform(&self, grid: &Grid) -> Grid {
+        let mut image = Grid::new(grid.width, grid.height, grid.boundary_condition.clone());
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                let (tx, ty) = self.apply(x, y, grid.width, grid.height);
+                image.cells[ty * grid.width + tx] = grid.get(y, x);
+            }
+        }
+        image
+    }
+
+    /// Check whether the transformation maps the pattern, don't-care cells included, onto itself
+    pub fn fixes(&self, pattern: &TargetPattern) -> bool {
+        let (width, height) = (pattern.grid.width, pattern.grid.height);
+        (0..height).all(|y| {
+            (0..width).all(|x| {
+                let (tx, ty) = self.apply(x, y, width, height);
+                pattern.cares(y, x) == pattern.cares(ty, tx) && pattern.grid.get(y, x) == pattern.grid.get(ty, tx)
+            })
+        })
+    }
+}
+
+/// Non-identity symmetries of the problem that fix `target`
+///
+/// Together with the identity these form a group, as lex-leader constraints require.
+pub fn target_symmetries(target: &Grid, boundary_condition: &BoundaryCondition) -> Vec<GridSymmetry> {
+    pattern_symmetries(&TargetPattern::from_grid(target.clone()), boundary_condition)
+}
+
+/// Non-identity symmetries of the problem that fix `pattern`
+///
+/// A symmetry must map specified cells to specified cells of the same state
+/// and don't-care cells to don't-care cells; otherwise it would map a
+/// solution to a grid that no longer matches the pattern.
+pub fn pattern_symmetries(pattern: &TargetPattern, boundary_condition: &BoundaryCondition) -> Vec<GridSymmetry> {
+    let (width, height) = (pattern.grid.width, pattern.grid.height);
+    let shifts: Vec<(usize, usize)> = match boundary_condition {
+        BoundaryCondition::Wrap => (0..height)
+            .flat_map(|dy| (0..width).map(move |dx| (dx, dy)))
+            .collect(),
+        BoundaryCondition::Dead | BoundaryCondition::Mirror => vec![(0, 0)],
+    };
+
+    Dihedral::ALL
+        .iter()
+        .filter(|dihedral| dihedral.preserves_shape(width, height))
+        .flat_map(|&dihedral| shifts.iter().map(move |&shift| GridSymmetry { dihedral, shift }))
+        .filter(|symmetry| !symmetry.is_identity())
+        .filter(|symmetry| symmetry.fixes(pattern))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_of_life::{GameOfLifeRules, Rule};
+
+    #[test]
+    fn test_detects_target_symmetries() {
+        // Horizontal blinker: fixed by the half turn and both axis flips
+        let mut blinker = Grid::new(5, 5, BoundaryCondition::Dead);
+        for x in 1..4 {
+            blinker.set(2, x, true).unwrap();
+        }
+        let symmetries = target_symmetries(&blinker, &BoundaryCondition::Dead);
+        let dihedrals: Vec<Dihedral> = symmetries.iter().map(|s| s.dihedral).collect();
+        assert_eq!(dihedrals, vec![Dihedral::Rotate180, Dihedral::FlipHorizontal, Dihedral::FlipVertical]);
+
+        // Off-center, only the vertical flip survives
+        let shifted = GridSymmetry { dihedral: Dihedral::Identity, shift: (1, 0) }.transform(&blinker);
+        let dihedrals: Vec<Dihedral> = target_symmetries(&shifted, &BoundaryCondition::Dead)
+            .iter()
+            .map(|s| s.dihedral)
+            .collect();
+        assert_eq!(dihedrals, vec![Dihedral::FlipVertical]);
+
+        // On a torus, the empty 3x3 grid is fixed by all 72 symmetries
+        let empty = Grid::new(3, 3, BoundaryCondition::Wrap);
+        assert_eq!(target_symmetries(&empty, &BoundaryCondition::Wrap).len(), 71);
+
+        // Don't-care cells must map to don't-care cells: a free left column
+        // breaks the horizontal flip and the half turn of the blinker
+        let mut care = vec![true; 25];
+        for y in 0..5 {
+            care[y * 5] = false;
+        }
+        let pattern = TargetPattern::new(blinker, care).unwrap();
+        let dihedrals: Vec<Dihedral> = pattern_symmetries(&pattern, &BoundaryCondition::Dead)
+            .iter()
+            .map(|s| s.dihedral)
+            .collect();
+        assert_eq!(dihedrals, vec![Dihedral::FlipVertical]);
+    }
+
+    #[test]
+    fn test_symmetries_commute_with_evolution() {
+        let rules: Vec<Rule> = ["B3/S23", "B2-a/S12", "B3-cnq/S2-k3"].iter().map(|r| r.parse().unwrap()).collect();
+
+        for boundary in [BoundaryCondition::Dead, BoundaryCondition::Wrap, BoundaryCondition::Mirror] {
+            // A pseudo-random, asymmetric 5x5 pattern
+            let mut grid = Grid::new(5, 5, boundary.clone());
+            for (i, cell) in grid.cells.iter_mut().enumerate() {
+                *cell = (i * 7 + i / 3) % 5 < 2;
+            }
+            // Every candidate symmetry fixes the empty grid
+            let symmetries = target_symmetries(&Grid::new(5, 5, boundary.clone()), &boundary);
+
+            for symmetry in &symmetries {
+                for rule in &rules {
+                    let evolved_image = GameOfLifeRules::evolve_with_rule(&symmetry.transform(&grid), rule);
+                    let image_of_evolved = symmetry.transform(&GameOfLifeRules::evolve_with_rule(&grid, rule));
+                    assert_eq!(evolved_image, image_of_evolved, "{:?} under {} with {:?}", symmetry, rule, boundary);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! Minimized CNF for the 3x3 cell transition
+//!
+//! The transition is a function of nine inputs: the eight neighbors (bits
+//! 0-7, in `NEIGHBOR_OFFSETS` order) and the cell itself (bit 8). Its on-set
+//! and off-set are each minimized to a cover of prime implicants with
+//! Quine-McCluskey, and every implicant becomes one clause implying the next
+//! state. The result depends only on the rule, so it is computed once per rule
+//! and cached for the lifetime of the process.
+
+use crate::game_of_life::Rule;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Number of transition inputs: eight neighbors and the cell itself
+pub const TRANSITION_INPUTS: usize = 9;
+
+/// Input index of the cell's own current state
+pub const CURRENT_CELL_INPUT: usize = 8;
+
+const ALL_INPUTS: u16 = (1 << TRANSITION_INPUTS) - 1;
+
+/// A product term: inputs whose bit is set in `mask` must match `value`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Implicant {
+    pub mask: u16,
+    pub value: u16,
+}
+
+impl Implicant {
+    /// Check whether the term is true for the given input assignment
+    pub fn covers(&self, minterm: u16) -> bool {
+        minterm & self.mask == self.value
+    }
+
+    /// The fixed inputs as `(input index, required value)` pairs
+    pub fn literals(&self) -> impl Iterator<Item = (usize, bool)> + '_ {
+        (0..TRANSITION_INPUTS)
+            .filter(|&input| self.mask & (1 << input) != 0)
+            .map(|input| (input, self.value & (1 << input) != 0))
+    }
+
+    /// Number of fixed inputs
+    pub fn literal_count(&self) -> u32 {
+        self.mask.count_ones()
+    }
+}
+
+/// Minimized CNF of `next <-> rule(current, neighbors)`
+///
+/// Each term in `alive_terms` yields the clause `!term | next`, each term in
+/// `dead_terms` the clause `!term | !next`.
+#[derive(Debug, Clone)]
+pub struct TransitionCnf {
+    pub alive_terms: Vec<Implicant>,
+    pub dead_terms: Vec<Implicant>,
+}
+
+impl TransitionCnf {
+    /// Minimize the transition of the given rule
+    pub fn new(rule: &Rule) -> Self {
+        let next_state = |minterm: u16| {
+            let current = minterm & (1 << CURRENT_CELL_INPUT) != 0;
+            rule.next_state(current, (minterm & 0xFF) as u8)
+        };
+
+        let (alive, dead): (Vec<u16>, Vec<u16>) = (0..=ALL_INPUTS).partition(|&minterm| next_state(minterm));
+
+        Self {
+            alive_terms: minimal_cover(&alive),
+            dead_terms: minimal_cover(&dead),
+        }
+    }
+
+    /// Number of clauses per cell, before boundary simplification
+    pub fn clause_count(&self) -> usize {
+        self.alive_terms.len() + self.dead_terms.len()
+    }
+}
+
+/// Minimized transition CNF for `rule`, computed on first use and cached
+pub fn minimized_transition(rule: &Rule) -> Arc<TransitionCnf> {
+    static CACHE: OnceLock<Mutex<HashMap<Rule, Arc<TransitionCnf>>>> = OnceLock::new();
+
+    let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut cache = cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    Arc::clone(cache.entry(rule.clone()).or_insert_with(|| Arc::new(TransitionCnf::new(rule))))
+}
+
+/// All prime implicants of the function whose on-set is `minterms`
+fn prime_implicants(minterms: &[u16]) -> Vec<Implicant> {
+    let mut primes = Vec::new();
+    let mut level: HashSet<Implicant> = minterms
+        .iter()
+        .map(|&minterm| Implicant { mask: ALL_INPUTS, value: minterm })
+        .collect();
+
+    while !level.is_empty() {
+        let mut next_level = HashSet::new();
+        let mut merged = HashSet::new();
+
+        for implicant in &level {
+            for input in 0..TRANSITION_INPUTS {
+                let bit = 1 << input;
+                if implicant.mask & bit == 0 {
+                    continue;
+                }
+                let partner = Implicant { mask: implicant.mask, value: implicant.value ^ bit };
+                if level.contains(&partner) {
+                    merged.insert(*implicant);
+                    next_level.insert(Implicant { mask: implicant.mask & !bit, value: implicant.value & !bit });
+                }
+            }
+        }
+
+        primes.extend(level.into_iter().filter(|implicant| !merged.contains(implicant)));
+        level = next_level;
+    }
+
+    primes.sort();
+    primes
+}
+
+/// Irredundant cover of `minterms` by prime implicants
+///
+/// Essential primes first, then the prime covering the most remaining
+/// minterms (fewest literals on ties), then any prime made redundant by later
+/// picks is dropped. Not guaranteed minimum, but close on 9-input functions.
+fn minimal_cover(minterms: &[u16]) -> Vec<Implicant> {
+    let primes = prime_implicants(minterms);
+    let mut selected: Vec<Implicant> = Vec::new();
+    let mut uncovered: HashSet<u16> = minterms.iter().copied().collect();
+
+    for &minterm in minterms {
+        let mut covering = primes.iter().filter(|prime| prime.covers(minterm));
+        if let (Some(&only), None) = (covering.next(), covering.next()) {
+            if !selected.contains(&only) {
+                selected.push(only);
+                uncovered.retain(|&m| !only.covers(m));
+            }
+        }
+    }
+
+    while !uncovered.is_empty() {
+        let best = primes
+            .iter()
+            .filter(|prime| !selected.contains(prime))
+            .max_by_key(|prime| {
+                let gain = uncovered.iter().filter(|&&m| prime.covers(m)).count();
+                (gain, std::cmp::Reverse(prime.literal_count()))
+            })
+            .copied()
+            .expect("prime implicants cover every minterm");
+        selected.push(best);
+        uncovered.retain(|&m| !best.covers(m));
+    }
+
+    for i in (0..selected.len()).rev() {
+        let candidate = selected[i];
+        let redundant = minterms
+            .iter()
+            .filter(|&&m| candidate.covers(m))
+            .all(|&m| selected.iter().enumerate().any(|(j, other)| j != i && other.covers(m)));
+        if redundant {
+            selected.remove(i);
+        }
+    }
+
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Check the CNF against the rule on all 512 input assignments
+    fn assert_exact(rule: &Rule) {
+        let cnf = TransitionCnf::new(rule);
+
+        for minterm in 0..=ALL_INPUTS {
+            let current = minterm & (1 << CURRENT_CELL_INPUT) != 0;
+            let expected = rule.next_state(current, (minterm & 0xFF) as u8);
+
+            let forces_alive = cnf.alive_terms.iter().any(|term| term.covers(minterm));
+            let forces_dead = cnf.dead_terms.iter().any(|term| term.covers(minterm));
+            assert_eq!(forces_alive, expected, "{} on input {:09b}", rule, minterm);
+            assert_eq!(forces_dead, !expected, "{} on input {:09b}", rule, minterm);
+        }
+    }
+
+    #[test]
+    fn test_minimized_cnf_is_exact() {
+        for rule in ["B3/S23", "B36/S23", "B2/S", "B3678/S34678", "B2-a/S12", "B0/S8", "B/S"] {
+            assert_exact(&rule.parse().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_minimized_cnf_is_smaller_than_direct() {
+        // Life's transition has 190 prime-implicant clauses, against one
+        // clause per input assignment in the direct encoding
+        let cnf = TransitionCnf::new(&Rule::conway());
+        assert_eq!((cnf.alive_terms.len(), cnf.dead_terms.len()), (84, 106));
+        assert_eq!(cnf.clause_count(), 190);
+        assert!(cnf.clause_count() < 1 << TRANSITION_INPUTS);
+
+        // Constant functions collapse to a single empty term
+        let dead = TransitionCnf::new(&"B/S".parse().unwrap());
+        assert!(dead.alive_terms.is_empty());
+        assert_eq!(dead.dead_terms, vec![Implicant { mask: 0, value: 0 }]);
+    }
+
+    #[test]
+    fn test_prime_implicants() {
+        // f = a | b over inputs 0 and 1, with every other input free
+        let minterms: Vec<u16> = (0..=ALL_INPUTS).filter(|m| m & 0b11 != 0).collect();
+        let primes = prime_implicants(&minterms);
+        assert_eq!(primes, vec![Implicant { mask: 0b01, value: 0b01 }, Implicant { mask: 0b10, value: 0b10 }]);
+    }
+
+    #[test]
+    fn test_cache_reuses_minimization() {
+        let highlife: Rule = "B36/S23".parse().unwrap();
+        let first = minimized_transition(&highlife);
+        let second = minimized_transition(&highlife);
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+}
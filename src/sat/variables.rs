@@ -8,6 +8,8 @@ use anyhow::Result;
 pub enum VariableType {
     /// Cell state at position (x, y, t)
     Cell { x: usize, y: usize, t: usize },
+    /// Helper variable introduced by an encoding (e.g. a neighbor-count output)
+    Auxiliary { index: usize },
 }
 
 /// Manages SAT variables and their mapping to integers
@@ -22,17 +24,23 @@ pub struct VariableManager {
     height: usize,
     /// Number of time steps
     time_steps: usize,
+    /// Whether auxiliary variables may be created
+    use_auxiliary: bool,
+    /// Number of auxiliary variables created so far
+    auxiliary_count: usize,
 }
 
 impl VariableManager {
     /// Create a new variable manager
-    pub fn new(width: usize, height: usize, time_steps: usize, _use_auxiliary: bool) -> Self {
+    pub fn new(width: usize, height: usize, time_steps: usize, use_auxiliary: bool) -> Self {
         Self {
             variable_map: HashMap::new(),
             next_id: 1, // SAT variables start from 1
             width,
             height,
             time_steps,
+            use_auxiliary,
+            auxiliary_count: 0,
         }
     }
 
@@ -56,6 +64,13 @@ impl VariableManager {
         self.get_variable(VariableType::Cell { x, y, t })
     }
 
+    /// Create a fresh auxiliary variable
+    pub fn new_auxiliary_variable(&mut self) -> Result<i32> {
+        let id = self.get_variable(VariableType::Auxiliary { index: self.auxiliary_count })?;
+        self.auxiliary_count += 1;
+        Ok(id)
+    }
+
 
     /// Get all cell variables for a specific time step
     pub fn all_cell_variables_at_time(&mut self, t: usize) -> Result<Vec<i32>> {
@@ -69,6 +84,18 @@ impl VariableManager {
     }
 
 
+    /// Every cell variable created so far, as `((x, y, t), id)` in order of id
+    pub fn cell_variables(&self) -> Vec<((usize, usize, usize), i32)> {
+        let mut cells: Vec<((usize, usize, usize), i32)> = self.variable_map.iter()
+            .filter_map(|(var_type, &id)| match var_type {
+                VariableType::Cell { x, y, t } => Some(((*x, *y, *t), id)),
+                VariableType::Auxiliary { .. } => None,
+            })
+            .collect();
+        cells.sort_by_key(|&(_, id)| id);
+        cells
+    }
+
     /// Get the total number of variables created
     pub fn variable_count(&self) -> usize {
         (self.next_id - 1) as usize
@@ -94,6 +121,11 @@ impl VariableManager {
                     anyhow::bail!("Time step {} out of bounds (time_steps: {})", t, self.time_steps);
                 }
             }
+            VariableType::Auxiliary { .. } => {
+                if !self.use_auxiliary {
+                    anyhow::bail!("Auxiliary variables are disabled for this encoding");
+                }
+            }
         }
         Ok(())
     }
@@ -101,16 +133,19 @@ impl VariableManager {
     /// Get statistics about variable usage
     pub fn statistics(&self) -> VariableStatistics {
         let mut cell_vars = 0;
+        let mut auxiliary_vars = 0;
 
         for var_type in self.variable_map.keys() {
             match var_type {
                 VariableType::Cell { .. } => cell_vars += 1,
+                VariableType::Auxiliary { .. } => auxiliary_vars += 1,
             }
         }
 
         VariableStatistics {
             total_variables: self.variable_count(),
             cell_variables: cell_vars,
+            auxiliary_variables: auxiliary_vars,
         }
     }
 
@@ -118,6 +153,7 @@ impl VariableManager {
     pub fn clear(&mut self) {
         self.variable_map.clear();
         self.next_id = 1;
+        self.auxiliary_count = 0;
     }
 }
 
@@ -126,6 +162,7 @@ impl VariableManager {
 pub struct VariableStatistics {
     pub total_variables: usize,
     pub cell_variables: usize,
+    pub auxiliary_variables: usize,
 }
 
 impl std::fmt::Display for VariableStatistics {
@@ -133,6 +170,7 @@ impl std::fmt::Display for VariableStatistics {
         writeln!(f, "Variable Statistics:")?;
         writeln!(f, "  Total variables: {}", self.total_variables)?;
         writeln!(f, "  Cell variables: {}", self.cell_variables)?;
+        writeln!(f, "  Auxiliary variables: {}", self.auxiliary_variables)?;
         Ok(())
     }
 }
@@ -205,7 +243,24 @@ mod tests {
         vm.cell_variable(1, 1, 1).unwrap();
         
         let stats = vm.statistics();
+        assert_eq!(vm.cell_variables(), vec![((0, 0, 0), 1), ((1, 1, 1), 2)]);
         assert_eq!(stats.total_variables, 2);
         assert_eq!(stats.cell_variables, 2);
+        assert_eq!(stats.auxiliary_variables, 0);
+    }
+
+    #[test]
+    fn test_auxiliary_variables() {
+        let mut vm = VariableManager::new(2, 2, 2, true);
+
+        let cell = vm.cell_variable(0, 0, 0).unwrap();
+        let aux1 = vm.new_auxiliary_variable().unwrap();
+        let aux2 = vm.new_auxiliary_variable().unwrap();
+        assert_eq!((cell, aux1, aux2), (1, 2, 3));
+        assert_eq!(vm.statistics().auxiliary_variables, 2);
+
+        // Managers without auxiliary support refuse to create them
+        let mut direct = VariableManager::new(2, 2, 2, false);
+        assert!(direct.new_auxiliary_variable().is_err());
     }
 }
\ No newline at end of file
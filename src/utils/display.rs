@@ -1,8 +1,11 @@
 //! Display and output formatting utilities
 
-use crate::game_of_life::Grid;
-use crate::reverse::Solution;
-use crate::config::OutputFormat;
+use crate::game_of_life::image::{evolution_to_gif, grid_to_image};
+use crate::game_of_life::io::pattern_file_to_string;
+use crate::game_of_life::{Grid, ImageFormat, PatternFile, PatternFormat, PatternSource, TargetPattern};
+use crate::reverse::solution::Optimality;
+use crate::reverse::{Solution, UnreachableCore};
+use crate::config::{OutputConfig, OutputFormat};
 use anyhow::Result;
 use std::path::Path;
 
@@ -21,6 +24,11 @@ impl SolutionFormatter {
         output.push_str(&format!("Living Cells: {} → {}\n",
                                 solution.metadata.predecessor_living_cells,
                                 solution.metadata.target_living_cells));
+        match solution.metadata.optimality {
+            Optimality::Proven => output.push_str("Population: proven optimal\n"),
+            Optimality::Unproven => output.push_str("Population: best found, not proven optimal\n"),
+            Optimality::NotOptimized => {}
+        }
         
         if solution.metadata.stability.is_still_life {
             output.push_str("Type: Still Life\n");
@@ -64,6 +72,16 @@ impl SolutionFormatter {
         output.push_str(&format!("Living Cells: {} → {}\n",
                                 solution.metadata.predecessor_living_cells,
                                 solution.metadata.target_living_cells));
+        let source = &solution.metadata.source;
+        if let Some(name) = &source.name {
+            output.push_str(&format!("Target: {}\n", name));
+        }
+        if let Some(file) = &source.file {
+            output.push_str(&format!("Target File: {}\n", file.display()));
+        }
+        for comment in &source.comments {
+            output.push_str(&format!("Note: {}\n", comment));
+        }
         
         if solution.metadata.stability.is_still_life {
             output.push_str("Type: Still Life\n");
@@ -179,15 +197,54 @@ impl SolutionFormatter {
         output
     }
 
+    /// Format a target with the cells of an unreachable core highlighted
+    ///
+    /// Core cells are drawn as `▓▓` (alive) or `░░` (dead), in red where the
+    /// terminal supports it; don't-care cells are drawn as `??`.
+    pub fn format_core(core: &UnreachableCore) -> String {
+        let target = &core.target;
+        let mut output = String::new();
+
+        // Header with column numbers
+        output.push_str("   ");
+        for x in 0..target.grid.width {
+            if x == 0 {
+                output.push_str(&format!("{}", x % 10));
+            } else {
+                output.push_str(&format!("  {}", x % 10));
+            }
+        }
+        output.push('\n');
+
+        for y in 0..target.grid.height {
+            output.push_str(&format!("{:2} ", y));
+            for x in 0..target.grid.width {
+                let alive = target.grid.get(y, x);
+                if core.contains(y, x) {
+                    let symbol = if alive { "▓▓" } else { "░░" };
+                    output.push_str(&ColorOutput::colored(symbol, Color::Red));
+                } else if !target.cares(y, x) {
+                    output.push_str("??");
+                } else {
+                    output.push_str(if alive { "██" } else { "··" });
+                }
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
     /// Save solutions to files based on output format
     pub fn save_solutions<P: AsRef<Path>>(
         solutions: &[Solution],
         output_dir: P,
-        format: &OutputFormat,
+        output: &OutputConfig,
     ) -> Result<()> {
         let output_dir = output_dir.as_ref();
         std::fs::create_dir_all(output_dir)?;
 
+        let format = &output.format;
         match format {
             OutputFormat::Text => {
                 for (i, solution) in solutions.iter().enumerate() {
@@ -219,11 +276,71 @@ impl SolutionFormatter {
                     std::fs::write(filepath, content)?;
                 }
             }
+            OutputFormat::Rle | OutputFormat::Cells | OutputFormat::Life106 => {
+                let pattern_format = match format {
+                    OutputFormat::Rle => PatternFormat::Rle,
+                    OutputFormat::Cells => PatternFormat::Cells,
+                    _ => PatternFormat::Life106,
+                };
+                for (i, solution) in solutions.iter().enumerate() {
+                    let filename = format!("solution_{:03}.{}", i + 1, pattern_format.extension());
+                    let filepath = output_dir.join(filename);
+                    std::fs::write(filepath, Self::format_solution_pattern(solution, pattern_format))?;
+                }
+            }
+            OutputFormat::Pbm | OutputFormat::Png => {
+                // One frame per generation, the predecessor first
+                let image_format = if matches!(format, OutputFormat::Pbm) { ImageFormat::Pbm } else { ImageFormat::Png };
+                for (i, solution) in solutions.iter().enumerate() {
+                    for (generation, grid) in solution.evolution_path.iter().enumerate() {
+                        let filename = format!("solution_{:03}_gen_{:03}.{}", i + 1, generation, image_format.extension());
+                        std::fs::write(output_dir.join(filename), grid_to_image(grid, image_format, &output.image)?)?;
+                    }
+                }
+            }
+            OutputFormat::Gif => {
+                for (i, solution) in solutions.iter().enumerate() {
+                    let filename = format!("solution_{:03}.gif", i + 1);
+                    std::fs::write(output_dir.join(filename), evolution_to_gif(&solution.evolution_path, &output.image)?)?;
+                }
+            }
         }
 
         Ok(())
     }
 
+    /// Format a solution's predecessor as a pattern file, with the solution described in comments
+    ///
+    /// The name and comments of the target's file are carried over, so the
+    /// pattern keeps its provenance.
+    pub fn format_solution_pattern(solution: &Solution, format: PatternFormat) -> String {
+        let source = &solution.metadata.source;
+        let mut comments = vec![
+            format!("Predecessor that reaches the target after {} generation(s)", solution.generations),
+            format!("Living cells: {} -> {}",
+                    solution.metadata.predecessor_living_cells,
+                    solution.metadata.target_living_cells),
+        ];
+        if let Some(file) = &source.file {
+            comments.push(format!("Target file: {}", file.display()));
+        }
+        comments.extend(source.comments.iter().cloned());
+
+        let file = PatternFile {
+            pattern: TargetPattern::from_grid(solution.predecessor.clone()),
+            rule: Some(solution.metadata.rule.clone()),
+            source: PatternSource {
+                file: None,
+                name: Some(match &source.name {
+                    Some(name) => format!("Predecessor of {} ({})", name, solution.metadata.id),
+                    None => solution.metadata.id.clone(),
+                }),
+                comments,
+            },
+        };
+        pattern_file_to_string(&file, format)
+    }
+
     /// Create a visual representation of the evolution (for file output with 1s and 0s)
     fn create_visual_evolution(solution: &Solution) -> String {
         let mut output = String::new();
@@ -425,7 +542,7 @@ impl Color {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::BoundaryCondition;
+    use crate::config::{BoundaryCondition, ImageImportConfig, Settings};
 
     #[test]
     fn test_grid_formatting() {
@@ -444,6 +561,82 @@ mod tests {
         assert!(with_coords.contains("0  1  2"));
     }
 
+    #[test]
+    fn test_core_formatting() {
+        let grid = Grid::from_cells(vec![vec![true, false, true]], BoundaryCondition::Dead).unwrap();
+        let target = crate::game_of_life::TargetPattern::new(grid, vec![true, true, false]).unwrap();
+        let core = UnreachableCore { target, cells: vec![(0, 1)], minimal: true };
+
+        let formatted = SolutionFormatter::format_core(&core);
+        assert!(formatted.contains("██"));
+        assert!(formatted.contains("░░"));
+        assert!(formatted.contains("??"));
+        assert!(!formatted.contains("▓▓"));
+    }
+
+    #[test]
+    fn test_pattern_output() {
+        let predecessor = Grid::from_cells(vec![vec![false, true], vec![true, true]], BoundaryCondition::Dead).unwrap();
+        let target = Grid::from_cells(vec![vec![true, true], vec![true, true]], BoundaryCondition::Dead).unwrap();
+        let mut solution = Solution::new(predecessor.clone(), target.clone(), 1, vec![predecessor.clone(), target], std::time::Duration::ZERO);
+        solution.metadata.source.name = Some("Block".to_string());
+        solution.metadata.source.comments.push("Still life".to_string());
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut output = Settings::default().output;
+        output.format = OutputFormat::Rle;
+        SolutionFormatter::save_solutions(&[solution.clone()], temp_dir.path(), &output).unwrap();
+        let path = temp_dir.path().join("solution_001.rle");
+        assert!(std::fs::read_to_string(&path).unwrap().contains("x = 2, y = 2, rule = B3/S23\nbo$2o!"));
+        let loaded = crate::game_of_life::load_grid_from_file(&path, BoundaryCondition::Dead).unwrap();
+        assert_eq!(loaded, predecessor);
+
+        // Every pattern format keeps the target's name and comments
+        for (format, extension) in [(OutputFormat::Cells, "cells"), (OutputFormat::Life106, "lif")] {
+            output.format = format;
+            SolutionFormatter::save_solutions(&[solution.clone()], temp_dir.path(), &output).unwrap();
+            let path = temp_dir.path().join(format!("solution_001.{}", extension));
+            let file = crate::game_of_life::load_pattern_file(&path, BoundaryCondition::Dead).unwrap();
+            assert!(file.source.name.unwrap().starts_with("Predecessor of Block"));
+            assert!(file.source.comments.contains(&"Still life".to_string()));
+        }
+    }
+
+    #[test]
+    fn test_frame_output() {
+        let blinker = |vertical: bool| {
+            let cells: Vec<Vec<bool>> = (0..3).map(|row| (0..3).map(|col| if vertical { col == 1 } else { row == 1 }).collect()).collect();
+            Grid::from_cells(cells, BoundaryCondition::Dead).unwrap()
+        };
+        let path = vec![blinker(false), blinker(true), blinker(false)];
+        let solution = Solution::new(blinker(false), blinker(false), 2, path.clone(), std::time::Duration::ZERO);
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut output = Settings::default().output;
+        output.format = OutputFormat::Pbm;
+        output.image.cell_size = 4;
+        SolutionFormatter::save_solutions(std::slice::from_ref(&solution), temp_dir.path(), &output).unwrap();
+
+        let import = ImageImportConfig { downscale: 4, ..ImageImportConfig::default() };
+        for (generation, grid) in path.iter().enumerate() {
+            let frame = temp_dir.path().join(format!("solution_001_gen_{:03}.pbm", generation));
+            let loaded = crate::game_of_life::load_grid_from_image(&frame, &import, BoundaryCondition::Dead).unwrap();
+            assert_eq!(&loaded, grid);
+        }
+
+        output.format = OutputFormat::Png;
+        SolutionFormatter::save_solutions(std::slice::from_ref(&solution), temp_dir.path(), &output).unwrap();
+        let png = std::fs::read(temp_dir.path().join("solution_001_gen_002.png")).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+
+        // The whole path goes into one animation
+        output.format = OutputFormat::Gif;
+        SolutionFormatter::save_solutions(&[solution], temp_dir.path(), &output).unwrap();
+        let gif = std::fs::read(temp_dir.path().join("solution_001.gif")).unwrap();
+        assert!(gif.starts_with(b"GIF89a\x0C\x00\x0C\x00"));
+        assert_eq!(gif.windows(2).filter(|window| *window == [0x21, 0xF9]).count(), path.len());
+    }
+
     #[test]
     fn test_progress_indicator() {
         let mut progress = ProgressIndicator::new(100);
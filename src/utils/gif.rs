@@ -0,0 +1,246 @@
+//! Minimal animated GIF encoder
+//!
+//! Frames share one global palette and always cover the whole canvas, which
+//! is all an evolution path needs. The animation loops forever.
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Largest number of codes in the LZW table
+const MAX_CODES: u16 = 4096;
+
+/// One image of an animation, as palette indices row by row
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub pixels: Vec<u8>,
+    /// How long the frame stays on screen, rounded to hundredths of a second
+    pub delay: Duration,
+}
+
+/// Encode frames of `width` x `height` pixels as a looping GIF
+pub fn encode_animation(width: usize, height: usize, palette: &[[u8; 3]], frames: &[Frame]) -> Result<Vec<u8>> {
+    let (Ok(gif_width), Ok(gif_height)) = (u16::try_from(width), u16::try_from(height)) else {
+        anyhow::bail!("A {}x{} animation is too large for GIF", width, height);
+    };
+    if width == 0 || height == 0 || frames.is_empty() {
+        anyhow::bail!("A GIF animation needs at least one frame of one pixel");
+    }
+    if palette.is_empty() || palette.len() > 256 {
+        anyhow::bail!("A GIF palette holds 1 to 256 colors, not {}", palette.len());
+    }
+    for (index, frame) in frames.iter().enumerate() {
+        if frame.pixels.len() != width * height {
+            anyhow::bail!("Frame {} has {} pixels instead of {}", index, frame.pixels.len(), width * height);
+        }
+        if frame.pixels.iter().any(|&pixel| usize::from(pixel) >= palette.len()) {
+            anyhow::bail!("Frame {} uses a color outside the palette", index);
+        }
+    }
+
+    // The color table has 2^(size + 1) entries; LZW codes start one bit wider
+    let table_size = (palette.len().max(2).next_power_of_two().trailing_zeros() - 1) as u8;
+    let min_code_size = (table_size + 1).max(2);
+
+    let mut gif = b"GIF89a".to_vec();
+    gif.extend_from_slice(&gif_width.to_le_bytes());
+    gif.extend_from_slice(&gif_height.to_le_bytes());
+    // Global color table with 8 bits per primary, background color 0, square pixels
+    gif.extend_from_slice(&[0xF0 | table_size, 0, 0]);
+    for index in 0..2usize << table_size {
+        gif.extend_from_slice(&palette.get(index).copied().unwrap_or([0, 0, 0]));
+    }
+
+    // Netscape extension: repeat forever
+    gif.extend_from_slice(&[0x21, 0xFF, 0x0B]);
+    gif.extend_from_slice(b"NETSCAPE2.0");
+    gif.extend_from_slice(&[0x03, 0x01, 0x00, 0x00, 0x00]);
+
+    for frame in frames {
+        let centiseconds = u16::try_from((frame.delay.as_millis() + 5) / 10).unwrap_or(u16::MAX);
+        // Graphic control extension: leave the frame in place, no transparency
+        gif.extend_from_slice(&[0x21, 0xF9, 0x04, 0x04]);
+        gif.extend_from_slice(&centiseconds.to_le_bytes());
+        gif.extend_from_slice(&[0x00, 0x00]);
+
+        // Image descriptor covering the canvas, without a local color table
+        gif.push(0x2C);
+        gif.extend_from_slice(&[0, 0, 0, 0]);
+        gif.extend_from_slice(&gif_width.to_le_bytes());
+        gif.extend_from_slice(&gif_height.to_le_bytes());
+        gif.push(0x00);
+
+        gif.push(min_code_size);
+        for block in lzw_encode(&frame.pixels, min_code_size).chunks(255) {
+            gif.push(block.len() as u8);
+            gif.extend_from_slice(block);
+        }
+        gif.push(0x00);
+    }
+
+    gif.push(0x3B);
+    Ok(gif)
+}
+
+/// Compress palette indices with GIF's variable-width LZW
+fn lzw_encode(pixels: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let mut writer = BitWriter::default();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next_code = end + 1;
+    let mut code_size = min_code_size + 1;
+
+    writer.write(clear, code_size);
+    let mut prefix: Option<u16> = None;
+    for &pixel in pixels {
+        let Some(current) = prefix else {
+            prefix = Some(u16::from(pixel));
+            continue;
+        };
+        if let Some(&code) = table.get(&(current, pixel)) {
+            prefix = Some(code);
+            continue;
+        }
+
+        writer.write(current, code_size);
+        if next_code == MAX_CODES {
+            // The table is full: start over rather than let it go stale
+            writer.write(clear, code_size);
+            table.clear();
+            next_code = end + 1;
+            code_size = min_code_size + 1;
+        } else {
+            table.insert((current, pixel), next_code);
+            // The decoder adds each code one step later, so widen once it
+            // could be asked for a code that no longer fits
+            if next_code == 1 << code_size {
+                code_size += 1;
+            }
+            next_code += 1;
+        }
+        prefix = Some(u16::from(pixel));
+    }
+
+    if let Some(current) = prefix {
+        writer.write(current, code_size);
+    }
+    writer.write(end, code_size);
+    writer.finish()
+}
+
+/// Packs codes into bytes, least significant bit first
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.buffer |= u32::from(code) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decode GIF LZW data back into palette indices
+    fn lzw_decode(data: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear = 1usize << min_code_size;
+        let mut output = Vec::new();
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let mut code_size = min_code_size + 1;
+        let mut previous: Option<usize> = None;
+        let (mut buffer, mut bits, mut bytes) = (0u32, 0u8, data.iter());
+
+        loop {
+            while bits < code_size {
+                buffer |= u32::from(*bytes.next().expect("data ends before the end code")) << bits;
+                bits += 8;
+            }
+            let code = (buffer & ((1 << code_size) - 1)) as usize;
+            buffer >>= code_size;
+            bits -= code_size;
+
+            if code == clear {
+                table = (0..clear).map(|index| vec![index as u8]).chain([Vec::new(), Vec::new()]).collect();
+                code_size = min_code_size + 1;
+                previous = None;
+                continue;
+            }
+            if code == clear + 1 {
+                return output;
+            }
+
+            let entry = match (table.get(code), previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(previous)) => {
+                    let mut entry = table[previous].clone();
+                    entry.push(table[previous][0]);
+                    entry
+                }
+                (None, None) => panic!("unknown first code {}", code),
+            };
+            if let Some(previous) = previous {
+                if table.len() < MAX_CODES as usize {
+                    let mut added = table[previous].clone();
+                    added.push(entry[0]);
+                    table.push(added);
+                    if table.len() == 1 << code_size && code_size < 12 {
+                        code_size += 1;
+                    }
+                }
+            }
+            output.extend_from_slice(&entry);
+            previous = Some(code);
+        }
+    }
+
+    #[test]
+    fn test_lzw_round_trip() {
+        // Long runs fill the table and force a reset
+        let mut pixels: Vec<u8> = (0..20_000u32).map(|i| ((i * i / 7 + i / 13) % 4) as u8).collect();
+        pixels.extend([1; 5_000]);
+        for min_code_size in [2, 3, 8] {
+            assert_eq!(lzw_decode(&lzw_encode(&pixels, min_code_size), min_code_size), pixels);
+        }
+        assert_eq!(lzw_decode(&lzw_encode(&[], 2), 2), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_encode_animation() {
+        let palette = [[0, 0, 0], [255, 255, 255]];
+        let frames = [
+            Frame { pixels: vec![0, 1, 1, 0], delay: Duration::from_millis(200) },
+            Frame { pixels: vec![1, 0, 0, 1], delay: Duration::from_secs(2) },
+        ];
+        let gif = encode_animation(2, 2, &palette, &frames).unwrap();
+
+        assert!(gif.starts_with(b"GIF89a\x02\x00\x02\x00\xF0\x00\x00\x00\x00\x00\xFF\xFF\xFF"));
+        assert!(gif.windows(11).any(|window| window == b"NETSCAPE2.0"));
+        // Delays in hundredths of a second
+        assert!(gif.windows(6).any(|window| window == [0x21, 0xF9, 0x04, 0x04, 20, 0]));
+        assert!(gif.windows(6).any(|window| window == [0x21, 0xF9, 0x04, 0x04, 200, 0]));
+        assert_eq!(gif.last(), Some(&0x3B));
+
+        let bad_frame = [Frame { pixels: vec![0, 2, 0, 0], delay: Duration::ZERO }];
+        assert!(encode_animation(2, 2, &palette, &bad_frame).is_err());
+        assert!(encode_animation(2, 2, &palette, &[]).is_err());
+    }
+}
@@ -1,5 +1,7 @@
 //! Utility functions and display helpers
 
 pub mod display;
+pub mod gif;
+pub mod png;
 
-pub use display::*;
\ No newline at end of file
+pub use display::*;
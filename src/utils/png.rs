@@ -0,0 +1,131 @@
+//! Minimal PNG encoder
+//!
+//! Pixel data goes into uncompressed deflate blocks, which every PNG reader
+//! accepts. Frames of a few hundred cells stay small enough without
+//! compression.
+
+use anyhow::Result;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// Largest payload of a stored deflate block
+const MAX_STORED_BLOCK: usize = 65_535;
+
+/// Encode an image given as palette indices, one byte per pixel, row by row
+pub fn encode_indexed(width: usize, height: usize, palette: &[[u8; 3]], pixels: &[u8]) -> Result<Vec<u8>> {
+    if width == 0 || height == 0 {
+        anyhow::bail!("A PNG image needs at least one pixel");
+    }
+    if pixels.len() != width * height {
+        anyhow::bail!("Expected {} pixels for a {}x{} image, got {}", width * height, width, height, pixels.len());
+    }
+    if palette.is_empty() || palette.len() > 256 {
+        anyhow::bail!("A PNG palette holds 1 to 256 colors, not {}", palette.len());
+    }
+    if pixels.iter().any(|&pixel| usize::from(pixel) >= palette.len()) {
+        anyhow::bail!("Image uses a color outside the palette");
+    }
+    let (Ok(png_width), Ok(png_height)) = (u32::try_from(width), u32::try_from(height)) else {
+        anyhow::bail!("A {}x{} image is too large for PNG", width, height);
+    };
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&png_width.to_be_bytes());
+    header.extend_from_slice(&png_height.to_be_bytes());
+    // Bit depth 8, indexed color, deflate, adaptive filtering, no interlace
+    header.extend_from_slice(&[8, 3, 0, 0, 0]);
+
+    // Every scanline starts with its filter type, 0 for none
+    let mut scanlines = Vec::with_capacity((width + 1) * height);
+    for row in pixels.chunks(width) {
+        scanlines.push(0);
+        scanlines.extend_from_slice(row);
+    }
+
+    let mut png = SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"PLTE", palette.concat().as_slice());
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&scanlines));
+    write_chunk(&mut png, b"IEND", &[]);
+    Ok(png)
+}
+
+/// Append a chunk: length, type, data and the CRC of type and data
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    png.extend_from_slice(&crc32(kind.iter().chain(data)).to_be_bytes());
+}
+
+/// Wrap `data` in a zlib stream of stored deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    // Deflate with a 32K window; the check bits make the header a multiple of 31
+    let mut stream = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_STORED_BLOCK).peekable();
+    while let Some(block) = blocks.next() {
+        // BFINAL on the last block, BTYPE 00 for stored
+        stream.push(u8::from(blocks.peek().is_none()));
+        let length = block.len() as u16;
+        stream.extend_from_slice(&length.to_le_bytes());
+        stream.extend_from_slice(&(!length).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+/// CRC-32 as used by PNG chunks
+fn crc32<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// Adler-32 checksum closing a zlib stream
+fn adler32(data: &[u8]) -> u32 {
+    const MODULUS: u32 = 65_521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + u32::from(byte)) % MODULUS;
+        b = (b + a) % MODULUS;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn test_encode_indexed() {
+        let palette = [[0, 0, 0], [255, 128, 0]];
+        let png = encode_indexed(2, 2, &palette, &[0, 1, 1, 0]).unwrap();
+        assert!(png.starts_with(&SIGNATURE));
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..26], &[0, 0, 0, 2, 0, 0, 0, 2, 8, 3]);
+        assert_eq!(&png[37..47], &[b'P', b'L', b'T', b'E', 0, 0, 0, 255, 128, 0]);
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]));
+
+        // One final stored block holding both filtered scanlines
+        let idat = &png[51..];
+        assert_eq!(&idat[4..8], b"IDAT");
+        assert_eq!(&idat[8..15], &[0x78, 0x01, 0x01, 6, 0, 0xF9, 0xFF]);
+        assert_eq!(&idat[15..21], &[0, 0, 1, 0, 1, 0]);
+
+        assert!(encode_indexed(2, 2, &palette, &[0; 3]).is_err());
+        assert!(encode_indexed(2, 2, &palette, &[0, 1, 2, 0]).is_err());
+        assert!(encode_indexed(0, 2, &palette, &[]).is_err());
+    }
+}